@@ -0,0 +1,147 @@
+//! Serves the DID Resolution HTTP(S) binding.
+//!
+//! ```text
+//! did-resolver [--listen ADDR] [--documents DIR] [--proxy METHOD=URL] [--cache-ttl SECS]
+//! ```
+//!
+//! Every `*.json` file in `--documents` is loaded as a `Document` and served
+//! for its method. `--proxy` forwards a method to another resolver such as
+//! the Universal Resolver and needs the `http-client` feature.
+
+extern crate did_doc as did;
+
+use did::resolver::{CachingResolver, InMemoryResolver, MethodRegistry, Server};
+use did::Document;
+use indexmap::IndexMap;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process;
+use std::time::Duration;
+
+const USAGE: &str =
+    "usage: did-resolver [--listen ADDR] [--documents DIR] [--proxy METHOD=URL] [--cache-ttl SECS]";
+
+struct Config {
+    listen: String,
+    documents: Vec<String>,
+    proxies: Vec<(String, String)>,
+    cache_ttl: Option<Duration>,
+}
+
+fn parse_args() -> Result<Config, String> {
+    let mut config = Config {
+        listen: "127.0.0.1:8080".to_owned(),
+        documents: Vec::new(),
+        proxies: Vec::new(),
+        cache_ttl: None,
+    };
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--listen" => config.listen = value()?,
+            "--documents" => config.documents.push(value()?),
+            "--proxy" => {
+                let proxy = value()?;
+                let mut parts = proxy.splitn(2, '=');
+                match (parts.next(), parts.next()) {
+                    (Some(method), Some(url)) => {
+                        config.proxies.push((method.to_owned(), url.to_owned()))
+                    }
+                    _ => return Err(format!("invalid proxy {:?}", proxy)),
+                }
+            }
+            "--cache-ttl" => {
+                let secs = value()?
+                    .parse::<u64>()
+                    .map_err(|e| format!("invalid cache ttl: {}", e))?;
+                config.cache_ttl = Some(Duration::from_secs(secs));
+            }
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            _ => return Err(format!("unknown argument {:?}", arg)),
+        }
+    }
+
+    Ok(config)
+}
+
+fn load_documents(
+    dir: &str,
+    resolvers: &mut IndexMap<String, InMemoryResolver>,
+) -> Result<(), String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("{}: {}", dir, e))?;
+    for entry in entries {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let document = load_document(&path)?;
+        let method = document.subject().as_uri().method.clone();
+        resolvers.entry(method).or_default().insert(document);
+    }
+    Ok(())
+}
+
+fn load_document(path: &Path) -> Result<Document, String> {
+    let json = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    serde_json::from_str(&json).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+#[cfg(feature = "http-client")]
+fn register_proxy(registry: &mut MethodRegistry, method: &str, url: &str) -> Result<(), String> {
+    registry.register(method, did::resolver::UniversalResolver::new(url));
+    Ok(())
+}
+
+#[cfg(not(feature = "http-client"))]
+fn register_proxy(_: &mut MethodRegistry, _: &str, _: &str) -> Result<(), String> {
+    Err("--proxy needs the http-client feature".to_owned())
+}
+
+fn run() -> Result<(), String> {
+    let config = parse_args()?;
+
+    let mut resolvers = IndexMap::new();
+    for dir in &config.documents {
+        load_documents(dir, &mut resolvers)?;
+    }
+
+    let mut registry = MethodRegistry::new();
+    for (method, resolver) in resolvers {
+        registry.register(&method, resolver);
+    }
+    for (method, url) in &config.proxies {
+        register_proxy(&mut registry, method, url)?;
+    }
+
+    let mut resolver = CachingResolver::new(registry);
+    if let Some(ttl) = config.cache_ttl {
+        resolver.set_default_ttl(ttl);
+        resolver.set_not_found_ttl(ttl);
+    }
+
+    let server = Server::bind(config.listen.as_str(), resolver).map_err(|e| e.to_string())?;
+    let methods = server.resolver().resolver().methods().collect::<Vec<_>>();
+    eprintln!(
+        "serving {} on {}",
+        methods.join(", "),
+        server
+            .local_addr()
+            .map(|a| a.to_string())
+            .unwrap_or_default()
+    );
+    server.run();
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("{}\n{}", e, USAGE);
+        process::exit(1);
+    }
+}
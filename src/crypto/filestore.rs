@@ -0,0 +1,304 @@
+use crate::crypto::keystore::sign_with;
+use crate::crypto::{KeyPair, KeyStore, Signer};
+use crate::error::{DidError, DidErrorExt, DidErrorKind};
+use crate::fields::{KeyAlgorithm, Multicodec};
+use crate::uri::Uri;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use indexmap::IndexMap;
+use serde_derive::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use zeroize::Zeroizing;
+
+const KDF: &str = "PBKDF2-HMAC-SHA256";
+const ITERATIONS: u32 = 600_000;
+const NONCE_LEN: usize = 12;
+/// Associated data of the empty message that tells whether the passphrase
+/// is right before any key is read.
+const CHECK: &[u8] = b"did_doc key store";
+
+#[derive(Serialize, Deserialize)]
+struct Contents {
+    kdf: String,
+    iterations: u32,
+    salt: String,
+    check: String,
+    keys: IndexMap<String, Entry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    #[serde(rename = "publicKeyMultibase")]
+    public_key: String,
+    #[serde(rename = "secretKey")]
+    secret_key: String,
+}
+
+/// A `KeyStore` in a JSON file, for keys that have to outlive the process.
+///
+/// Secret keys are encrypted with ChaCha20-Poly1305 under a key derived
+/// from a passphrase with PBKDF2-HMAC-SHA256, and bound to the id of their
+/// verification method. Ids and public keys are kept in the clear. Every
+/// change is written to the file straight away.
+pub struct FileKeyStore {
+    path: PathBuf,
+    cipher: ChaCha20Poly1305,
+    contents: Contents,
+}
+
+impl FileKeyStore {
+    /// The fewest PBKDF2 iterations a store may be created or opened with.
+    /// Far below the default, it only refuses stores whose passphrase is
+    /// barely stretched at all, e.g. because the file was tampered with.
+    pub const MIN_ITERATIONS: u32 = 10_000;
+
+    /// Opens the key store at `path`, or creates an empty one if there is no
+    /// file there yet.
+    pub fn open<P: AsRef<Path>>(path: P, passphrase: &str) -> Result<Self, DidError> {
+        FileKeyStore::open_with_iterations(path, passphrase, ITERATIONS)
+    }
+
+    /// Like `open`, with the number of PBKDF2 iterations to use if the store
+    /// is created. An existing store keeps the count it was created with.
+    /// Fails if either count is below `MIN_ITERATIONS`.
+    pub fn open_with_iterations<P: AsRef<Path>>(
+        path: P,
+        passphrase: &str,
+        iterations: u32,
+    ) -> Result<Self, DidError> {
+        check_iterations(iterations)?;
+        let path = path.as_ref().to_path_buf();
+        let contents = match fs::read(&path) {
+            Ok(bytes) => Some(serde_json::from_slice::<Contents>(&bytes).map_err(|e| {
+                DidError::from_msg(DidErrorKind::Io, format!("malformed key store: {}", e))
+            })?),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.to_did(DidErrorKind::Io, "reading key store")),
+        };
+
+        match contents {
+            Some(contents) => {
+                if contents.kdf != KDF {
+                    return Err(DidError::from_msg(
+                        DidErrorKind::Io,
+                        format!("unsupported key derivation {}", contents.kdf),
+                    ));
+                }
+                check_iterations(contents.iterations)?;
+                let salt = decode(&contents.salt)?;
+                let cipher = cipher(passphrase, &salt, contents.iterations);
+                open(&cipher, &contents.check, CHECK)
+                    .map_err(|_| DidError::from_kind(DidErrorKind::WrongPassphrase))?;
+                Ok(FileKeyStore {
+                    path,
+                    cipher,
+                    contents,
+                })
+            }
+            None => {
+                let salt = random(16)?;
+                let cipher = cipher(passphrase, &salt, iterations);
+                let contents = Contents {
+                    kdf: KDF.to_owned(),
+                    iterations,
+                    salt: URL_SAFE_NO_PAD.encode(&salt),
+                    check: seal(&cipher, b"", CHECK)?,
+                    keys: IndexMap::new(),
+                };
+                let store = FileKeyStore {
+                    path,
+                    cipher,
+                    contents,
+                };
+                store.save()?;
+                Ok(store)
+            }
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn len(&self) -> usize {
+        self.contents.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.contents.keys.is_empty()
+    }
+
+    /// Writes the store to a temporary file next to it and moves that into
+    /// place, so that a crash never leaves half a store behind.
+    fn save(&self) -> Result<(), DidError> {
+        let json = serde_json::to_vec_pretty(&self.contents)
+            .map_err(|e| DidError::from_msg(DidErrorKind::Io, e.to_string()))?;
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        options
+            .open(&tmp)
+            .and_then(|mut file| {
+                file.write_all(&json)?;
+                file.sync_all()
+            })
+            .and_then(|_| fs::rename(&tmp, &self.path))
+            .map_err(|e| e.to_did(DidErrorKind::Io, "writing key store"))
+    }
+}
+
+impl KeyStore for FileKeyStore {
+    fn get(&self, key_id: &Uri) -> Result<Option<KeyPair>, DidError> {
+        let id = key_id.to_string();
+        let entry = match self.contents.keys.get(&id) {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+
+        let corrupt = || {
+            DidError::from_msg(
+                DidErrorKind::InvalidSecretKey,
+                format!("corrupt key store entry {}", id),
+            )
+        };
+        let (_, public_key) = multibase::decode(&entry.public_key).map_err(|_| corrupt())?;
+        let (codec, public_key) = Multicodec::decode(&public_key).ok_or_else(corrupt)?;
+        let secret_key = open(&self.cipher, &entry.secret_key, id.as_bytes())?;
+        let key = KeyPair::from_secret_key(KeyAlgorithm::from_multicodec(codec), &secret_key)?;
+        if key.public_key_bytes() != public_key {
+            return Err(corrupt());
+        }
+        Ok(Some(key))
+    }
+
+    fn insert(&mut self, key_id: &Uri, key: KeyPair) -> Result<(), DidError> {
+        let id = key_id.to_string();
+        let entry = Entry {
+            public_key: key.fingerprint(),
+            secret_key: seal(&self.cipher, &key.secret_key(), id.as_bytes())?,
+        };
+        self.contents.keys.insert(id, entry);
+        self.save()
+    }
+
+    fn remove(&mut self, key_id: &Uri) -> Result<bool, DidError> {
+        if self
+            .contents
+            .keys
+            .shift_remove(&key_id.to_string())
+            .is_none()
+        {
+            return Ok(false);
+        }
+        self.save()?;
+        Ok(true)
+    }
+
+    fn key_ids(&self) -> Result<Vec<Uri>, DidError> {
+        self.contents
+            .keys
+            .keys()
+            .map(|k| Uri::from_str(k))
+            .collect()
+    }
+}
+
+impl Signer for FileKeyStore {
+    fn sign(&self, key_id: &Uri, message: &[u8]) -> Result<Vec<u8>, DidError> {
+        sign_with(self, key_id, message)
+    }
+}
+
+fn check_iterations(iterations: u32) -> Result<(), DidError> {
+    if iterations < FileKeyStore::MIN_ITERATIONS {
+        return Err(DidError::from_msg(
+            DidErrorKind::Io,
+            format!(
+                "{} PBKDF2 iterations are too few, at least {} are needed",
+                iterations,
+                FileKeyStore::MIN_ITERATIONS
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn cipher(passphrase: &str, salt: &[u8], iterations: u32) -> ChaCha20Poly1305 {
+    let key = Zeroizing::new(pbkdf2::pbkdf2_hmac_array::<Sha256, 32>(
+        passphrase.as_bytes(),
+        salt,
+        iterations,
+    ));
+    ChaCha20Poly1305::new(key.as_ref().into())
+}
+
+/// Encrypts `plaintext` under a fresh nonce, giving the nonce followed by
+/// the ciphertext.
+fn seal(cipher: &ChaCha20Poly1305, plaintext: &[u8], aad: &[u8]) -> Result<String, DidError> {
+    let mut out = random(NONCE_LEN)?;
+    let ciphertext = cipher
+        .encrypt(
+            &nonce(&out),
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .map_err(|_| DidError::from_msg(DidErrorKind::Io, "encryption failed"))?;
+    out.extend(ciphertext);
+    Ok(URL_SAFE_NO_PAD.encode(out))
+}
+
+fn open(
+    cipher: &ChaCha20Poly1305,
+    sealed: &str,
+    aad: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, DidError> {
+    let sealed = decode(sealed)?;
+    if sealed.len() < NONCE_LEN {
+        return Err(DidError::from_kind(DidErrorKind::InvalidSecretKey));
+    }
+    let (prefix, ciphertext) = sealed.split_at(NONCE_LEN);
+    cipher
+        .decrypt(
+            &nonce(prefix),
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .map(Zeroizing::new)
+        .map_err(|_| DidError::from_kind(DidErrorKind::InvalidSecretKey))
+}
+
+fn nonce(bytes: &[u8]) -> Nonce {
+    let mut nonce = [0u8; NONCE_LEN];
+    nonce.copy_from_slice(&bytes[..NONCE_LEN]);
+    Nonce::from(nonce)
+}
+
+fn decode(value: &str) -> Result<Vec<u8>, DidError> {
+    URL_SAFE_NO_PAD
+        .decode(value)
+        .map_err(|e| DidError::from_msg(DidErrorKind::Io, format!("malformed key store: {}", e)))
+}
+
+fn random(len: usize) -> Result<Vec<u8>, DidError> {
+    let mut out = vec![0u8; len];
+    getrandom::getrandom(&mut out)
+        .map_err(|e| DidError::from_msg(DidErrorKind::Io, format!("no random numbers: {}", e)))?;
+    Ok(out)
+}
@@ -0,0 +1,265 @@
+use crate::doc::Document;
+use crate::error::{DidError, DidErrorKind};
+use crate::fields::{KeyAlgorithm, PublicKey, PublicKeyEncoding, PublicKeyType};
+use std::fmt;
+use zeroize::Zeroizing;
+
+const DID_V1: &str = "https://www.w3.org/ns/did/v1";
+
+/// A generated or imported key pair.
+///
+/// Secret keys are 32 bytes for every supported algorithm: the seed for
+/// Ed25519, the big endian scalar for secp256k1 and P-256 and the scalar for
+/// X25519. They are wiped from memory when dropped.
+#[derive(Clone)]
+pub struct KeyPair {
+    algorithm: KeyAlgorithm,
+    public_key: Vec<u8>,
+    secret_key: Zeroizing<Vec<u8>>,
+}
+
+impl KeyPair {
+    /// Generates a key pair with the operating system's random number
+    /// generator.
+    pub fn generate(algorithm: KeyAlgorithm) -> Result<KeyPair, DidError> {
+        loop {
+            let mut secret_key = Zeroizing::new(vec![0u8; 32]);
+            getrandom::getrandom(&mut secret_key).map_err(|e| {
+                DidError::from_msg(DidErrorKind::Io, format!("no random numbers: {}", e))
+            })?;
+            match KeyPair::from_secret_key(algorithm, &secret_key) {
+                // the odd scalar that is out of range for the curve
+                Err(ref e) if e.kind() == DidErrorKind::InvalidSecretKey => continue,
+                result => return result,
+            }
+        }
+    }
+
+    pub fn from_secret_key(
+        algorithm: KeyAlgorithm,
+        secret_key: &[u8],
+    ) -> Result<KeyPair, DidError> {
+        if secret_key.len() != 32 {
+            return Err(DidError::from_msg(
+                DidErrorKind::InvalidSecretKey,
+                format!("{} bytes instead of 32", secret_key.len()),
+            ));
+        }
+        let public_key = match algorithm {
+            KeyAlgorithm::Ed25519 => ed25519(secret_key)?,
+            KeyAlgorithm::X25519 => x25519(secret_key)?,
+            KeyAlgorithm::Secp256k1 => secp256k1(secret_key)?,
+            KeyAlgorithm::P256 => p256(secret_key)?,
+            _ => return Err(unsupported(algorithm, "")),
+        };
+        Ok(KeyPair {
+            algorithm,
+            public_key,
+            secret_key: Zeroizing::new(secret_key.to_vec()),
+        })
+    }
+
+    pub fn algorithm(&self) -> KeyAlgorithm {
+        self.algorithm
+    }
+
+    /// The raw public key, with elliptic curve points compressed.
+    pub fn public_key_bytes(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    pub fn secret_key(&self) -> Zeroizing<Vec<u8>> {
+        self.secret_key.clone()
+    }
+
+    /// The public key in `encoding`, typed as the verification method that
+    /// suits it: `JsonWebKey2020` for JWK, the 2020 types for multibase
+    /// Ed25519 and X25519 keys and the 2018 and 2019 types otherwise.
+    /// Encodings that type does not allow, e.g. hex for Ed25519, fail with
+    /// `UnsupportedConversion`.
+    pub fn public_key(
+        &self,
+        id: &str,
+        controller: &str,
+        encoding: PublicKeyEncoding,
+    ) -> Result<PublicKey, DidError> {
+        let key_type = match (self.algorithm, encoding) {
+            (_, PublicKeyEncoding::Jwk) => PublicKeyType::JsonWebKey2020,
+            (KeyAlgorithm::Ed25519, PublicKeyEncoding::Multibase) => {
+                PublicKeyType::Ed25519VerificationKey2020
+            }
+            (KeyAlgorithm::Ed25519, _) => PublicKeyType::Ed25519VerificationKey2018,
+            (KeyAlgorithm::X25519, PublicKeyEncoding::Multibase) => {
+                PublicKeyType::X25519KeyAgreementKey2020
+            }
+            (KeyAlgorithm::X25519, _) => PublicKeyType::X25519KeyAgreementKey2019,
+            (KeyAlgorithm::Secp256k1, PublicKeyEncoding::EthereumAddress) => {
+                PublicKeyType::EcdsaSecp256k1RecoveryMethod2020
+            }
+            (KeyAlgorithm::Secp256k1, _) => PublicKeyType::EcdsaSecp256k1VerificationKey2019,
+            (KeyAlgorithm::P256, _) => PublicKeyType::EcdsaSecp256r1VerificationKey2019,
+            _ => PublicKeyType::UnknownKey,
+        };
+        if !key_type.permits(encoding) {
+            return Err(DidError::from_msg(
+                DidErrorKind::UnsupportedConversion {
+                    from: PublicKeyEncoding::Multibase.field_name().to_owned(),
+                    to: encoding.field_name().to_owned(),
+                },
+                format!("{} keys cannot be in {}", key_type, encoding.field_name()),
+            ));
+        }
+        PublicKey::new(
+            id,
+            key_type,
+            controller,
+            PublicKeyEncoding::Multibase,
+            &self.fingerprint(),
+        )?
+        .to_encoding(encoding)
+    }
+
+    /// The document of the did:key DID for the public key.
+    ///
+    /// The key, identified by its fingerprint, is embedded in `publicKey`
+    /// and referenced from `authentication`, and Ed25519 keys get their
+    /// X25519 counterpart in `keyAgreement`. X25519 keys only go in
+    /// `keyAgreement`.
+    pub fn did_key(&self, encoding: PublicKeyEncoding) -> Result<Document, DidError> {
+        self.document("did:key:", encoding)
+    }
+
+    /// The document of the did:peer DID for the public key, using method
+    /// 0 of the peer DID specification which inlines the key the way
+    /// did:key does.
+    pub fn did_peer(&self, encoding: PublicKeyEncoding) -> Result<Document, DidError> {
+        self.document("did:peer:0", encoding)
+    }
+
+    fn document(&self, prefix: &str, encoding: PublicKeyEncoding) -> Result<Document, DidError> {
+        let fingerprint = self.fingerprint();
+        let did = format!("{}{}", prefix, fingerprint);
+        let key = self.public_key(&format!("{}#{}", did, fingerprint), &did, encoding)?;
+
+        let mut doc = Document::new(DID_V1, &did);
+        if self.algorithm == KeyAlgorithm::X25519 {
+            doc.add_key_agreement(key);
+        } else {
+            doc.add_authentication(PublicKey::reference_to(key.subject().as_uri()));
+            doc.add_public_key(key);
+            doc.derive_key_agreement()?;
+        }
+        Ok(doc)
+    }
+
+    /// The multibase encoded multicodec public key, as `PublicKey::fingerprint`
+    /// gives it.
+    pub fn fingerprint(&self) -> String {
+        multibase::encode(
+            multibase::Base::Base58Btc,
+            self.algorithm.multicodec().encode(&self.public_key),
+        )
+    }
+}
+
+impl fmt::Debug for KeyPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyPair")
+            .field("algorithm", &self.algorithm)
+            .field("public_key", &hex::encode(&self.public_key))
+            .finish_non_exhaustive()
+    }
+}
+
+fn unsupported(algorithm: KeyAlgorithm, msg: &'static str) -> DidError {
+    DidError::from_msg(
+        DidErrorKind::UnsupportedKeyAlgorithm {
+            algorithm: format!("{:?}", algorithm),
+        },
+        msg,
+    )
+}
+
+// unused when no algorithm feature is enabled
+#[allow(dead_code)]
+fn invalid() -> DidError {
+    DidError::from_kind(DidErrorKind::InvalidSecretKey)
+}
+
+#[cfg_attr(not(feature = "ed25519"), allow(unused_variables))]
+fn ed25519(secret_key: &[u8]) -> Result<Vec<u8>, DidError> {
+    #[cfg(feature = "ed25519")]
+    {
+        use ed25519_dalek::SigningKey;
+        use std::convert::TryInto;
+
+        let seed: &[u8; 32] = secret_key.try_into().map_err(|_| invalid())?;
+        Ok(SigningKey::from_bytes(seed)
+            .verifying_key()
+            .to_bytes()
+            .to_vec())
+    }
+    #[cfg(not(feature = "ed25519"))]
+    Err(unsupported(
+        KeyAlgorithm::Ed25519,
+        "enable the ed25519 cargo feature",
+    ))
+}
+
+#[cfg_attr(not(feature = "x25519"), allow(unused_variables))]
+fn x25519(secret_key: &[u8]) -> Result<Vec<u8>, DidError> {
+    #[cfg(feature = "x25519")]
+    {
+        use std::convert::TryInto;
+        use x25519_dalek::{PublicKey, StaticSecret};
+
+        let scalar: [u8; 32] = secret_key.try_into().map_err(|_| invalid())?;
+        let secret = StaticSecret::from(scalar);
+        Ok(PublicKey::from(&secret).as_bytes().to_vec())
+    }
+    #[cfg(not(feature = "x25519"))]
+    Err(unsupported(
+        KeyAlgorithm::X25519,
+        "enable the x25519 cargo feature",
+    ))
+}
+
+#[cfg_attr(not(feature = "secp256k1"), allow(unused_variables))]
+fn secp256k1(secret_key: &[u8]) -> Result<Vec<u8>, DidError> {
+    #[cfg(feature = "secp256k1")]
+    {
+        use k256::elliptic_curve::sec1::ToEncodedPoint;
+
+        let secret = k256::SecretKey::from_slice(secret_key).map_err(|_| invalid())?;
+        Ok(secret
+            .public_key()
+            .to_encoded_point(true)
+            .as_bytes()
+            .to_vec())
+    }
+    #[cfg(not(feature = "secp256k1"))]
+    Err(unsupported(
+        KeyAlgorithm::Secp256k1,
+        "enable the secp256k1 cargo feature",
+    ))
+}
+
+#[cfg_attr(not(feature = "p256"), allow(unused_variables))]
+fn p256(secret_key: &[u8]) -> Result<Vec<u8>, DidError> {
+    #[cfg(feature = "p256")]
+    {
+        use p256::elliptic_curve::sec1::ToEncodedPoint;
+
+        let secret = p256::SecretKey::from_slice(secret_key).map_err(|_| invalid())?;
+        Ok(secret
+            .public_key()
+            .to_encoded_point(true)
+            .as_bytes()
+            .to_vec())
+    }
+    #[cfg(not(feature = "p256"))]
+    Err(unsupported(
+        KeyAlgorithm::P256,
+        "enable the p256 cargo feature",
+    ))
+}
@@ -0,0 +1,76 @@
+use crate::crypto::{KeyPair, Signer};
+use crate::error::{err_msg, DidError, DidErrorKind};
+use crate::uri::Uri;
+use indexmap::IndexMap;
+use std::str::FromStr;
+
+/// Keeps key pairs by the id of the verification method they belong to.
+///
+/// Every store is also a `Signer` for the keys it holds.
+pub trait KeyStore: Send + Sync {
+    fn get(&self, key_id: &Uri) -> Result<Option<KeyPair>, DidError>;
+
+    /// Adds a key pair, replacing any kept for the same verification method.
+    fn insert(&mut self, key_id: &Uri, key: KeyPair) -> Result<(), DidError>;
+
+    fn remove(&mut self, key_id: &Uri) -> Result<bool, DidError>;
+
+    fn key_ids(&self) -> Result<Vec<Uri>, DidError>;
+}
+
+/// Signs with the key pair `store` keeps for `key_id`.
+pub(crate) fn sign_with<K: KeyStore + ?Sized>(
+    store: &K,
+    key_id: &Uri,
+    message: &[u8],
+) -> Result<Vec<u8>, DidError> {
+    match store.get(key_id)? {
+        Some(key) => key.sign(message),
+        None => Err(err_msg(DidErrorKind::MissingSecretKey, key_id.to_string())),
+    }
+}
+
+/// A `KeyStore` that keeps its keys in memory for the life of the process.
+#[derive(Default, Debug)]
+pub struct InMemoryKeyStore {
+    keys: IndexMap<String, KeyPair>,
+}
+
+impl InMemoryKeyStore {
+    pub fn new() -> Self {
+        InMemoryKeyStore::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+impl KeyStore for InMemoryKeyStore {
+    fn get(&self, key_id: &Uri) -> Result<Option<KeyPair>, DidError> {
+        Ok(self.keys.get(&key_id.to_string()).cloned())
+    }
+
+    fn insert(&mut self, key_id: &Uri, key: KeyPair) -> Result<(), DidError> {
+        self.keys.insert(key_id.to_string(), key);
+        Ok(())
+    }
+
+    fn remove(&mut self, key_id: &Uri) -> Result<bool, DidError> {
+        Ok(self.keys.shift_remove(&key_id.to_string()).is_some())
+    }
+
+    fn key_ids(&self) -> Result<Vec<Uri>, DidError> {
+        self.keys.keys().map(|k| Uri::from_str(k)).collect()
+    }
+}
+
+impl Signer for InMemoryKeyStore {
+    fn sign(&self, key_id: &Uri, message: &[u8]) -> Result<Vec<u8>, DidError> {
+        sign_with(self, key_id, message)
+    }
+}
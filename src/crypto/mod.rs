@@ -0,0 +1,22 @@
+//! Cryptographic operations with the keys found in a `Document`. Every
+//! signature algorithm sits behind a cargo feature of the same name:
+//! `ed25519`, `secp256k1`, `p256` and `rsa`, and generating X25519 key
+//! agreement keys needs the `x25519` feature.
+//!
+//! Private keys are reached through a `Signer`, so that they can stay in a
+//! `KeyStore` or outside the process altogether. `FileKeyStore` needs the
+//! `file-keystore` feature.
+
+#[cfg(feature = "file-keystore")]
+pub use self::filestore::FileKeyStore;
+pub use self::keygen::KeyPair;
+pub use self::keystore::{InMemoryKeyStore, KeyStore};
+pub use self::signer::{sign, Signer};
+pub use self::verify::SignatureAlgorithm;
+
+#[cfg(feature = "file-keystore")]
+mod filestore;
+mod keygen;
+mod keystore;
+mod signer;
+mod verify;
@@ -0,0 +1,115 @@
+use crate::crypto::verify::unsupported;
+use crate::crypto::{KeyPair, SignatureAlgorithm};
+use crate::doc::{Document, Resource};
+use crate::error::{err_msg, DidError, DidErrorKind};
+use crate::fields::KeyAlgorithm;
+use crate::uri::Uri;
+
+/// Signs with the private key of a verification method, wherever that key
+/// is kept: a `KeyStore`, or an HSM that never hands it out.
+pub trait Signer: Send + Sync {
+    /// Signs `message` with the key of the verification method `key_id`,
+    /// producing a signature `PublicKey::verify` accepts for that method.
+    fn sign(&self, key_id: &Uri, message: &[u8]) -> Result<Vec<u8>, DidError>;
+}
+
+impl<S: Signer + ?Sized> Signer for &S {
+    fn sign(&self, key_id: &Uri, message: &[u8]) -> Result<Vec<u8>, DidError> {
+        (**self).sign(key_id, message)
+    }
+}
+
+impl<S: Signer + ?Sized> Signer for Box<S> {
+    fn sign(&self, key_id: &Uri, message: &[u8]) -> Result<Vec<u8>, DidError> {
+        (**self).sign(key_id, message)
+    }
+}
+
+/// Signs `message` through `signer` with one of the keys of `doc`.
+/// `key_id` may be relative to the document, and is handed to the signer
+/// in its absolute form.
+pub fn sign<S: Signer + ?Sized>(
+    doc: &Document,
+    signer: &S,
+    key_id: &Uri,
+    message: &[u8],
+) -> Result<Vec<u8>, DidError> {
+    match doc.dereference(key_id) {
+        Some(Resource::PublicKey(_)) => {
+            signer.sign(&key_id.to_absolute(doc.subject().as_uri()), message)
+        }
+        _ => Err(err_msg(DidErrorKind::DanglingReference, key_id.to_string())),
+    }
+}
+
+impl KeyPair {
+    /// Signs `message` with the default signature algorithm of the key,
+    /// giving ECDSA signatures as fixed size `r || s`.
+    pub fn sign(&self, message: &[u8]) -> Result<Vec<u8>, DidError> {
+        let algorithm = SignatureAlgorithm::default_for(self.algorithm()).ok_or_else(|| {
+            DidError::from_kind(DidErrorKind::UnsupportedAlgorithm {
+                algorithm: format!("{:?}", self.algorithm()),
+            })
+        })?;
+        let secret_key = self.secret_key();
+        match self.algorithm() {
+            KeyAlgorithm::Ed25519 => ed25519(&secret_key, message),
+            KeyAlgorithm::Secp256k1 => secp256k1(&secret_key, message),
+            KeyAlgorithm::P256 => p256(&secret_key, message),
+            _ => Err(unsupported(algorithm)),
+        }
+    }
+}
+
+// unused when no algorithm feature is enabled
+#[allow(dead_code)]
+fn invalid() -> DidError {
+    DidError::from_kind(DidErrorKind::InvalidSecretKey)
+}
+
+#[cfg_attr(not(feature = "ed25519"), allow(unused_variables))]
+fn ed25519(secret_key: &[u8], message: &[u8]) -> Result<Vec<u8>, DidError> {
+    #[cfg(feature = "ed25519")]
+    {
+        use ed25519_dalek::{Signer as _, SigningKey};
+        use std::convert::TryInto;
+
+        let seed: &[u8; 32] = secret_key.try_into().map_err(|_| invalid())?;
+        Ok(SigningKey::from_bytes(seed)
+            .sign(message)
+            .to_bytes()
+            .to_vec())
+    }
+    #[cfg(not(feature = "ed25519"))]
+    Err(unsupported(SignatureAlgorithm::EdDsa))
+}
+
+#[cfg_attr(not(feature = "secp256k1"), allow(unused_variables))]
+fn secp256k1(secret_key: &[u8], message: &[u8]) -> Result<Vec<u8>, DidError> {
+    #[cfg(feature = "secp256k1")]
+    {
+        use k256::ecdsa::signature::Signer as _;
+        use k256::ecdsa::{Signature, SigningKey};
+
+        let key = SigningKey::from_slice(secret_key).map_err(|_| invalid())?;
+        let signature: Signature = key.sign(message);
+        Ok(signature.to_bytes().to_vec())
+    }
+    #[cfg(not(feature = "secp256k1"))]
+    Err(unsupported(SignatureAlgorithm::Es256K))
+}
+
+#[cfg_attr(not(feature = "p256"), allow(unused_variables))]
+fn p256(secret_key: &[u8], message: &[u8]) -> Result<Vec<u8>, DidError> {
+    #[cfg(feature = "p256")]
+    {
+        use p256::ecdsa::signature::Signer as _;
+        use p256::ecdsa::{Signature, SigningKey};
+
+        let key = SigningKey::from_slice(secret_key).map_err(|_| invalid())?;
+        let signature: Signature = key.sign(message);
+        Ok(signature.to_bytes().to_vec())
+    }
+    #[cfg(not(feature = "p256"))]
+    Err(unsupported(SignatureAlgorithm::Es256))
+}
@@ -0,0 +1,239 @@
+use crate::error::{DidError, DidErrorKind};
+use crate::fields::{KeyAlgorithm, PublicKey};
+
+/// The signature algorithms `PublicKey` can verify, by their JOSE names.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SignatureAlgorithm {
+    /// Ed25519 (RFC 8032).
+    EdDsa,
+    /// ECDSA over secp256k1 with SHA-256.
+    Es256K,
+    /// ECDSA over P-256 with SHA-256.
+    Es256,
+    /// RSASSA-PKCS1-v1_5 with SHA-256.
+    Rs256,
+    /// RSASSA-PSS with SHA-256 and MGF1 with SHA-256.
+    Ps256,
+}
+
+impl SignatureAlgorithm {
+    pub fn name(self) -> &'static str {
+        match self {
+            SignatureAlgorithm::EdDsa => "EdDSA",
+            SignatureAlgorithm::Es256K => "ES256K",
+            SignatureAlgorithm::Es256 => "ES256",
+            SignatureAlgorithm::Rs256 => "RS256",
+            SignatureAlgorithm::Ps256 => "PS256",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "EdDSA" => Some(SignatureAlgorithm::EdDsa),
+            "ES256K" => Some(SignatureAlgorithm::Es256K),
+            "ES256" => Some(SignatureAlgorithm::Es256),
+            "RS256" => Some(SignatureAlgorithm::Rs256),
+            "PS256" => Some(SignatureAlgorithm::Ps256),
+            _ => None,
+        }
+    }
+
+    /// The algorithm keys of `algorithm` are assumed to sign with when
+    /// nothing else says otherwise.
+    pub fn default_for(algorithm: KeyAlgorithm) -> Option<Self> {
+        match algorithm {
+            KeyAlgorithm::Ed25519 => Some(SignatureAlgorithm::EdDsa),
+            KeyAlgorithm::Secp256k1 => Some(SignatureAlgorithm::Es256K),
+            KeyAlgorithm::P256 => Some(SignatureAlgorithm::Es256),
+            KeyAlgorithm::Rsa => Some(SignatureAlgorithm::Rs256),
+            _ => None,
+        }
+    }
+
+    /// The kind of key that makes signatures of this algorithm.
+    pub fn key_algorithm(self) -> KeyAlgorithm {
+        match self {
+            SignatureAlgorithm::EdDsa => KeyAlgorithm::Ed25519,
+            SignatureAlgorithm::Es256K => KeyAlgorithm::Secp256k1,
+            SignatureAlgorithm::Es256 => KeyAlgorithm::P256,
+            SignatureAlgorithm::Rs256 | SignatureAlgorithm::Ps256 => KeyAlgorithm::Rsa,
+        }
+    }
+}
+
+impl PublicKey {
+    /// Verifies `signature` over `message` with the algorithm named by the
+    /// `alg` of a JWK key, or else the default for the key's algorithm.
+    /// RSA keys without an `alg` are taken to use RS256; use `verify_with`
+    /// for PS256 signatures.
+    ///
+    /// ECDSA signatures may be fixed size `r || s` or DER encoded.
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<(), DidError> {
+        let named = self
+            .jwk()
+            .and_then(|jwk| jwk.alg.as_deref())
+            .and_then(SignatureAlgorithm::from_name);
+        let algorithm = named
+            .or_else(|| self.algorithm().and_then(SignatureAlgorithm::default_for))
+            .ok_or_else(|| {
+                DidError::from_kind(DidErrorKind::UnsupportedAlgorithm {
+                    algorithm: self.key_type().to_string(),
+                })
+            })?;
+        self.verify_with(algorithm, message, signature)
+    }
+
+    /// Verifies `signature` over `message` with a specific algorithm, which
+    /// has to match the algorithm of the key.
+    pub fn verify_with(
+        &self,
+        algorithm: SignatureAlgorithm,
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<(), DidError> {
+        if self.algorithm() != Some(algorithm.key_algorithm()) {
+            return Err(DidError::from_msg(
+                DidErrorKind::UnsupportedAlgorithm {
+                    algorithm: algorithm.name().to_owned(),
+                },
+                format!(
+                    "{} is not a {:?} key",
+                    self.subject().as_uri(),
+                    algorithm.key_algorithm()
+                ),
+            ));
+        }
+
+        let key = self.key_bytes()?;
+        let malformed = || {
+            DidError::from_kind(DidErrorKind::MalformedKeyData {
+                encoding: self.encoding().field_name().to_owned(),
+            })
+        };
+        match algorithm {
+            SignatureAlgorithm::EdDsa => ed25519(&key, message, signature, malformed),
+            SignatureAlgorithm::Es256K => secp256k1(&key, message, signature, malformed),
+            SignatureAlgorithm::Es256 => p256(&key, message, signature, malformed),
+            SignatureAlgorithm::Rs256 | SignatureAlgorithm::Ps256 => {
+                rsa(algorithm, &key, message, signature, malformed)
+            }
+        }
+    }
+}
+
+// unused when every algorithm feature is enabled
+#[allow(dead_code)]
+pub(super) fn unsupported(algorithm: SignatureAlgorithm) -> DidError {
+    DidError::from_msg(
+        DidErrorKind::UnsupportedAlgorithm {
+            algorithm: algorithm.name().to_owned(),
+        },
+        "enable the matching cargo feature",
+    )
+}
+
+// unused when no algorithm feature is enabled
+#[allow(dead_code)]
+fn invalid() -> DidError {
+    DidError::from_kind(DidErrorKind::InvalidSignature)
+}
+
+#[cfg_attr(not(feature = "ed25519"), allow(unused_variables))]
+fn ed25519<F>(key: &[u8], message: &[u8], signature: &[u8], malformed: F) -> Result<(), DidError>
+where
+    F: Fn() -> DidError,
+{
+    #[cfg(feature = "ed25519")]
+    {
+        use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+        use std::convert::TryInto;
+
+        let key: &[u8; 32] = key.try_into().map_err(|_| malformed())?;
+        let key = VerifyingKey::from_bytes(key).map_err(|_| malformed())?;
+        let signature = Signature::from_slice(signature).map_err(|_| invalid())?;
+        key.verify(message, &signature).map_err(|_| invalid())
+    }
+    #[cfg(not(feature = "ed25519"))]
+    Err(unsupported(SignatureAlgorithm::EdDsa))
+}
+
+#[cfg_attr(not(feature = "secp256k1"), allow(unused_variables))]
+fn secp256k1<F>(key: &[u8], message: &[u8], signature: &[u8], malformed: F) -> Result<(), DidError>
+where
+    F: Fn() -> DidError,
+{
+    #[cfg(feature = "secp256k1")]
+    {
+        use k256::ecdsa::signature::Verifier;
+        use k256::ecdsa::{Signature, VerifyingKey};
+
+        let key = VerifyingKey::from_sec1_bytes(key).map_err(|_| malformed())?;
+        let signature = Signature::from_slice(signature)
+            .or_else(|_| Signature::from_der(signature))
+            .map_err(|_| invalid())?;
+        // a high s is the malleable twin of a valid signature, refuse it
+        // like k256 and bitcoin do
+        if signature.normalize_s().is_some() {
+            return Err(invalid());
+        }
+        key.verify(message, &signature).map_err(|_| invalid())
+    }
+    #[cfg(not(feature = "secp256k1"))]
+    Err(unsupported(SignatureAlgorithm::Es256K))
+}
+
+#[cfg_attr(not(feature = "p256"), allow(unused_variables))]
+fn p256<F>(key: &[u8], message: &[u8], signature: &[u8], malformed: F) -> Result<(), DidError>
+where
+    F: Fn() -> DidError,
+{
+    #[cfg(feature = "p256")]
+    {
+        use p256::ecdsa::signature::Verifier;
+        use p256::ecdsa::{Signature, VerifyingKey};
+
+        let key = VerifyingKey::from_sec1_bytes(key).map_err(|_| malformed())?;
+        let signature = Signature::from_slice(signature)
+            .or_else(|_| Signature::from_der(signature))
+            .map_err(|_| invalid())?;
+        key.verify(message, &signature).map_err(|_| invalid())
+    }
+    #[cfg(not(feature = "p256"))]
+    Err(unsupported(SignatureAlgorithm::Es256))
+}
+
+#[cfg_attr(not(feature = "rsa"), allow(unused_variables))]
+fn rsa<F>(
+    algorithm: SignatureAlgorithm,
+    key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+    malformed: F,
+) -> Result<(), DidError>
+where
+    F: Fn() -> DidError,
+{
+    #[cfg(feature = "rsa")]
+    {
+        use rsa::pkcs1::DecodeRsaPublicKey;
+        use rsa::signature::Verifier;
+        use rsa::{pkcs1v15, pss, RsaPublicKey};
+        use sha2::Sha256;
+        use std::convert::TryFrom;
+
+        let key = RsaPublicKey::from_pkcs1_der(key).map_err(|_| malformed())?;
+        if algorithm == SignatureAlgorithm::Ps256 {
+            let signature = pss::Signature::try_from(signature).map_err(|_| invalid())?;
+            pss::VerifyingKey::<Sha256>::new(key)
+                .verify(message, &signature)
+                .map_err(|_| invalid())
+        } else {
+            let signature = pkcs1v15::Signature::try_from(signature).map_err(|_| invalid())?;
+            pkcs1v15::VerifyingKey::<Sha256>::new(key)
+                .verify(message, &signature)
+                .map_err(|_| invalid())
+        }
+    }
+    #[cfg(not(feature = "rsa"))]
+    Err(unsupported(algorithm))
+}
@@ -1,14 +1,23 @@
-use crate::fields::{string_or_list, Context, PublicKey, ServiceEndpoint, Subject};
+use crate::error::{err_msg, DidError, DidErrorKind};
+use crate::fields::{
+    Context, ContextEntry, DidCommEndpoint, KeyAlgorithm, PublicKey, ServiceEndpoint, Subject,
+};
+use crate::jsonld::{self, ContextLoader};
+use crate::uri::Uri;
+use crate::validate::{self, Diagnostic};
 use indexmap::IndexMap;
+use percent_encoding::percent_decode_str;
 use serde_derive::{Deserialize, Serialize};
 use serde_json::{self, Value};
+use std::fmt;
 use std::str::FromStr;
-use std::string::{String, ToString};
+use std::string::String;
+use url::Url;
 use void::Void;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Document {
-    #[serde(rename = "@context", deserialize_with = "string_or_list")]
+    #[serde(rename = "@context")]
     context: Context,
     id: Subject,
     #[serde(skip_serializing_if = "String::is_empty", default)]
@@ -19,6 +28,12 @@ pub struct Document {
     public_key: Vec<PublicKey>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     authentication: Vec<PublicKey>,
+    #[serde(
+        rename = "keyAgreement",
+        skip_serializing_if = "Vec::is_empty",
+        default
+    )]
+    key_agreement: Vec<PublicKey>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     service: Vec<ServiceEndpoint>,
     //#[serde(skip_serializing_if = "Proof::is_empty", default)]
@@ -27,6 +42,13 @@ pub struct Document {
     pub extra: IndexMap<String, Value>,
 }
 
+/// Something embedded in a `Document` that a DID URL can point at.
+#[derive(Debug, Clone, Copy)]
+pub enum Resource<'a> {
+    PublicKey(&'a PublicKey),
+    Service(&'a ServiceEndpoint),
+}
+
 impl Document {
     pub fn new(context: &str, id: &str) -> Self {
         Document {
@@ -36,13 +58,23 @@ impl Document {
             updated: String::new(),
             public_key: Vec::default(),
             authentication: Vec::default(),
+            key_agreement: Vec::default(),
             service: Vec::default(),
             extra: IndexMap::default(),
         }
     }
 
-    pub fn context(&self) -> &Vec<String> {
-        &self.context.as_vec()
+    pub fn context(&self) -> &Vec<ContextEntry> {
+        self.context.as_vec()
+    }
+
+    pub(crate) fn contexts(&self) -> &Context {
+        &self.context
+    }
+
+    /// Appends `entry` to `@context` unless it is there already.
+    pub fn add_context<E: Into<ContextEntry>>(&mut self, entry: E) {
+        self.context.push(entry.into());
     }
 
     pub fn subject(&self) -> &Subject {
@@ -57,14 +89,227 @@ impl Document {
         &self.authentication
     }
 
+    pub fn key_agreement(&self) -> &Vec<PublicKey> {
+        &self.key_agreement
+    }
+
     pub fn service(&self) -> &Vec<ServiceEndpoint> {
         &self.service
     }
+
+    /// The services of type `service_type`, in document order.
+    pub fn services_by_type(&self, service_type: &str) -> Vec<&ServiceEndpoint> {
+        self.service
+            .iter()
+            .filter(|s| s.kind() == service_type)
+            .collect()
+    }
+
+    /// Finds the service with id `id`, which may be relative to the
+    /// document, e.g. `#files`.
+    pub fn service_by_id(&self, id: &str) -> Option<&ServiceEndpoint> {
+        match self.dereference(&Uri::from_str(id).ok()?) {
+            Some(Resource::Service(service)) => Some(service),
+            _ => None,
+        }
+    }
+
+    /// The origins of all `LinkedDomains` services, in document order.
+    pub fn linked_domains(&self) -> Vec<String> {
+        self.service
+            .iter()
+            .filter_map(ServiceEndpoint::linked_domains)
+            .flat_map(|service| service.origins)
+            .collect()
+    }
+
+    /// The endpoints of all DIDComm services of the document, most
+    /// preferred first. Endpoints of equal priority keep the order of the
+    /// document and of the sets within it.
+    pub fn didcomm_endpoints(&self) -> Vec<DidCommEndpoint> {
+        let mut endpoints: Vec<_> = self
+            .service
+            .iter()
+            .flat_map(ServiceEndpoint::didcomm)
+            .collect();
+        endpoints.sort_by_key(|e| e.priority);
+        endpoints
+    }
+
+    pub fn add_public_key(&mut self, key: PublicKey) {
+        self.public_key.push(key);
+    }
+
+    pub fn add_authentication(&mut self, key: PublicKey) {
+        self.authentication.push(key);
+    }
+
+    pub fn add_key_agreement(&mut self, key: PublicKey) {
+        self.key_agreement.push(key);
+    }
+
+    pub fn add_service(&mut self, service: ServiceEndpoint) {
+        self.service.push(service);
+    }
+
+    /// Adds to `keyAgreement` the X25519 key derived from every embedded
+    /// Ed25519 key (see `PublicKey::to_x25519`) that is not there already.
+    pub fn derive_key_agreement(&mut self) -> Result<(), DidError> {
+        let derived = self
+            .public_key
+            .iter()
+            .chain(self.authentication.iter())
+            .filter(|k| !k.reference() && k.algorithm() == Some(KeyAlgorithm::Ed25519))
+            .map(PublicKey::to_x25519)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for key in derived {
+            let id = self.absolute(key.subject().as_uri());
+            if !self
+                .key_agreement
+                .iter()
+                .any(|k| self.absolute(k.subject().as_uri()) == id)
+            {
+                self.key_agreement.push(key);
+            }
+        }
+        Ok(())
+    }
+
+    /// Finds the embedded public key or service whose id matches `uri`.
+    /// Fragment-only URIs are interpreted relative to the document subject.
+    pub fn dereference(&self, uri: &Uri) -> Option<Resource<'_>> {
+        let target = self.absolute(uri);
+
+        self.public_key
+            .iter()
+            .chain(self.authentication.iter())
+            .chain(self.key_agreement.iter())
+            .filter(|k| !k.reference())
+            .find(|k| self.absolute(k.subject().as_uri()) == target)
+            .map(Resource::PublicKey)
+            .or_else(|| {
+                self.service
+                    .iter()
+                    .filter(|s| !s.subject().is_empty())
+                    .find(|s| self.absolute(s.subject().as_uri()) == target)
+                    .map(Resource::Service)
+            })
+    }
+
+    /// Returns the authentication keys with every reference resolved to the
+    /// embedded key it points at.
+    pub fn authentication_keys(&self) -> Result<Vec<&PublicKey>, DidError> {
+        self.authentication
+            .iter()
+            .map(|k| {
+                if !k.reference() {
+                    return Ok(k);
+                }
+                match self.dereference(k.subject().as_uri()) {
+                    Some(Resource::PublicKey(pk)) => Ok(pk),
+                    _ => Err(err_msg(
+                        DidErrorKind::DanglingReference,
+                        k.subject().as_uri().to_string(),
+                    )),
+                }
+            })
+            .collect()
+    }
+
+    /// Reports the ways in which the document breaks the DID data model,
+    /// e.g. keys encoded in a way their type does not permit.
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        validate::validate(self)
+    }
+
+    /// Reports what keeps the document from being read as JSON-LD: a first
+    /// context that is not a DID context, and key types that no context
+    /// defines. Plain JSON consumers can ignore these.
+    pub fn validate_contexts(&self) -> Vec<Diagnostic> {
+        validate::validate_contexts(self)
+    }
+
+    /// Builds the URL a DID URL such as
+    /// `did:example:123?service=files&relativeRef=%2Fa.pdf` points to, as
+    /// `Dereferencer` does for this document.
+    pub fn service_url(&self, did_url: &Uri) -> Result<String, DidError> {
+        if did_url.did() != self.id.as_uri().did() {
+            return Err(err_msg(
+                DidErrorKind::DanglingReference,
+                format!("{} is not a URL of this document", did_url),
+            ));
+        }
+        let query = did_url.query.clone().unwrap_or_default();
+        let service = query
+            .get("service")
+            .ok_or_else(|| err_msg(DidErrorKind::InvalidDidUrl, "no service parameter"))?;
+        self.build_service_url(
+            service,
+            query.get("relativeRef").map(String::as_str),
+            did_url.fragment.as_deref(),
+        )
+    }
+
+    /// The URL selected by a `service` query parameter, resolving an
+    /// optional `relativeRef` against the service endpoint per RFC 3986 and
+    /// carrying over any fragment of the DID URL.
+    pub(crate) fn build_service_url(
+        &self,
+        service: &str,
+        relative_ref: Option<&str>,
+        fragment: Option<&str>,
+    ) -> Result<String, DidError> {
+        let service = self
+            .service_by_id(&format!("#{}", service))
+            .ok_or_else(|| err_msg(DidErrorKind::DanglingReference, format!("#{}", service)))?;
+        let endpoint =
+            *service.endpoint().uris().first().ok_or_else(|| {
+                err_msg(DidErrorKind::DanglingReference, "the service has no URI")
+            })?;
+
+        let mut url = Url::parse(endpoint)
+            .map_err(|e| err_msg(DidErrorKind::InvalidServiceEndpoint, e.to_string()))?;
+        if let Some(relative_ref) = relative_ref {
+            let relative_ref = percent_decode_str(relative_ref)
+                .decode_utf8()
+                .map_err(|e| err_msg(DidErrorKind::InvalidDidUrl, e.to_string()))?;
+            url = url
+                .join(&relative_ref)
+                .map_err(|e| err_msg(DidErrorKind::InvalidDidUrl, e.to_string()))?;
+        }
+        if let Some(fragment) = fragment {
+            url.set_fragment(Some(fragment));
+        }
+        Ok(url.into())
+    }
+
+    /// The document in JSON-LD expanded form. Members that none of its
+    /// contexts define are an `InvalidJsonLd` error rather than dropped.
+    /// Under contexts without a `publicKey` term, such as DID Core, the keys
+    /// are expanded as `verificationMethod`.
+    pub fn expand<L: ContextLoader + ?Sized>(&self, loader: &L) -> Result<Value, DidError> {
+        jsonld::expand_document(self, loader)
+    }
+
+    /// Reads a document back from the expanded form `expanded`, compacted
+    /// with the contexts in `context`.
+    pub fn compact<L: ContextLoader + ?Sized>(
+        expanded: &Value,
+        context: &[ContextEntry],
+        loader: &L,
+    ) -> Result<Document, DidError> {
+        jsonld::compact_document(expanded, context, loader)
+    }
+
+    fn absolute(&self, uri: &Uri) -> Uri {
+        uri.to_absolute(self.id.as_uri())
+    }
 }
 
-impl ToString for Document {
-    fn to_string(&self) -> String {
-        serde_json::to_string(&self).unwrap()
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", serde_json::to_string(&self).unwrap())
     }
 }
 
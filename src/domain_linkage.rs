@@ -0,0 +1,492 @@
+//! Domain Linkage as specified by the DIF Well Known DID Configuration:
+//! an origin proves that it is controlled by the subject of a DID by
+//! serving credentials signed with the DID's keys at
+//! `/.well-known/did-configuration.json`.
+//!
+//! Credentials in the JWT format are generated and verified in full.
+//! Credentials in the JSON-LD format are rejected with `UnsupportedAlgorithm`
+//! once their claims check out, since their proofs need RDF
+//! canonicalization.
+
+use crate::crypto::{sign, SignatureAlgorithm, Signer};
+use crate::datetime;
+use crate::doc::{Document, Resource};
+use crate::error::{err_msg, DidError, DidErrorKind};
+use crate::uri::Uri;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::{self, json, Value};
+use std::fmt;
+use std::str::FromStr;
+#[cfg(feature = "http-client")]
+use std::time::Duration;
+use std::time::{SystemTime, UNIX_EPOCH};
+use url::Url;
+
+pub const DID_CONFIGURATION_CONTEXT: &str =
+    "https://identity.foundation/.well-known/did-configuration/v1";
+pub const CREDENTIALS_CONTEXT: &str = "https://www.w3.org/2018/credentials/v1";
+pub const WELL_KNOWN_PATH: &str = "/.well-known/did-configuration.json";
+
+const VERIFIABLE_CREDENTIAL: &str = "VerifiableCredential";
+const DOMAIN_LINKAGE_CREDENTIAL: &str = "DomainLinkageCredential";
+
+/// The resource served at `/.well-known/did-configuration.json`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct DidConfiguration {
+    #[serde(rename = "@context")]
+    pub context: String,
+    pub linked_dids: Vec<LinkedDid>,
+}
+
+impl DidConfiguration {
+    pub fn new() -> Self {
+        DidConfiguration {
+            context: DID_CONFIGURATION_CONTEXT.to_owned(),
+            linked_dids: Vec::new(),
+        }
+    }
+
+    /// The URL the configuration of `origin` is served at.
+    pub fn url(origin: &str) -> Result<String, DidError> {
+        Ok(format!("{}{}", to_origin(origin)?, WELL_KNOWN_PATH))
+    }
+
+    /// Checks that the configuration links `origin` to the DID of `doc`,
+    /// returning the first credential to do so. Otherwise the error is the
+    /// one of the first credential issued by the DID.
+    pub fn verify(
+        &self,
+        doc: &Document,
+        origin: &str,
+        now: SystemTime,
+    ) -> Result<DomainLinkageCredential, DidError> {
+        let did = doc.subject().as_uri().to_string();
+        let mut error = None;
+        for linked_did in &self.linked_dids {
+            if linked_did.issuer().as_deref() != Some(did.as_str()) {
+                continue;
+            }
+            match linked_did.verify(doc, origin, now) {
+                Ok(credential) => return Ok(credential),
+                Err(e) => {
+                    error.get_or_insert(e);
+                }
+            }
+        }
+        Err(error.unwrap_or_else(|| {
+            err_msg(
+                DidErrorKind::InvalidDomainLinkage,
+                format!("no credential issued by {}", did),
+            )
+        }))
+    }
+}
+
+impl Default for DidConfiguration {
+    fn default() -> Self {
+        DidConfiguration::new()
+    }
+}
+
+impl fmt::Display for DidConfiguration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", serde_json::to_string(&self).unwrap())
+    }
+}
+
+impl FromStr for DidConfiguration {
+    type Err = serde_json::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_str(s)
+    }
+}
+
+/// An entry of `linked_dids`: a Domain Linkage credential as a JWT or as a
+/// JSON-LD credential with an embedded proof.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum LinkedDid {
+    Jwt(String),
+    Credential(DomainLinkageCredential),
+}
+
+impl LinkedDid {
+    /// The DID that claims to issue the credential, before any check.
+    pub fn issuer(&self) -> Option<String> {
+        match self {
+            LinkedDid::Jwt(jwt) => Jwt::decode(jwt).ok().map(|jwt| jwt.claims.iss),
+            LinkedDid::Credential(credential) => Some(credential.issuer.clone()),
+        }
+    }
+
+    /// Checks that the credential links `origin` to the DID of `doc` and is
+    /// valid at `now`. JSON-LD credentials whose claims pass still fail, as
+    /// their proof cannot be verified.
+    pub fn verify(
+        &self,
+        doc: &Document,
+        origin: &str,
+        now: SystemTime,
+    ) -> Result<DomainLinkageCredential, DidError> {
+        match self {
+            LinkedDid::Jwt(jwt) => DomainLinkageCredential::verify_jwt(jwt, doc, origin, now),
+            LinkedDid::Credential(credential) => {
+                credential.check(doc, origin, now)?;
+                let proof_type = credential
+                    .proof
+                    .as_ref()
+                    .and_then(|proof| proof.get("type"))
+                    .and_then(Value::as_str)
+                    .unwrap_or("missing");
+                Err(DidError::from_msg(
+                    DidErrorKind::UnsupportedAlgorithm {
+                        algorithm: proof_type.to_owned(),
+                    },
+                    "JSON-LD proofs cannot be verified",
+                ))
+            }
+        }
+    }
+}
+
+/// The claim that `credential_subject.origin` is controlled by the subject
+/// of `credential_subject.id`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DomainLinkageCredential {
+    #[serde(rename = "@context")]
+    pub context: Vec<String>,
+    pub issuer: String,
+    pub issuance_date: String,
+    pub expiration_date: String,
+    #[serde(rename = "type")]
+    pub credential_type: Vec<String>,
+    pub credential_subject: LinkedOrigin,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub proof: Option<Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct LinkedOrigin {
+    pub id: String,
+    pub origin: String,
+}
+
+impl DomainLinkageCredential {
+    /// An unsigned credential linking `origin` to `did`. Timestamps are
+    /// kept to the second.
+    pub fn new(
+        did: &Uri,
+        origin: &str,
+        issued: SystemTime,
+        expires: SystemTime,
+    ) -> Result<Self, DidError> {
+        let did = did.did().to_string();
+        Ok(DomainLinkageCredential {
+            context: vec![
+                CREDENTIALS_CONTEXT.to_owned(),
+                DID_CONFIGURATION_CONTEXT.to_owned(),
+            ],
+            issuer: did.clone(),
+            issuance_date: datetime::format(seconds(issued)),
+            expiration_date: datetime::format(seconds(expires)),
+            credential_type: vec![
+                VERIFIABLE_CREDENTIAL.to_owned(),
+                DOMAIN_LINKAGE_CREDENTIAL.to_owned(),
+            ],
+            credential_subject: LinkedOrigin {
+                id: did,
+                origin: to_origin(origin)?,
+            },
+            proof: None,
+        })
+    }
+
+    /// Signs the credential as a JWT with the key `key_id` of `doc`, which
+    /// has to be the document of the issuer.
+    pub fn to_jwt<S: Signer + ?Sized>(
+        &self,
+        doc: &Document,
+        signer: &S,
+        key_id: &Uri,
+    ) -> Result<String, DidError> {
+        let key = match doc.dereference(key_id) {
+            Some(Resource::PublicKey(key)) => key,
+            _ => return Err(err_msg(DidErrorKind::DanglingReference, key_id.to_string())),
+        };
+        let algorithm = key
+            .algorithm()
+            .and_then(SignatureAlgorithm::default_for)
+            .ok_or_else(|| {
+                DidError::from_kind(DidErrorKind::UnsupportedAlgorithm {
+                    algorithm: key.key_type().to_string(),
+                })
+            })?;
+        let header = json!({
+            "alg": algorithm.name(),
+            "kid": key_id.to_absolute(doc.subject().as_uri()).to_string(),
+        });
+        let mut vc = self.clone();
+        vc.proof = None;
+        let claims = Claims {
+            exp: timestamp(&self.expiration_date)?,
+            iss: self.issuer.clone(),
+            nbf: timestamp(&self.issuance_date)?,
+            sub: self.credential_subject.id.clone(),
+            vc,
+        };
+
+        let input = format!("{}.{}", encode(&header)?, encode(&claims)?);
+        let signature = sign(doc, signer, key_id, input.as_bytes())?;
+        Ok(format!("{}.{}", input, URL_SAFE_NO_PAD.encode(signature)))
+    }
+
+    /// Verifies a credential in the JWT format against the keys of `doc`
+    /// and checks that it links `origin` to its DID at `now`.
+    pub fn verify_jwt(
+        jwt: &str,
+        doc: &Document,
+        origin: &str,
+        now: SystemTime,
+    ) -> Result<Self, DidError> {
+        let jwt = Jwt::decode(jwt)?;
+        let claims = &jwt.claims;
+        let credential = &claims.vc;
+        credential.check(doc, origin, now)?;
+        if claims.iss != credential.issuer || claims.sub != credential.credential_subject.id {
+            return Err(invalid("iss and sub have to match the credential"));
+        }
+        if claims.nbf != timestamp(&credential.issuance_date)?
+            || claims.exp != timestamp(&credential.expiration_date)?
+        {
+            return Err(invalid("nbf and exp have to match the credential"));
+        }
+
+        let algorithm = SignatureAlgorithm::from_name(&jwt.header.alg).ok_or_else(|| {
+            DidError::from_kind(DidErrorKind::UnsupportedAlgorithm {
+                algorithm: jwt.header.alg.clone(),
+            })
+        })?;
+        let kid = Uri::from_str(&jwt.header.kid)?;
+        if kid.did() != *doc.subject().as_uri() {
+            return Err(invalid("the kid is not a key of the issuer"));
+        }
+        match doc.dereference(&kid) {
+            Some(Resource::PublicKey(key)) => {
+                key.verify_with(algorithm, jwt.input.as_bytes(), &jwt.signature)?
+            }
+            _ => return Err(err_msg(DidErrorKind::DanglingReference, jwt.header.kid)),
+        }
+        Ok(jwt.claims.vc)
+    }
+
+    /// The checks shared by both formats.
+    fn check(&self, doc: &Document, origin: &str, now: SystemTime) -> Result<(), DidError> {
+        let has_type = |t| self.credential_type.iter().any(|c| c == t);
+        if !has_type(VERIFIABLE_CREDENTIAL) || !has_type(DOMAIN_LINKAGE_CREDENTIAL) {
+            return Err(invalid("not a DomainLinkageCredential"));
+        }
+        if !self.context.iter().any(|c| c == DID_CONFIGURATION_CONTEXT) {
+            return Err(invalid("the DID configuration context is missing"));
+        }
+
+        let did = doc.subject().as_uri().to_string();
+        if self.issuer != did || self.credential_subject.id != did {
+            return Err(invalid(format!(
+                "the credential is not issued by and about {}",
+                did
+            )));
+        }
+        if to_origin(&self.credential_subject.origin)? != to_origin(origin)? {
+            return Err(invalid(format!(
+                "the credential is for {}",
+                self.credential_subject.origin
+            )));
+        }
+
+        let now = seconds(now);
+        if now < timestamp(&self.issuance_date)? {
+            return Err(invalid("the credential is not valid yet"));
+        }
+        if now >= timestamp(&self.expiration_date)? {
+            return Err(invalid("the credential has expired"));
+        }
+        Ok(())
+    }
+}
+
+/// Fetches the DID configuration of an origin. `HttpFetcher` does so over
+/// HTTPS, other implementations let tests run offline.
+pub trait ConfigurationFetcher {
+    /// Returns the body served at `url`, the `DidConfiguration::url` of an
+    /// origin.
+    fn fetch(&self, url: &str) -> Result<String, DidError>;
+}
+
+impl<F: ConfigurationFetcher + ?Sized> ConfigurationFetcher for &F {
+    fn fetch(&self, url: &str) -> Result<String, DidError> {
+        (**self).fetch(url)
+    }
+}
+
+impl<F: ConfigurationFetcher + ?Sized> ConfigurationFetcher for Box<F> {
+    fn fetch(&self, url: &str) -> Result<String, DidError> {
+        (**self).fetch(url)
+    }
+}
+
+/// A `ConfigurationFetcher` that makes plain `GET` requests.
+#[cfg(feature = "http-client")]
+pub struct HttpFetcher {
+    agent: ureq::Agent,
+}
+
+#[cfg(feature = "http-client")]
+impl HttpFetcher {
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+    pub fn new() -> Self {
+        HttpFetcher::with_timeout(Self::DEFAULT_TIMEOUT)
+    }
+
+    pub fn with_timeout(timeout: Duration) -> Self {
+        HttpFetcher {
+            agent: ureq::AgentBuilder::new()
+                .timeout(timeout)
+                .redirects(0)
+                .build(),
+        }
+    }
+}
+
+#[cfg(feature = "http-client")]
+impl Default for HttpFetcher {
+    fn default() -> Self {
+        HttpFetcher::new()
+    }
+}
+
+#[cfg(feature = "http-client")]
+impl ConfigurationFetcher for HttpFetcher {
+    fn fetch(&self, url: &str) -> Result<String, DidError> {
+        self.agent
+            .get(url)
+            .call()
+            .map_err(|e| DidError::from_msg(DidErrorKind::Io, e.to_string()))?
+            .into_string()
+            .map_err(|e| DidError::from_msg(DidErrorKind::Io, e.to_string()))
+    }
+}
+
+/// Fetches the DID configuration of `origin` and checks that it links the
+/// origin to the DID of `doc`.
+pub fn verify_origin<F: ConfigurationFetcher + ?Sized>(
+    doc: &Document,
+    origin: &str,
+    fetcher: &F,
+    now: SystemTime,
+) -> Result<DomainLinkageCredential, DidError> {
+    let body = fetcher.fetch(&DidConfiguration::url(origin)?)?;
+    let configuration = DidConfiguration::from_str(&body)
+        .map_err(|e| invalid(format!("malformed DID configuration: {}", e)))?;
+    configuration.verify(doc, origin, now)
+}
+
+/// Verifies every origin of the `LinkedDomains` services of `doc` with
+/// `verify_origin`.
+pub fn verify_linked_domains<F: ConfigurationFetcher + ?Sized>(
+    doc: &Document,
+    fetcher: &F,
+    now: SystemTime,
+) -> Vec<(String, Result<DomainLinkageCredential, DidError>)> {
+    doc.linked_domains()
+        .into_iter()
+        .map(|origin| {
+            let result = verify_origin(doc, &origin, fetcher, now);
+            (origin, result)
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct Header {
+    alg: String,
+    kid: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Claims {
+    exp: i64,
+    iss: String,
+    nbf: i64,
+    sub: String,
+    vc: DomainLinkageCredential,
+}
+
+struct Jwt {
+    header: Header,
+    claims: Claims,
+    input: String,
+    signature: Vec<u8>,
+}
+
+impl Jwt {
+    fn decode(jwt: &str) -> Result<Self, DidError> {
+        let parts: Vec<&str> = jwt.split('.').collect();
+        if parts.len() != 3 {
+            return Err(invalid("not a compact JWS"));
+        }
+        let part = |i: usize| {
+            URL_SAFE_NO_PAD
+                .decode(parts[i])
+                .map_err(|_| invalid("malformed JWT"))
+        };
+        let header = serde_json::from_slice(&part(0)?)
+            .map_err(|e| invalid(format!("malformed JWT header: {}", e)))?;
+        let claims = serde_json::from_slice(&part(1)?)
+            .map_err(|e| invalid(format!("malformed JWT claims: {}", e)))?;
+        Ok(Jwt {
+            header,
+            claims,
+            input: format!("{}.{}", parts[0], parts[1]),
+            signature: part(2)?,
+        })
+    }
+}
+
+fn invalid<D>(msg: D) -> DidError
+where
+    D: fmt::Display + fmt::Debug + Send + Sync + 'static,
+{
+    DidError::from_msg(DidErrorKind::InvalidDomainLinkage, msg)
+}
+
+fn encode<T: serde::Serialize>(value: &T) -> Result<String, DidError> {
+    let json = serde_json::to_vec(value).map_err(|e| invalid(e.to_string()))?;
+    Ok(URL_SAFE_NO_PAD.encode(json))
+}
+
+/// The ASCII serialization of the origin of `url`, e.g.
+/// `https://example.com` for `https://example.com/`.
+fn to_origin(url: &str) -> Result<String, DidError> {
+    let parsed = Url::parse(url).map_err(|_| invalid(format!("{} is not an origin", url)))?;
+    let origin = parsed.origin();
+    if !origin.is_tuple() {
+        return Err(invalid(format!("{} is not an origin", url)));
+    }
+    Ok(origin.ascii_serialization())
+}
+
+fn seconds(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs() as i64,
+        Err(e) => -(e.duration().as_secs() as i64),
+    }
+}
+
+fn timestamp(date: &str) -> Result<i64, DidError> {
+    datetime::parse(date).ok_or_else(|| invalid(format!("malformed date {}", date)))
+}
@@ -1,3 +1,6 @@
+// failure_derive predates the non_local_definitions lint
+#![allow(non_local_definitions)]
+
 use failure::{Backtrace, Context, Fail};
 
 #[derive(Clone, Eq, PartialEq, Debug, Fail)]
@@ -6,6 +9,38 @@ pub enum DidErrorKind {
     InvalidUri,
     #[fail(display = "Unknown did method: {:?}", msg)]
     UnknownMethod { msg: String },
+    #[fail(display = "DID URL does not reference anything in the document")]
+    DanglingReference,
+    #[fail(display = "I/O error")]
+    Io,
+    #[fail(display = "Malformed {} key data", encoding)]
+    MalformedKeyData { encoding: String },
+    #[fail(display = "Key material cannot be decoded from {}", encoding)]
+    UnsupportedEncoding { encoding: String },
+    #[fail(display = "Cannot convert {} key data to {}", from, to)]
+    UnsupportedConversion { from: String, to: String },
+    #[fail(display = "{} signatures are not supported", algorithm)]
+    UnsupportedAlgorithm { algorithm: String },
+    #[fail(display = "Signature verification failed")]
+    InvalidSignature,
+    #[fail(display = "{} keys are not supported", algorithm)]
+    UnsupportedKeyAlgorithm { algorithm: String },
+    #[fail(display = "Invalid secret key")]
+    InvalidSecretKey,
+    #[fail(display = "No secret key is kept for the verification method")]
+    MissingSecretKey,
+    #[fail(display = "Wrong key store passphrase")]
+    WrongPassphrase,
+    #[fail(display = "Invalid DID URL")]
+    InvalidDidUrl,
+    #[fail(display = "Service endpoint is not a valid URL")]
+    InvalidServiceEndpoint,
+    #[fail(display = "Domain linkage could not be verified")]
+    InvalidDomainLinkage,
+    #[fail(display = "Invalid JSON-LD")]
+    InvalidJsonLd,
+    #[fail(display = "JSON-LD context {} could not be loaded", url)]
+    UnknownContext { url: String },
 }
 
 #[derive(Debug)]
@@ -48,7 +83,7 @@ impl std::fmt::Display for DidError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let mut first = true;
 
-        for cause in Fail::iter_chain(&self.inner) {
+        for cause in <dyn Fail>::iter_chain(&self.inner) {
             if first {
                 first = false;
                 writeln!(f, "Error: {}", cause)?;
@@ -0,0 +1,232 @@
+use crate::fields::spki;
+use crate::fields::{Jwk, Multicodec};
+use num_bigint::BigUint;
+
+/// The kind of key material held by a `PublicKey`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum KeyAlgorithm {
+    Ed25519,
+    X25519,
+    Secp256k1,
+    P256,
+    P384,
+    Rsa,
+    Bls12381G1,
+    Bls12381G2,
+}
+
+impl KeyAlgorithm {
+    pub fn multicodec(self) -> Multicodec {
+        match self {
+            KeyAlgorithm::Ed25519 => Multicodec::Ed25519Pub,
+            KeyAlgorithm::X25519 => Multicodec::X25519Pub,
+            KeyAlgorithm::Secp256k1 => Multicodec::Secp256k1Pub,
+            KeyAlgorithm::P256 => Multicodec::P256Pub,
+            KeyAlgorithm::P384 => Multicodec::P384Pub,
+            KeyAlgorithm::Rsa => Multicodec::RsaPub,
+            KeyAlgorithm::Bls12381G1 => Multicodec::Bls12381G1Pub,
+            KeyAlgorithm::Bls12381G2 => Multicodec::Bls12381G2Pub,
+        }
+    }
+
+    pub fn from_multicodec(codec: Multicodec) -> Self {
+        match codec {
+            Multicodec::Ed25519Pub => KeyAlgorithm::Ed25519,
+            Multicodec::X25519Pub => KeyAlgorithm::X25519,
+            Multicodec::Secp256k1Pub => KeyAlgorithm::Secp256k1,
+            Multicodec::P256Pub => KeyAlgorithm::P256,
+            Multicodec::P384Pub => KeyAlgorithm::P384,
+            Multicodec::RsaPub => KeyAlgorithm::Rsa,
+            Multicodec::Bls12381G1Pub => KeyAlgorithm::Bls12381G1,
+            Multicodec::Bls12381G2Pub => KeyAlgorithm::Bls12381G2,
+        }
+    }
+
+    /// The JWK `kty` and `crv` for keys of this algorithm, if JWK has them.
+    pub fn jwk_params(self) -> Option<(&'static str, Option<&'static str>)> {
+        match self {
+            KeyAlgorithm::Ed25519 => Some(("OKP", Some("Ed25519"))),
+            KeyAlgorithm::X25519 => Some(("OKP", Some("X25519"))),
+            KeyAlgorithm::Secp256k1 => Some(("EC", Some("secp256k1"))),
+            KeyAlgorithm::P256 => Some(("EC", Some("P-256"))),
+            KeyAlgorithm::P384 => Some(("EC", Some("P-384"))),
+            KeyAlgorithm::Rsa => Some(("RSA", None)),
+            KeyAlgorithm::Bls12381G1 | KeyAlgorithm::Bls12381G2 => None,
+        }
+    }
+
+    pub fn from_jwk(jwk: &Jwk) -> Option<Self> {
+        match (jwk.kty.as_str(), jwk.crv.as_deref()) {
+            ("OKP", Some("Ed25519")) => Some(KeyAlgorithm::Ed25519),
+            ("OKP", Some("X25519")) => Some(KeyAlgorithm::X25519),
+            ("EC", Some("secp256k1")) => Some(KeyAlgorithm::Secp256k1),
+            ("EC", Some("P-256")) => Some(KeyAlgorithm::P256),
+            ("EC", Some("P-384")) => Some(KeyAlgorithm::P384),
+            ("RSA", _) => Some(KeyAlgorithm::Rsa),
+            _ => None,
+        }
+    }
+
+    /// The algorithm and named curve OIDs identifying keys of this algorithm
+    /// in a `SubjectPublicKeyInfo`.
+    pub(crate) fn spki_oids(self) -> Option<(&'static [u8], Option<&'static [u8]>)> {
+        match self {
+            KeyAlgorithm::Ed25519 => Some((spki::ID_ED25519, None)),
+            KeyAlgorithm::X25519 => Some((spki::ID_X25519, None)),
+            KeyAlgorithm::Secp256k1 => Some((spki::ID_EC_PUBLIC_KEY, Some(spki::SECP256K1))),
+            KeyAlgorithm::P256 => Some((spki::ID_EC_PUBLIC_KEY, Some(spki::SECP256R1))),
+            KeyAlgorithm::P384 => Some((spki::ID_EC_PUBLIC_KEY, Some(spki::SECP384R1))),
+            KeyAlgorithm::Rsa => Some((spki::ID_RSA_ENCRYPTION, None)),
+            KeyAlgorithm::Bls12381G1 | KeyAlgorithm::Bls12381G2 => None,
+        }
+    }
+
+    pub(crate) fn from_spki(info: &spki::SubjectPublicKeyInfo) -> Option<Self> {
+        [
+            KeyAlgorithm::Ed25519,
+            KeyAlgorithm::X25519,
+            KeyAlgorithm::Secp256k1,
+            KeyAlgorithm::P256,
+            KeyAlgorithm::P384,
+            KeyAlgorithm::Rsa,
+        ]
+        .iter()
+        .copied()
+        .find(|alg| match alg.spki_oids() {
+            Some((oid, None)) => info.algorithm == oid,
+            Some((oid, Some(curve))) => {
+                info.algorithm == oid && info.curve.as_deref() == Some(curve)
+            }
+            None => false,
+        })
+    }
+
+    /// The size in bytes of a field element for elliptic curve algorithms.
+    pub(crate) fn field_len(self) -> Option<usize> {
+        match self {
+            KeyAlgorithm::Secp256k1 | KeyAlgorithm::P256 => Some(32),
+            KeyAlgorithm::P384 => Some(48),
+            _ => None,
+        }
+    }
+
+    /// Whether `key` has a valid length for a raw key of this algorithm.
+    pub(crate) fn accepts(self, key: &[u8]) -> bool {
+        self.multicodec().fits(key)
+    }
+
+    /// Converts an elliptic curve point to its uncompressed SEC1 form.
+    pub(crate) fn uncompress(self, point: &[u8]) -> Option<Vec<u8>> {
+        let len = self.field_len()?;
+        if point.len() == 2 * len + 1 && point[0] == 0x04 {
+            return Some(point.to_vec());
+        }
+        if point.len() != len + 1 || (point[0] != 0x02 && point[0] != 0x03) {
+            return None;
+        }
+
+        let (p, a, b) = self.curve()?;
+        let x = BigUint::from_bytes_be(&point[1..]);
+        if x >= p {
+            return None;
+        }
+        // y^2 = x^3 + ax + b, and since p = 3 mod 4 for every supported
+        // curve the square root is (y^2)^((p + 1) / 4).
+        let y2 = (x.modpow(&BigUint::from(3u8), &p) + &a * &x + b) % &p;
+        let mut y = y2.modpow(&((&p + 1u8) >> 2), &p);
+        if y.modpow(&BigUint::from(2u8), &p) != y2 {
+            return None;
+        }
+        if y.bit(0) != (point[0] == 0x03) {
+            y = &p - y;
+        }
+
+        let mut out = vec![0x04];
+        out.extend(pad(&x.to_bytes_be(), len));
+        out.extend(pad(&y.to_bytes_be(), len));
+        Some(out)
+    }
+
+    /// Converts an elliptic curve point to its compressed SEC1 form.
+    pub(crate) fn compress(self, point: &[u8]) -> Option<Vec<u8>> {
+        let len = self.field_len()?;
+        if point.len() == len + 1 && (point[0] == 0x02 || point[0] == 0x03) {
+            return Some(point.to_vec());
+        }
+        if point.len() != 2 * len + 1 || point[0] != 0x04 {
+            return None;
+        }
+        let mut out = vec![0x02 | (point[2 * len] & 1)];
+        out.extend_from_slice(&point[1..=len]);
+        Some(out)
+    }
+
+    /// The field prime and the `a` and `b` coefficients of the curve.
+    fn curve(self) -> Option<(BigUint, BigUint, BigUint)> {
+        let (p, b): (&[u8], &[u8]) = match self {
+            KeyAlgorithm::Secp256k1 => (
+                b"fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+                b"07",
+            ),
+            KeyAlgorithm::P256 => (
+                b"ffffffff00000001000000000000000000000000ffffffffffffffffffffffff",
+                b"5ac635d8aa3a93e7b3ebbd55769886bc651d06b0cc53b0f63bce3c3e27d2604b",
+            ),
+            KeyAlgorithm::P384 => (
+                b"fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffeffffffff0000000000000000ffffffff",
+                b"b3312fa7e23ee7e4988e056be3f82d19181d9c6efe8141120314088f5013875ac656398d8a2ed19d2a85c8edd3ec2aef",
+            ),
+            _ => return None,
+        };
+        let p = BigUint::parse_bytes(p, 16)?;
+        let b = BigUint::parse_bytes(b, 16)?;
+        // a is 0 for secp256k1 and -3 for the NIST curves
+        let a = match self {
+            KeyAlgorithm::Secp256k1 => BigUint::from(0u8),
+            _ => &p - 3u8,
+        };
+        Some((p, a, b))
+    }
+}
+
+/// Maps an Ed25519 public key to the X25519 public key of the same secret
+/// with the birational map `u = (1 + y) / (1 - y)` of RFC 7748.
+pub(crate) fn ed25519_to_x25519(key: &[u8]) -> Option<Vec<u8>> {
+    if key.len() != 32 {
+        return None;
+    }
+    let p = (BigUint::from(1u8) << 255u32) - 19u8;
+    let inverse = |n: &BigUint| n.modpow(&(&p - 2u8), &p);
+    let mut y = key.to_vec();
+    y[31] &= 0x7f;
+    let y = BigUint::from_bytes_le(&y);
+    if y >= p {
+        return None;
+    }
+
+    // the key has to be a point on the curve, so x^2 = (y^2 - 1) / (dy^2 + 1)
+    // with d = -121665 / 121666 must have a square root
+    let d = (&p - 121665u32) * inverse(&BigUint::from(121666u32)) % &p;
+    let y2 = &y * &y % &p;
+    let x2 = (&y2 + &p - 1u8) * inverse(&((d * &y2 + 1u8) % &p)) % &p;
+    let legendre = x2.modpow(&((&p - 1u8) >> 1), &p);
+    if legendre > BigUint::from(1u8) {
+        return None;
+    }
+    // y = 1 is the identity, which has no Montgomery counterpart
+    let denominator = (&p + 1u8 - &y) % &p;
+    if denominator == BigUint::from(0u8) {
+        return None;
+    }
+
+    let u = (y + 1u8) * inverse(&denominator) % &p;
+    let mut out = u.to_bytes_le();
+    out.resize(32, 0);
+    Some(out)
+}
+
+fn pad(bytes: &[u8], len: usize) -> Vec<u8> {
+    let mut out = vec![0; len.saturating_sub(bytes.len())];
+    out.extend_from_slice(bytes);
+    out
+}
@@ -1,26 +1,98 @@
+use indexmap::IndexMap;
+use serde::de::{Deserialize, Deserializer};
 use serde::ser::{Serialize, SerializeSeq, Serializer};
-use serde_derive::Deserialize;
-use std::default::Default;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
 use std::str::FromStr;
 use void::Void;
 
-#[derive(Deserialize, Debug)]
-#[serde(transparent)]
-pub struct Context(Vec<String>);
+/// The value of `@context`: a single entry or a list of them.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Context(Vec<ContextEntry>);
+
+/// An entry of `@context`, either the URL of a context or an inline
+/// context with term definitions.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum ContextEntry {
+    Uri(String),
+    Object(IndexMap<String, Value>),
+}
 
 impl Context {
-    pub fn as_vec(&self) -> &Vec<String> {
+    pub fn as_vec(&self) -> &Vec<ContextEntry> {
         &self.0
     }
 
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Appends `entry` unless it is there already.
+    pub fn push(&mut self, entry: ContextEntry) {
+        if !self.0.contains(&entry) {
+            self.0.push(entry);
+        }
+    }
+
+    /// Whether the URL `uri` is one of the entries.
+    pub fn contains(&self, uri: &str) -> bool {
+        self.0.iter().any(|entry| entry == uri)
+    }
+
+    /// Whether one of the entries defines `term`, either inline or as a
+    /// `KnownContext`.
+    pub fn defines(&self, term: &str) -> bool {
+        self.0.iter().any(|entry| match entry {
+            ContextEntry::Uri(uri) => KnownContext::from_url(uri)
+                .map(|known| known.terms().contains(&term))
+                .unwrap_or(false),
+            ContextEntry::Object(terms) => terms.contains_key(term),
+        })
+    }
 }
 
-impl Default for Context {
-    fn default() -> Self {
-        Context(vec![])
+impl ContextEntry {
+    pub fn as_uri(&self) -> Option<&str> {
+        match self {
+            ContextEntry::Uri(uri) => Some(uri),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&IndexMap<String, Value>> {
+        match self {
+            ContextEntry::Object(terms) => Some(terms),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for ContextEntry {
+    fn from(uri: &str) -> Self {
+        ContextEntry::Uri(uri.to_owned())
+    }
+}
+
+impl From<KnownContext> for ContextEntry {
+    fn from(context: KnownContext) -> Self {
+        ContextEntry::Uri(context.url().to_owned())
+    }
+}
+
+impl PartialEq<&str> for ContextEntry {
+    fn eq(&self, rhs: &&str) -> bool {
+        self.as_uri() == Some(*rhs)
+    }
+}
+
+impl PartialEq<str> for ContextEntry {
+    fn eq(&self, rhs: &str) -> bool {
+        self.as_uri() == Some(rhs)
     }
 }
 
@@ -28,7 +100,26 @@ impl FromStr for Context {
     type Err = Void;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Context(vec![s.to_owned()]))
+        Ok(Context(vec![ContextEntry::from(s)]))
+    }
+}
+
+impl<'de> Deserialize<'de> for Context {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum OneOrMany {
+            One(ContextEntry),
+            Many(Vec<ContextEntry>),
+        }
+
+        Ok(match OneOrMany::deserialize(deserializer)? {
+            OneOrMany::One(entry) => Context(vec![entry]),
+            OneOrMany::Many(entries) => Context(entries),
+        })
     }
 }
 
@@ -39,7 +130,7 @@ impl Serialize for Context {
     {
         match self.0.len() {
             0 => serializer.serialize_none(),
-            1 => serializer.serialize_str(&self.0[0]),
+            1 => self.0[0].serialize(serializer),
             _ => {
                 let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
                 for element in &self.0 {
@@ -50,3 +141,116 @@ impl Serialize for Context {
         }
     }
 }
+
+/// The DID and security contexts whose terms are known without fetching
+/// them.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum KnownContext {
+    /// The context of the DID specification drafts, which also carries the
+    /// security terms of the time.
+    DidV1,
+    /// The context of DID Core 1.0.
+    DidCore,
+    SecurityV1,
+    SecurityV2,
+    Ed25519Signature2018,
+    Ed25519Signature2020,
+    X25519KeyAgreement2019,
+    X25519KeyAgreement2020,
+    JsonWebSignature2020,
+    EcdsaSecp256k1Signature2019,
+    EcdsaSecp256k1Recovery2020,
+    Bls12381Signature2020,
+    Multikey,
+}
+
+const KNOWN_CONTEXTS: &[KnownContext] = &[
+    KnownContext::DidV1,
+    KnownContext::DidCore,
+    KnownContext::SecurityV1,
+    KnownContext::SecurityV2,
+    KnownContext::Ed25519Signature2018,
+    KnownContext::Ed25519Signature2020,
+    KnownContext::X25519KeyAgreement2019,
+    KnownContext::X25519KeyAgreement2020,
+    KnownContext::JsonWebSignature2020,
+    KnownContext::EcdsaSecp256k1Signature2019,
+    KnownContext::EcdsaSecp256k1Recovery2020,
+    KnownContext::Bls12381Signature2020,
+    KnownContext::Multikey,
+];
+
+impl KnownContext {
+    pub fn all() -> &'static [KnownContext] {
+        KNOWN_CONTEXTS
+    }
+
+    pub fn url(self) -> &'static str {
+        match self {
+            KnownContext::DidV1 => "https://w3id.org/did/v1",
+            KnownContext::DidCore => "https://www.w3.org/ns/did/v1",
+            KnownContext::SecurityV1 => "https://w3id.org/security/v1",
+            KnownContext::SecurityV2 => "https://w3id.org/security/v2",
+            KnownContext::Ed25519Signature2018 => {
+                "https://w3id.org/security/suites/ed25519-2018/v1"
+            }
+            KnownContext::Ed25519Signature2020 => {
+                "https://w3id.org/security/suites/ed25519-2020/v1"
+            }
+            KnownContext::X25519KeyAgreement2019 => {
+                "https://w3id.org/security/suites/x25519-2019/v1"
+            }
+            KnownContext::X25519KeyAgreement2020 => {
+                "https://w3id.org/security/suites/x25519-2020/v1"
+            }
+            KnownContext::JsonWebSignature2020 => "https://w3id.org/security/suites/jws-2020/v1",
+            KnownContext::EcdsaSecp256k1Signature2019 => {
+                "https://w3id.org/security/suites/secp256k1-2019/v1"
+            }
+            KnownContext::EcdsaSecp256k1Recovery2020 => {
+                "https://w3id.org/security/suites/secp256k1recovery-2020/v2"
+            }
+            KnownContext::Bls12381Signature2020 => {
+                "https://w3id.org/security/suites/bls12381-2020/v1"
+            }
+            KnownContext::Multikey => "https://w3id.org/security/multikey/v1",
+        }
+    }
+
+    pub fn from_url(url: &str) -> Option<Self> {
+        KNOWN_CONTEXTS.iter().copied().find(|c| c.url() == url)
+    }
+
+    /// Whether this is one of the contexts a DID document has to start
+    /// with.
+    pub fn is_did(self) -> bool {
+        self == KnownContext::DidV1 || self == KnownContext::DidCore
+    }
+
+    /// The verification method types the context defines.
+    pub fn terms(self) -> &'static [&'static str] {
+        match self {
+            KnownContext::DidV1 => &[
+                "Ed25519VerificationKey2018",
+                "RsaVerificationKey2018",
+                "EcdsaSecp256k1VerificationKey2019",
+            ],
+            KnownContext::DidCore | KnownContext::SecurityV1 => &[],
+            KnownContext::SecurityV2 => &[
+                "Ed25519VerificationKey2018",
+                "RsaVerificationKey2018",
+                "EcdsaSecp256k1VerificationKey2019",
+                "X25519KeyAgreementKey2019",
+            ],
+            KnownContext::Ed25519Signature2018 => &["Ed25519VerificationKey2018"],
+            KnownContext::Ed25519Signature2020 => &["Ed25519VerificationKey2020"],
+            KnownContext::X25519KeyAgreement2019 => &["X25519KeyAgreementKey2019"],
+            KnownContext::X25519KeyAgreement2020 => &["X25519KeyAgreementKey2020"],
+            KnownContext::JsonWebSignature2020 => &["JsonWebKey2020"],
+            KnownContext::EcdsaSecp256k1Signature2019 => &["EcdsaSecp256k1VerificationKey2019"],
+            KnownContext::EcdsaSecp256k1Recovery2020 => &["EcdsaSecp256k1RecoveryMethod2020"],
+            KnownContext::Bls12381Signature2020 => &["Bls12381G1Key2020", "Bls12381G2Key2020"],
+            KnownContext::Multikey => &["Multikey"],
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use crate::doc::{Document, Resource};
+use crate::error::{err_msg, DidError, DidErrorKind};
+use crate::fields::{
+    Endpoint, Multicodec, PublicKey, PublicKeyEncoding, PublicKeyType, ServiceEndpoint, Subject,
+};
+use crate::uri::Uri;
+use indexmap::IndexMap;
+use serde_json::Value;
+use std::str::FromStr;
+
+/// The service types DIDComm agents advertise themselves with.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DidCommType {
+    /// DIDComm v2.
+    Messaging,
+    /// DIDComm v1 as described by Aries RFC 0067.
+    DidCommunication,
+    /// The Indy flavour of DIDComm v1, which lists raw base58 verkeys.
+    IndyAgent,
+}
+
+impl DidCommType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DidCommType::Messaging => "DIDCommMessaging",
+            DidCommType::DidCommunication => "did-communication",
+            DidCommType::IndyAgent => "IndyAgent",
+        }
+    }
+}
+
+impl FromStr for DidCommType {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "DIDCommMessaging" => Ok(DidCommType::Messaging),
+            "did-communication" => Ok(DidCommType::DidCommunication),
+            "IndyAgent" => Ok(DidCommType::IndyAgent),
+            _ => Err(()),
+        }
+    }
+}
+
+/// One endpoint of a DIDComm service.
+///
+/// A `DIDCommMessaging` service yields an endpoint for every URI or map in
+/// its `serviceEndpoint`, with `accept` and `routingKeys` taken from the map
+/// or, for plain URIs, from the service itself. The v1 types take all of
+/// `recipientKeys`, `routingKeys`, `accept` and `priority` from the service.
+/// Members of the wrong JSON type are ignored.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DidCommEndpoint {
+    pub service_type: DidCommType,
+    /// The id of the service the endpoint belongs to.
+    pub id: Subject,
+    pub uri: String,
+    pub accept: Vec<String>,
+    /// Key references, or raw base58 keys for `IndyAgent`. Always empty for
+    /// DIDComm v2, whose recipients are the `keyAgreement` keys.
+    pub recipient_keys: Vec<String>,
+    pub routing_keys: Vec<String>,
+    /// Lower values are preferred. Taken from a DIDComm v2 endpoint map or
+    /// else the service, and 0 when neither says.
+    pub priority: u64,
+}
+
+impl DidCommEndpoint {
+    /// The keys messages for this endpoint are encrypted to: the listed
+    /// recipient keys for DIDComm v1, the `keyAgreement` keys of `doc` for
+    /// DIDComm v2.
+    pub fn resolve_recipient_keys(&self, doc: &Document) -> Result<Vec<PublicKey>, DidError> {
+        if self.service_type == DidCommType::Messaging {
+            return doc
+                .key_agreement()
+                .iter()
+                .map(|k| {
+                    if k.reference() {
+                        resolve_key(doc, &k.subject().as_uri().to_string())
+                    } else {
+                        Ok(k.clone())
+                    }
+                })
+                .collect();
+        }
+        self.recipient_keys
+            .iter()
+            .map(|k| resolve_key(doc, k))
+            .collect()
+    }
+
+    /// The keys of the mediators between the sender and this endpoint, in
+    /// order. Only did:key references and keys embedded in `doc` can be
+    /// resolved without a `Resolver`.
+    pub fn resolve_routing_keys(&self, doc: &Document) -> Result<Vec<PublicKey>, DidError> {
+        self.routing_keys
+            .iter()
+            .map(|k| resolve_key(doc, k))
+            .collect()
+    }
+}
+
+impl ServiceEndpoint {
+    /// The DIDComm endpoints of the service, or none if it is not a DIDComm
+    /// service.
+    pub fn didcomm(&self) -> Vec<DidCommEndpoint> {
+        let service_type = match DidCommType::from_str(self.kind()) {
+            Ok(service_type) => service_type,
+            Err(_) => return Vec::new(),
+        };
+        let endpoint = |uri: &str, members: &IndexMap<String, Value>| DidCommEndpoint {
+            service_type,
+            id: self.subject().clone(),
+            uri: uri.to_owned(),
+            accept: strings(members.get("accept")),
+            recipient_keys: strings(members.get("recipientKeys")),
+            routing_keys: strings(members.get("routingKeys")),
+            priority: members
+                .get("priority")
+                .or_else(|| self.extra.get("priority"))
+                .and_then(Value::as_u64)
+                .unwrap_or(0),
+        };
+
+        if service_type != DidCommType::Messaging {
+            return self
+                .endpoint()
+                .uris()
+                .first()
+                .map(|uri| endpoint(uri, &self.extra))
+                .into_iter()
+                .collect();
+        }
+
+        let entries = match self.endpoint() {
+            Endpoint::Set(set) => set.iter().collect(),
+            single => vec![single],
+        };
+        entries
+            .into_iter()
+            .filter_map(|entry| match entry {
+                Endpoint::Uri(uri) => Some(endpoint(uri, &self.extra)),
+                Endpoint::Map(map) => {
+                    let uri = map.get("uri").and_then(Value::as_str)?;
+                    Some(endpoint(uri, map))
+                }
+                Endpoint::Set(_) => None,
+            })
+            .map(|mut e| {
+                e.recipient_keys.clear();
+                e
+            })
+            .collect()
+    }
+}
+
+/// The most preferred DIDComm endpoint of `doc` that accepts one of the
+/// media types in `accept`, e.g. `didcomm/v2`. Endpoints without `accept`
+/// are taken to accept anything, and so is every endpoint if `accept` is
+/// empty.
+pub fn select_didcomm(doc: &Document, accept: &[&str]) -> Option<DidCommEndpoint> {
+    doc.didcomm_endpoints().into_iter().find(|e| {
+        accept.is_empty()
+            || e.accept.is_empty()
+            || e.accept.iter().any(|a| accept.contains(&a.as_str()))
+    })
+}
+
+fn strings(value: Option<&Value>) -> Vec<String> {
+    value
+        .and_then(Value::as_array)
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_owned)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Finds the key a DIDComm service refers to: a key embedded in `doc`, a
+/// did:key DID URL, or for `IndyAgent` a raw base58 key, which is matched
+/// against the keys of `doc` or else taken to be an Ed25519 key.
+fn resolve_key(doc: &Document, reference: &str) -> Result<PublicKey, DidError> {
+    let dangling = || err_msg(DidErrorKind::DanglingReference, reference.to_owned());
+
+    if !reference.contains(':') && !reference.contains('#') {
+        let bytes = bs58::decode(reference).into_vec().map_err(|_| dangling())?;
+        let embedded = doc
+            .public_key()
+            .iter()
+            .chain(doc.authentication().iter())
+            .chain(doc.key_agreement().iter())
+            .filter(|k| !k.reference())
+            .find(|k| k.key_bytes().ok().as_ref() == Some(&bytes));
+        return Ok(match embedded {
+            Some(key) => key.clone(),
+            None => PublicKey::new(
+                &format!("{}#{}", doc.subject().as_uri(), reference),
+                PublicKeyType::Ed25519VerificationKey2018,
+                &doc.subject().as_uri().to_string(),
+                PublicKeyEncoding::Base58,
+                reference,
+            )?,
+        });
+    }
+
+    let uri = Uri::from_str(reference).map_err(|_| dangling())?;
+    if let Some(Resource::PublicKey(key)) = doc.dereference(&uri) {
+        return Ok(key.clone());
+    }
+    if uri.method != "key" {
+        return Err(dangling());
+    }
+
+    let (_, bytes) = multibase::decode(&uri.id).map_err(|_| dangling())?;
+    let key_type = match Multicodec::decode(&bytes).map(|(codec, _)| codec) {
+        Some(Multicodec::Ed25519Pub) => PublicKeyType::Ed25519VerificationKey2020,
+        Some(Multicodec::X25519Pub) => PublicKeyType::X25519KeyAgreementKey2020,
+        Some(_) => PublicKeyType::Multikey,
+        None => return Err(dangling()),
+    };
+    PublicKey::new(
+        reference,
+        key_type,
+        &uri.did().to_string(),
+        PublicKeyEncoding::Multibase,
+        &uri.id,
+    )
+}
@@ -0,0 +1,127 @@
+use crate::error::{DidError, DidErrorKind};
+use crate::fields::spki::rsa_public_key;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use indexmap::IndexMap;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// A JSON Web Key (RFC 7517) as found in `publicKeyJwk`.
+///
+/// Members other than the ones modelled here are kept in `extra` so that the
+/// key serializes back the way it was read.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
+pub struct Jwk {
+    pub kty: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub crv: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub x: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub y: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub n: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub e: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub kid: Option<String>,
+    #[serde(rename = "use", skip_serializing_if = "Option::is_none", default)]
+    pub key_use: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub alg: Option<String>,
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
+}
+
+impl Jwk {
+    /// Checks that the members required for the key type are present: `crv`
+    /// and `x` for `OKP`, `crv`, `x` and `y` for `EC`, and `n` and `e` for
+    /// `RSA`. Other key types cannot describe a public key.
+    pub fn validate(&self) -> Result<(), DidError> {
+        match self.defect() {
+            Some(defect) => Err(malformed(defect)),
+            None => Ok(()),
+        }
+    }
+
+    /// What `validate` objects to, if anything.
+    pub(crate) fn defect(&self) -> Option<String> {
+        let required: &[(&str, &Option<String>)] = match self.kty.as_str() {
+            "OKP" => &[("crv", &self.crv), ("x", &self.x)],
+            "EC" => &[("crv", &self.crv), ("x", &self.x), ("y", &self.y)],
+            "RSA" => &[("n", &self.n), ("e", &self.e)],
+            kty => return Some(format!("unsupported kty {:?}", kty)),
+        };
+
+        required
+            .iter()
+            .find(|(_, value)| value.is_none())
+            .map(|(member, _)| format!("{} key is missing {:?}", self.kty, member))
+    }
+
+    /// Decodes the raw public key: `x` for `OKP` keys, the uncompressed SEC1
+    /// point for `EC` keys and the PKCS#1 `RSAPublicKey` for `RSA` keys.
+    pub fn key_bytes(&self) -> Result<Vec<u8>, DidError> {
+        self.validate()?;
+        match self.kty.as_str() {
+            "OKP" => member(&self.x),
+            "EC" => {
+                let x = member(&self.x)?;
+                let y = member(&self.y)?;
+                if x.len() != y.len() {
+                    return Err(malformed("x and y differ in length"));
+                }
+                let mut point = vec![0x04];
+                point.extend(x);
+                point.extend(y);
+                Ok(point)
+            }
+            _ => Ok(rsa_public_key(&member(&self.n)?, &member(&self.e)?)),
+        }
+    }
+
+    /// The RFC 7638 thumbprint: the base64url encoded SHA-256 hash of the
+    /// required members, in lexicographic order and without whitespace.
+    pub fn thumbprint(&self) -> Result<String, DidError> {
+        self.validate()?;
+        let required: &[(&str, &Option<String>)] = match self.kty.as_str() {
+            "OKP" => &[("crv", &self.crv), ("x", &self.x)],
+            "EC" => &[("crv", &self.crv), ("x", &self.x), ("y", &self.y)],
+            _ => &[("e", &self.e), ("n", &self.n)],
+        };
+
+        let mut members = required
+            .iter()
+            .map(|(name, value)| (*name, value.as_deref().unwrap_or("")))
+            .chain(Some(("kty", self.kty.as_str())))
+            .collect::<Vec<_>>();
+        members.sort();
+        let json = members
+            .iter()
+            .map(|(name, value)| format!("{}:{}", Value::from(*name), Value::from(*value)))
+            .collect::<Vec<_>>()
+            .join(",");
+        Ok(URL_SAFE_NO_PAD.encode(Sha256::digest(format!("{{{}}}", json))))
+    }
+}
+
+fn member(value: &Option<String>) -> Result<Vec<u8>, DidError> {
+    let value = value.as_deref().unwrap_or("");
+    let bytes = URL_SAFE_NO_PAD
+        .decode(value.trim_end_matches('='))
+        .map_err(malformed)?;
+    if bytes.is_empty() {
+        return Err(malformed("empty key member"));
+    }
+    Ok(bytes)
+}
+
+fn malformed<D: std::fmt::Display>(cause: D) -> DidError {
+    DidError::from_msg(
+        DidErrorKind::MalformedKeyData {
+            encoding: "publicKeyJwk".to_owned(),
+        },
+        cause.to_string(),
+    )
+}
@@ -0,0 +1,70 @@
+use crate::error::DidError;
+use crate::fields::{Endpoint, ServiceEndpoint, Subject};
+use crate::uri::Uri;
+use indexmap::IndexMap;
+use serde_json::Value;
+use std::str::FromStr;
+
+pub const LINKED_DOMAINS: &str = "LinkedDomains";
+
+/// A `LinkedDomains` service, listing the web origins the DID subject
+/// claims to control. The claim is only proven by a Domain Linkage
+/// credential served by each origin, see `domain_linkage`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LinkedDomains {
+    pub id: Subject,
+    pub origins: Vec<String>,
+}
+
+impl LinkedDomains {
+    /// Fails if `id` is neither a DID URL nor a relative fragment.
+    pub fn new(id: &str, origins: Vec<String>) -> Result<Self, DidError> {
+        Ok(LinkedDomains {
+            id: Uri::from_str(id)?.into(),
+            origins,
+        })
+    }
+
+    /// The service, with a single origin as a plain URI and several as an
+    /// `origins` map.
+    pub fn to_service(&self) -> ServiceEndpoint {
+        let endpoint = match self.origins.as_slice() {
+            [origin] => Endpoint::Uri(origin.clone()),
+            origins => {
+                let mut map = IndexMap::new();
+                map.insert("origins".to_owned(), Value::from(origins.to_vec()));
+                Endpoint::Map(map)
+            }
+        };
+        ServiceEndpoint::with_subject(self.id.clone(), LINKED_DOMAINS, endpoint)
+    }
+}
+
+impl ServiceEndpoint {
+    /// The service as a `LinkedDomains` service, if it is one. Its origins
+    /// are the URIs of `serviceEndpoint` and the members of an `origins`
+    /// map.
+    pub fn linked_domains(&self) -> Option<LinkedDomains> {
+        if self.kind() != LINKED_DOMAINS {
+            return None;
+        }
+        let mut origins: Vec<String> = self
+            .endpoint()
+            .uris()
+            .into_iter()
+            .map(str::to_owned)
+            .collect();
+        if let Some(listed) = self
+            .endpoint()
+            .as_map()
+            .and_then(|map| map.get("origins"))
+            .and_then(Value::as_array)
+        {
+            origins.extend(listed.iter().filter_map(Value::as_str).map(str::to_owned));
+        }
+        Some(LinkedDomains {
+            id: self.subject().clone(),
+            origins,
+        })
+    }
+}
@@ -1,11 +1,22 @@
-pub use self::context::Context;
+pub use self::algorithm::KeyAlgorithm;
+pub use self::context::{Context, ContextEntry, KnownContext};
+pub use self::didcomm::{select_didcomm, DidCommEndpoint, DidCommType};
 pub use self::helpers::{string_or_list, string_or_struct};
+pub use self::jwk::Jwk;
+pub use self::linked_domains::{LinkedDomains, LINKED_DOMAINS};
+pub use self::multicodec::Multicodec;
 pub use self::publickey::{PublicKey, PublicKeyEncoding, PublicKeyType};
-pub use self::service_endpoint::ServiceEndpoint;
+pub use self::service_endpoint::{Endpoint, ServiceEndpoint};
 pub use self::subject::Subject;
 
+mod algorithm;
 mod context;
+mod didcomm;
 mod helpers;
+mod jwk;
+mod linked_domains;
+mod multicodec;
 mod publickey;
 mod service_endpoint;
+mod spki;
 mod subject;
@@ -0,0 +1,79 @@
+/// The multicodec public key codecs used by `publicKeyMultibase` values and
+/// did:key identifiers. See https://github.com/multiformats/multicodec.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Multicodec {
+    Ed25519Pub,
+    X25519Pub,
+    Secp256k1Pub,
+    P256Pub,
+    P384Pub,
+    Bls12381G1Pub,
+    Bls12381G2Pub,
+    RsaPub,
+}
+
+const CODECS: &[Multicodec] = &[
+    Multicodec::Ed25519Pub,
+    Multicodec::X25519Pub,
+    Multicodec::Secp256k1Pub,
+    Multicodec::P256Pub,
+    Multicodec::P384Pub,
+    Multicodec::Bls12381G1Pub,
+    Multicodec::Bls12381G2Pub,
+    Multicodec::RsaPub,
+];
+
+impl Multicodec {
+    /// The unsigned varint prefix identifying the codec.
+    pub fn prefix(self) -> &'static [u8] {
+        match self {
+            Multicodec::Ed25519Pub => &[0xed, 0x01],
+            Multicodec::X25519Pub => &[0xec, 0x01],
+            Multicodec::Secp256k1Pub => &[0xe7, 0x01],
+            Multicodec::P256Pub => &[0x80, 0x24],
+            Multicodec::P384Pub => &[0x81, 0x24],
+            Multicodec::Bls12381G1Pub => &[0xea, 0x01],
+            Multicodec::Bls12381G2Pub => &[0xeb, 0x01],
+            Multicodec::RsaPub => &[0x85, 0x24],
+        }
+    }
+
+    /// The lengths a key of this codec may have, empty if it varies.
+    /// Elliptic curve keys may be compressed or uncompressed points.
+    pub fn key_lens(self) -> &'static [usize] {
+        match self {
+            Multicodec::Ed25519Pub | Multicodec::X25519Pub => &[32],
+            Multicodec::Secp256k1Pub | Multicodec::P256Pub => &[33, 65],
+            Multicodec::P384Pub => &[49, 97],
+            Multicodec::Bls12381G1Pub => &[48],
+            Multicodec::Bls12381G2Pub => &[96],
+            Multicodec::RsaPub => &[],
+        }
+    }
+
+    /// Prepends the codec prefix to `key`.
+    pub fn encode(self, key: &[u8]) -> Vec<u8> {
+        let mut bytes = self.prefix().to_vec();
+        bytes.extend_from_slice(key);
+        bytes
+    }
+
+    /// Splits a known codec prefix off `bytes` if the rest has the length a
+    /// key of that codec must have.
+    pub fn decode(bytes: &[u8]) -> Option<(Multicodec, &[u8])> {
+        Multicodec::split(bytes).filter(|(codec, key)| codec.fits(key))
+    }
+
+    /// Splits a known codec prefix off `bytes`, whatever follows it.
+    pub(crate) fn split(bytes: &[u8]) -> Option<(Multicodec, &[u8])> {
+        CODECS
+            .iter()
+            .find_map(|&codec| bytes.strip_prefix(codec.prefix()).map(|key| (codec, key)))
+    }
+
+    /// Whether `key` has a length a key of this codec may have.
+    pub(crate) fn fits(self, key: &[u8]) -> bool {
+        let lens = self.key_lens();
+        lens.is_empty() || lens.contains(&key.len())
+    }
+}
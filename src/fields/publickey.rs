@@ -1,28 +1,226 @@
-use crate::fields::Subject;
+use crate::error::{DidError, DidErrorKind};
+use crate::fields::algorithm::ed25519_to_x25519;
+use crate::fields::spki::{pem_decode, pem_encode, rsa_components, SubjectPublicKeyInfo};
+use crate::fields::{Jwk, KeyAlgorithm, KnownContext, Multicodec, Subject};
+use crate::uri::Uri;
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use indexmap::IndexMap;
 use serde::de::{self, Deserialize, Deserializer, MapAccess, Visitor};
-use serde::ser::{Serialize, SerializeStruct, Serializer};
-use serde_derive::{Deserialize, Serialize};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use serde_json::Value;
 use std::default::Default;
 use std::fmt;
 use std::str::FromStr;
+use void::Void;
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
-#[serde(rename_all = "PascalCase")]
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
 pub enum PublicKeyType {
+    #[default]
     UnknownKey,
     Ed25519VerificationKey2018,
+    Ed25519VerificationKey2020,
+    X25519KeyAgreementKey2019,
+    X25519KeyAgreementKey2020,
     RsaVerificationKey2018,
     EcdsaSecp256k1VerificationKey2019,
+    EcdsaSecp256k1RecoveryMethod2020,
+    EcdsaSecp256r1VerificationKey2019,
+    EcdsaSecp384r1VerificationKey2019,
+    Bls12381G1Key2020,
+    Bls12381G2Key2020,
+    JsonWebKey2020,
+    Multikey,
+    /// A type this crate does not know, kept as it was written.
+    Other(String),
 }
 
-impl Default for PublicKeyType {
-    fn default() -> Self {
-        PublicKeyType::UnknownKey
+const KNOWN_TYPES: &[PublicKeyType] = &[
+    PublicKeyType::UnknownKey,
+    PublicKeyType::Ed25519VerificationKey2018,
+    PublicKeyType::Ed25519VerificationKey2020,
+    PublicKeyType::X25519KeyAgreementKey2019,
+    PublicKeyType::X25519KeyAgreementKey2020,
+    PublicKeyType::RsaVerificationKey2018,
+    PublicKeyType::EcdsaSecp256k1VerificationKey2019,
+    PublicKeyType::EcdsaSecp256k1RecoveryMethod2020,
+    PublicKeyType::EcdsaSecp256r1VerificationKey2019,
+    PublicKeyType::EcdsaSecp384r1VerificationKey2019,
+    PublicKeyType::Bls12381G1Key2020,
+    PublicKeyType::Bls12381G2Key2020,
+    PublicKeyType::JsonWebKey2020,
+    PublicKeyType::Multikey,
+];
+
+impl PublicKeyType {
+    /// The name of the type as it appears in the `type` member.
+    pub fn as_str(&self) -> &str {
+        match self {
+            PublicKeyType::UnknownKey => "UnknownKey",
+            PublicKeyType::Ed25519VerificationKey2018 => "Ed25519VerificationKey2018",
+            PublicKeyType::Ed25519VerificationKey2020 => "Ed25519VerificationKey2020",
+            PublicKeyType::X25519KeyAgreementKey2019 => "X25519KeyAgreementKey2019",
+            PublicKeyType::X25519KeyAgreementKey2020 => "X25519KeyAgreementKey2020",
+            PublicKeyType::RsaVerificationKey2018 => "RsaVerificationKey2018",
+            PublicKeyType::EcdsaSecp256k1VerificationKey2019 => "EcdsaSecp256k1VerificationKey2019",
+            PublicKeyType::EcdsaSecp256k1RecoveryMethod2020 => "EcdsaSecp256k1RecoveryMethod2020",
+            PublicKeyType::EcdsaSecp256r1VerificationKey2019 => "EcdsaSecp256r1VerificationKey2019",
+            PublicKeyType::EcdsaSecp384r1VerificationKey2019 => "EcdsaSecp384r1VerificationKey2019",
+            PublicKeyType::Bls12381G1Key2020 => "Bls12381G1Key2020",
+            PublicKeyType::Bls12381G2Key2020 => "Bls12381G2Key2020",
+            PublicKeyType::JsonWebKey2020 => "JsonWebKey2020",
+            PublicKeyType::Multikey => "Multikey",
+            PublicKeyType::Other(name) => name,
+        }
+    }
+
+    /// The algorithm of the keys this type describes, if it is specific to
+    /// one.
+    pub fn algorithm(&self) -> Option<KeyAlgorithm> {
+        match self {
+            PublicKeyType::UnknownKey
+            | PublicKeyType::JsonWebKey2020
+            | PublicKeyType::Multikey
+            | PublicKeyType::Other(_) => None,
+            PublicKeyType::Ed25519VerificationKey2018
+            | PublicKeyType::Ed25519VerificationKey2020 => Some(KeyAlgorithm::Ed25519),
+            PublicKeyType::X25519KeyAgreementKey2019 | PublicKeyType::X25519KeyAgreementKey2020 => {
+                Some(KeyAlgorithm::X25519)
+            }
+            PublicKeyType::RsaVerificationKey2018 => Some(KeyAlgorithm::Rsa),
+            PublicKeyType::EcdsaSecp256k1VerificationKey2019
+            | PublicKeyType::EcdsaSecp256k1RecoveryMethod2020 => Some(KeyAlgorithm::Secp256k1),
+            PublicKeyType::EcdsaSecp256r1VerificationKey2019 => Some(KeyAlgorithm::P256),
+            PublicKeyType::EcdsaSecp384r1VerificationKey2019 => Some(KeyAlgorithm::P384),
+            PublicKeyType::Bls12381G1Key2020 => Some(KeyAlgorithm::Bls12381G1),
+            PublicKeyType::Bls12381G2Key2020 => Some(KeyAlgorithm::Bls12381G2),
+        }
+    }
+
+    /// The encodings the type's specification allows for its key data.
+    /// Anything goes for `UnknownKey` and types this crate does not know.
+    pub fn encodings(&self) -> &'static [PublicKeyEncoding] {
+        use PublicKeyEncoding::*;
+        match self {
+            PublicKeyType::UnknownKey | PublicKeyType::Other(_) => &[
+                Unknown,
+                Pem,
+                Jwk,
+                Hex,
+                Base64,
+                Base58,
+                Multibase,
+                EthereumAddress,
+            ],
+            PublicKeyType::Ed25519VerificationKey2018 => &[Base58, Jwk],
+            PublicKeyType::X25519KeyAgreementKey2019 => &[Base58, Jwk],
+            PublicKeyType::Ed25519VerificationKey2020
+            | PublicKeyType::X25519KeyAgreementKey2020
+            | PublicKeyType::Multikey => &[Multibase],
+            PublicKeyType::RsaVerificationKey2018 => &[Pem, Jwk],
+            PublicKeyType::EcdsaSecp256k1VerificationKey2019 => &[Hex, Base58, Jwk, Multibase],
+            PublicKeyType::EcdsaSecp256k1RecoveryMethod2020 => &[EthereumAddress, Hex, Jwk],
+            PublicKeyType::EcdsaSecp256r1VerificationKey2019
+            | PublicKeyType::EcdsaSecp384r1VerificationKey2019 => &[Jwk, Multibase],
+            PublicKeyType::Bls12381G1Key2020 | PublicKeyType::Bls12381G2Key2020 => &[Base58, Jwk],
+            PublicKeyType::JsonWebKey2020 => &[Jwk],
+        }
+    }
+
+    pub fn permits(&self, encoding: PublicKeyEncoding) -> bool {
+        self.encodings().contains(&encoding)
+    }
+
+    /// A type for keys of `algorithm` that permits `encoding`, falling back
+    /// to `JsonWebKey2020` for JWKs and `Multikey` for multibase keys.
+    pub fn for_encoding(algorithm: KeyAlgorithm, encoding: PublicKeyEncoding) -> Option<Self> {
+        use PublicKeyType::*;
+        let types: &[PublicKeyType] = match algorithm {
+            KeyAlgorithm::Ed25519 => &[Ed25519VerificationKey2018, Ed25519VerificationKey2020],
+            KeyAlgorithm::X25519 => &[X25519KeyAgreementKey2019, X25519KeyAgreementKey2020],
+            KeyAlgorithm::Secp256k1 => &[
+                EcdsaSecp256k1VerificationKey2019,
+                EcdsaSecp256k1RecoveryMethod2020,
+            ],
+            KeyAlgorithm::P256 => &[EcdsaSecp256r1VerificationKey2019],
+            KeyAlgorithm::P384 => &[EcdsaSecp384r1VerificationKey2019],
+            KeyAlgorithm::Rsa => &[RsaVerificationKey2018],
+            KeyAlgorithm::Bls12381G1 => &[Bls12381G1Key2020],
+            KeyAlgorithm::Bls12381G2 => &[Bls12381G2Key2020],
+        };
+        match types.iter().find(|t| t.permits(encoding)) {
+            Some(key_type) => Some(key_type.clone()),
+            None if encoding == PublicKeyEncoding::Jwk => Some(JsonWebKey2020),
+            None if encoding == PublicKeyEncoding::Multibase => Some(Multikey),
+            None => None,
+        }
+    }
+
+    /// The context a document with keys of this type is expected to name,
+    /// if the type is defined by a known context.
+    pub fn context(&self) -> Option<KnownContext> {
+        match self {
+            PublicKeyType::Ed25519VerificationKey2018 => Some(KnownContext::Ed25519Signature2018),
+            PublicKeyType::Ed25519VerificationKey2020 => Some(KnownContext::Ed25519Signature2020),
+            PublicKeyType::X25519KeyAgreementKey2019 => Some(KnownContext::X25519KeyAgreement2019),
+            PublicKeyType::X25519KeyAgreementKey2020 => Some(KnownContext::X25519KeyAgreement2020),
+            PublicKeyType::RsaVerificationKey2018 => Some(KnownContext::SecurityV2),
+            PublicKeyType::EcdsaSecp256k1VerificationKey2019 => {
+                Some(KnownContext::EcdsaSecp256k1Signature2019)
+            }
+            PublicKeyType::EcdsaSecp256k1RecoveryMethod2020 => {
+                Some(KnownContext::EcdsaSecp256k1Recovery2020)
+            }
+            PublicKeyType::Bls12381G1Key2020 | PublicKeyType::Bls12381G2Key2020 => {
+                Some(KnownContext::Bls12381Signature2020)
+            }
+            PublicKeyType::JsonWebKey2020 => Some(KnownContext::JsonWebSignature2020),
+            PublicKeyType::Multikey => Some(KnownContext::Multikey),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for PublicKeyType {
+    type Err = Void;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(KNOWN_TYPES
+            .iter()
+            .find(|t| t.as_str() == s)
+            .cloned()
+            .unwrap_or_else(|| PublicKeyType::Other(s.to_owned())))
+    }
+}
+
+impl fmt::Display for PublicKeyType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+impl Serialize for PublicKeyType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for PublicKeyType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        Ok(PublicKeyType::from_str(&name).unwrap())
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
 pub enum PublicKeyEncoding {
+    #[default]
     Unknown,
     Pem,
     Jwk,
@@ -33,9 +231,19 @@ pub enum PublicKeyEncoding {
     EthereumAddress,
 }
 
-impl Default for PublicKeyEncoding {
-    fn default() -> Self {
-        PublicKeyEncoding::Unknown
+impl PublicKeyEncoding {
+    /// The name of the `PublicKey` member holding key data in this encoding.
+    pub fn field_name(&self) -> &'static str {
+        match self {
+            PublicKeyEncoding::Unknown => "publicKeyUnknown",
+            PublicKeyEncoding::Pem => "publicKeyPem",
+            PublicKeyEncoding::Jwk => "publicKeyJwk",
+            PublicKeyEncoding::Hex => "publicKeyHex",
+            PublicKeyEncoding::Base64 => "publicKeyBase64",
+            PublicKeyEncoding::Base58 => "publicKeyBase58",
+            PublicKeyEncoding::Multibase => "publicKeyMultibase",
+            PublicKeyEncoding::EthereumAddress => "ethereumAddress",
+        }
     }
 }
 
@@ -57,17 +265,93 @@ impl FromStr for PublicKeyEncoding {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PublicKey {
     id: Subject,
     key_type: PublicKeyType,
     controller: Subject,
     key_data_type: PublicKeyEncoding,
     key_data: String,
+    jwk: Option<Box<Jwk>>,
     reference: bool,
+    pub extra: IndexMap<String, Value>,
 }
 
 impl PublicKey {
+    /// A key with its data in `encoding`, which cannot be `Jwk`; use
+    /// `from_jwk` for those. Fails if `id` or `controller` is not a DID URL.
+    pub fn new(
+        id: &str,
+        key_type: PublicKeyType,
+        controller: &str,
+        encoding: PublicKeyEncoding,
+        key_data: &str,
+    ) -> Result<Self, DidError> {
+        if encoding == PublicKeyEncoding::Jwk {
+            return Err(DidError::from_msg(
+                DidErrorKind::UnsupportedEncoding {
+                    encoding: encoding.field_name().to_owned(),
+                },
+                "JWK keys are made with PublicKey::from_jwk",
+            ));
+        }
+        PublicKey::embedded(id, key_type, controller, encoding, key_data, None)
+    }
+
+    pub fn from_jwk(
+        id: &str,
+        key_type: PublicKeyType,
+        controller: &str,
+        jwk: Jwk,
+    ) -> Result<Self, DidError> {
+        let jwk = Some(Box::new(jwk));
+        PublicKey::embedded(id, key_type, controller, PublicKeyEncoding::Jwk, "", jwk)
+    }
+
+    fn embedded(
+        id: &str,
+        key_type: PublicKeyType,
+        controller: &str,
+        encoding: PublicKeyEncoding,
+        key_data: &str,
+        jwk: Option<Box<Jwk>>,
+    ) -> Result<Self, DidError> {
+        Ok(PublicKey {
+            id: Uri::from_str(id)?.into(),
+            key_type,
+            controller: Uri::from_str(controller)?.into(),
+            key_data_type: encoding,
+            key_data: key_data.to_owned(),
+            jwk,
+            reference: false,
+            extra: IndexMap::default(),
+        })
+    }
+
+    /// A reference to a key embedded elsewhere, as listed under a
+    /// verification relationship.
+    pub fn reference_to(id: &Uri) -> Self {
+        PublicKey {
+            id: id.clone().into(),
+            key_type: PublicKeyType::default(),
+            controller: Subject::default(),
+            key_data_type: PublicKeyEncoding::Unknown,
+            key_data: "".to_owned(),
+            jwk: None,
+            reference: true,
+            extra: IndexMap::default(),
+        }
+    }
+
+    /// The same key under another id, e.g. one made from its `fingerprint`.
+    /// Fails if `id` is not a DID URL.
+    pub fn with_id(self, id: &str) -> Result<Self, DidError> {
+        Ok(PublicKey {
+            id: Uri::from_str(id)?.into(),
+            ..self
+        })
+    }
+
     pub fn subject(&self) -> &Subject {
         &self.id
     }
@@ -77,13 +361,24 @@ impl PublicKey {
     }
 
     pub fn kind(&self) -> PublicKeyType {
-        self.key_type
+        self.key_type.clone()
+    }
+
+    /// Borrows the type, which `kind` clones.
+    pub fn key_type(&self) -> &PublicKeyType {
+        &self.key_type
     }
 
+    /// The key data for every encoding but `Jwk`, for which it is empty.
     pub fn data(&self) -> &String {
         &self.key_data
     }
 
+    /// The key for the `Jwk` encoding.
+    pub fn jwk(&self) -> Option<&Jwk> {
+        self.jwk.as_deref()
+    }
+
     pub fn encoding(&self) -> PublicKeyEncoding {
         self.key_data_type
     }
@@ -91,6 +386,351 @@ impl PublicKey {
     pub fn reference(&self) -> bool {
         self.reference
     }
+
+    /// Decodes the key data into the raw public key.
+    ///
+    /// Multibase values have their multicodec prefix removed when it is one
+    /// of the known public key codecs. PEM values may hold a
+    /// `SubjectPublicKeyInfo`, which yields the contents of its
+    /// `subjectPublicKey` (the key itself for Ed25519 and X25519, the SEC1
+    /// point for elliptic curve keys and the PKCS#1 `RSAPublicKey` for RSA),
+    /// or a PKCS#1 `RSA PUBLIC KEY`, which is returned unchanged.
+    pub fn key_bytes(&self) -> Result<Vec<u8>, DidError> {
+        self.decode().map(|(_, bytes)| bytes)
+    }
+
+    /// The algorithm of the key, taken from its type or, for types that do
+    /// not name one, from the multicodec, PEM or JWK key data.
+    pub fn algorithm(&self) -> Option<KeyAlgorithm> {
+        self.key_type
+            .algorithm()
+            .or_else(|| self.decode().ok().and_then(|(algorithm, _)| algorithm))
+    }
+
+    /// Re-encodes the key material in another encoding.
+    ///
+    /// Hex, Base64 and Base58 take the raw key as returned by `key_bytes`.
+    /// Multibase, PEM and JWK need to know the key algorithm, and elliptic
+    /// curve keys are compressed for Multibase and uncompressed for JWK.
+    /// Only secp256k1 keys can be turned into an Ethereum address, and
+    /// nothing can be recovered from one.
+    ///
+    /// A key whose type does not permit the new encoding takes the type
+    /// `PublicKeyType::for_encoding` picks, e.g. an
+    /// `Ed25519VerificationKey2018` becomes an `Ed25519VerificationKey2020`
+    /// in multibase. There is none for Ed25519 keys in hex or PEM, so those
+    /// conversions fail.
+    pub fn to_encoding(&self, encoding: PublicKeyEncoding) -> Result<PublicKey, DidError> {
+        if encoding == self.key_data_type {
+            return Ok(self.clone());
+        }
+        let unsupported = || self.unsupported(encoding);
+        let (algorithm, bytes) = self.decode_checked(encoding)?;
+        let key_type = if self.key_type.permits(encoding) {
+            self.key_type.clone()
+        } else {
+            algorithm
+                .and_then(|algorithm| PublicKeyType::for_encoding(algorithm, encoding))
+                .ok_or_else(unsupported)?
+        };
+
+        let mut jwk = None;
+        let key_data = match encoding {
+            PublicKeyEncoding::Hex => hex::encode(&bytes),
+            PublicKeyEncoding::Base64 => STANDARD.encode(&bytes),
+            PublicKeyEncoding::Base58 => bs58::encode(&bytes).into_string(),
+            PublicKeyEncoding::Multibase => multikey(algorithm.ok_or_else(unsupported)?, bytes),
+            PublicKeyEncoding::Pem => {
+                let (oid, curve) = algorithm
+                    .and_then(KeyAlgorithm::spki_oids)
+                    .ok_or_else(unsupported)?;
+                let info = SubjectPublicKeyInfo {
+                    algorithm: oid.to_vec(),
+                    curve: curve.map(<[u8]>::to_vec),
+                    key: bytes,
+                };
+                pem_encode("PUBLIC KEY", &info.to_der())
+            }
+            PublicKeyEncoding::Jwk => {
+                let (algorithm, (kty, crv)) = algorithm
+                    .and_then(|algorithm| Some((algorithm, algorithm.jwk_params()?)))
+                    .ok_or_else(unsupported)?;
+                let mut value = Jwk {
+                    kty: kty.to_owned(),
+                    crv: crv.map(str::to_owned),
+                    ..Jwk::default()
+                };
+                self.fill_jwk(&mut value, algorithm, &bytes)?;
+                jwk = Some(Box::new(value));
+                String::new()
+            }
+            PublicKeyEncoding::EthereumAddress => match algorithm {
+                Some(KeyAlgorithm::Secp256k1) => {
+                    let point = KeyAlgorithm::Secp256k1
+                        .uncompress(&bytes)
+                        .ok_or_else(|| self.malformed("not a point on secp256k1"))?;
+                    ethereum_address(&point)
+                }
+                _ => return Err(unsupported()),
+            },
+            PublicKeyEncoding::Unknown => return Err(unsupported()),
+        };
+
+        Ok(PublicKey {
+            key_type,
+            key_data_type: encoding,
+            key_data,
+            jwk,
+            ..self.clone()
+        })
+    }
+
+    /// The multibase encoded multicodec key used as the method specific id
+    /// of did:key, e.g. `z6Mk...` for Ed25519 keys. It only depends on the
+    /// key material, so it makes a stable key id whatever the encoding.
+    pub fn fingerprint(&self) -> Result<String, DidError> {
+        let to = PublicKeyEncoding::Multibase;
+        let (algorithm, bytes) = self.decode_checked(to)?;
+        Ok(multikey(
+            algorithm.ok_or_else(|| self.unsupported(to))?,
+            bytes,
+        ))
+    }
+
+    /// The RFC 7638 thumbprint of the key as a JWK. Keys in other encodings
+    /// are converted first.
+    pub fn jwk_thumbprint(&self) -> Result<String, DidError> {
+        match &self.jwk {
+            Some(jwk) => jwk.thumbprint(),
+            None => self.to_encoding(PublicKeyEncoding::Jwk)?.jwk_thumbprint(),
+        }
+    }
+
+    /// The X25519 key agreement key for the secret of an Ed25519 key, as
+    /// DIDComm stacks derive it.
+    ///
+    /// The new key has the key agreement counterpart of the key's type,
+    /// keeps the encoding where that type permits it, and is identified by
+    /// its fingerprint in place of the fragment of the key's id.
+    pub fn to_x25519(&self) -> Result<PublicKey, DidError> {
+        if self.algorithm() != Some(KeyAlgorithm::Ed25519) {
+            return Err(DidError::from_kind(DidErrorKind::UnsupportedConversion {
+                from: self.key_type.to_string(),
+                to: "X25519".to_owned(),
+            }));
+        }
+        let key_type = match &self.key_type {
+            PublicKeyType::Ed25519VerificationKey2018 => PublicKeyType::X25519KeyAgreementKey2019,
+            PublicKeyType::Ed25519VerificationKey2020 => PublicKeyType::X25519KeyAgreementKey2020,
+            other => other.clone(),
+        };
+        let encoding = if key_type.permits(self.key_data_type) {
+            self.key_data_type
+        } else {
+            key_type.encodings()[0]
+        };
+
+        let (_, bytes) = self.decode_checked(encoding)?;
+        let key =
+            ed25519_to_x25519(&bytes).ok_or_else(|| self.malformed("not an Ed25519 point"))?;
+        let derived = PublicKey {
+            key_type: PublicKeyType::X25519KeyAgreementKey2019,
+            key_data_type: PublicKeyEncoding::Hex,
+            key_data: hex::encode(key),
+            jwk: None,
+            extra: IndexMap::default(),
+            ..self.clone()
+        }
+        .to_encoding(encoding)?;
+
+        let id = self.id.as_uri().to_string();
+        let base = id.split('#').next().unwrap_or("");
+        let id = format!("{}#{}", base, derived.fingerprint()?);
+        Ok(PublicKey {
+            key_type,
+            ..derived.with_id(&id)?
+        })
+    }
+
+    /// Decodes the key data for conversion to `to`, checking that it agrees
+    /// with the key type and has a valid length.
+    fn decode_checked(
+        &self,
+        to: PublicKeyEncoding,
+    ) -> Result<(Option<KeyAlgorithm>, Vec<u8>), DidError> {
+        if self.reference
+            || self.key_data_type == PublicKeyEncoding::Unknown
+            || self.key_data_type == PublicKeyEncoding::EthereumAddress
+        {
+            return Err(self.unsupported(to));
+        }
+
+        let (hint, bytes) = self.decode()?;
+        let algorithm = match (self.key_type.algorithm(), hint) {
+            (Some(expected), Some(found)) if expected != found => {
+                return Err(self.malformed(format!("key data is not a {:?} key", expected)))
+            }
+            (expected, found) => expected.or(found),
+        };
+        if let Some(algorithm) = algorithm {
+            if !algorithm.accepts(&bytes) {
+                return Err(self.malformed(format!(
+                    "{} bytes is not a valid {:?} key length",
+                    bytes.len(),
+                    algorithm
+                )));
+            }
+        }
+        Ok((algorithm, bytes))
+    }
+
+    /// Sets the JWK members holding a raw key.
+    fn fill_jwk(
+        &self,
+        jwk: &mut Jwk,
+        algorithm: KeyAlgorithm,
+        bytes: &[u8],
+    ) -> Result<(), DidError> {
+        match algorithm {
+            KeyAlgorithm::Rsa => {
+                let (n, e) = rsa_components(bytes)
+                    .ok_or_else(|| self.malformed("not a PKCS#1 RSAPublicKey"))?;
+                jwk.n = Some(URL_SAFE_NO_PAD.encode(n));
+                jwk.e = Some(URL_SAFE_NO_PAD.encode(e));
+            }
+            KeyAlgorithm::Secp256k1 | KeyAlgorithm::P256 | KeyAlgorithm::P384 => {
+                let point = algorithm
+                    .uncompress(bytes)
+                    .ok_or_else(|| self.malformed(format!("not a point on {:?}", algorithm)))?;
+                let len = (point.len() - 1) / 2;
+                jwk.x = Some(URL_SAFE_NO_PAD.encode(&point[1..=len]));
+                jwk.y = Some(URL_SAFE_NO_PAD.encode(&point[len + 1..]));
+            }
+            _ => jwk.x = Some(URL_SAFE_NO_PAD.encode(bytes)),
+        }
+        Ok(())
+    }
+
+    /// Decodes the key data into the raw key and, where the encoding says,
+    /// the algorithm of the key.
+    fn decode(&self) -> Result<(Option<KeyAlgorithm>, Vec<u8>), DidError> {
+        let data = self.key_data.trim();
+        let mut algorithm = None;
+        let bytes = match self.key_data_type {
+            PublicKeyEncoding::Hex => {
+                hex::decode(data.trim_start_matches("0x")).map_err(|e| self.malformed(e))?
+            }
+            PublicKeyEncoding::Base64 => STANDARD_NO_PAD
+                .decode(data.trim_end_matches('='))
+                .map_err(|e| self.malformed(e))?,
+            PublicKeyEncoding::Base58 => bs58::decode(data)
+                .into_vec()
+                .map_err(|e| self.malformed(e))?,
+            PublicKeyEncoding::Multibase => {
+                let (_, bytes) = multibase::decode(data).map_err(|e| self.malformed(e))?;
+                match Multicodec::split(&bytes) {
+                    Some((codec, key)) if codec.fits(key) => {
+                        algorithm = Some(KeyAlgorithm::from_multicodec(codec));
+                        key.to_vec()
+                    }
+                    Some((codec, key)) => {
+                        return Err(self.malformed(format!(
+                            "{} bytes are too many or too few for {:?}",
+                            key.len(),
+                            codec
+                        )))
+                    }
+                    None => bytes,
+                }
+            }
+            PublicKeyEncoding::Pem => {
+                let (label, der) =
+                    pem_decode(data).ok_or_else(|| self.malformed("not a PEM block"))?;
+                match label.as_str() {
+                    "PUBLIC KEY" => {
+                        let info = SubjectPublicKeyInfo::from_der(&der)
+                            .ok_or_else(|| self.malformed("not a SubjectPublicKeyInfo"))?;
+                        algorithm = KeyAlgorithm::from_spki(&info);
+                        info.key
+                    }
+                    "RSA PUBLIC KEY" => {
+                        algorithm = Some(KeyAlgorithm::Rsa);
+                        der
+                    }
+                    _ => return Err(self.malformed(format!("unexpected PEM label {}", label))),
+                }
+            }
+            PublicKeyEncoding::Jwk => match &self.jwk {
+                Some(jwk) => {
+                    algorithm = KeyAlgorithm::from_jwk(jwk);
+                    jwk.key_bytes()?
+                }
+                None => return Err(self.malformed("no key data")),
+            },
+            PublicKeyEncoding::Unknown | PublicKeyEncoding::EthereumAddress => {
+                return Err(DidError::from_kind(DidErrorKind::UnsupportedEncoding {
+                    encoding: self.key_data_type.field_name().to_owned(),
+                }))
+            }
+        };
+
+        if bytes.is_empty() {
+            return Err(self.malformed("no key data"));
+        }
+        Ok((algorithm, bytes))
+    }
+
+    fn unsupported(&self, to: PublicKeyEncoding) -> DidError {
+        DidError::from_kind(DidErrorKind::UnsupportedConversion {
+            from: self.key_data_type.field_name().to_owned(),
+            to: to.field_name().to_owned(),
+        })
+    }
+
+    fn malformed<D: fmt::Display>(&self, cause: D) -> DidError {
+        DidError::from_msg(
+            DidErrorKind::MalformedKeyData {
+                encoding: self.key_data_type.field_name().to_owned(),
+            },
+            cause.to_string(),
+        )
+    }
+}
+
+/// The base58btc multibase form of a raw key behind its multicodec prefix,
+/// with elliptic curve points compressed.
+fn multikey(algorithm: KeyAlgorithm, bytes: Vec<u8>) -> String {
+    let key = algorithm.compress(&bytes).unwrap_or(bytes);
+    multibase::encode(
+        multibase::Base::Base58Btc,
+        algorithm.multicodec().encode(&key),
+    )
+}
+
+/// The EIP-55 checksummed address of an uncompressed secp256k1 point.
+fn ethereum_address(point: &[u8]) -> String {
+    let hash = keccak256(&point[1..]);
+    let address = hex::encode(&hash[12..]);
+    let checksum = keccak256(address.as_bytes());
+    let mut out = "0x".to_owned();
+    for (i, c) in address.chars().enumerate() {
+        let nibble = (checksum[i / 2] >> (4 * (1 - i % 2))) & 0x0f;
+        if nibble >= 8 {
+            out.push(c.to_ascii_uppercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    use tiny_keccak::{Hasher, Keccak};
+    let mut hasher = Keccak::v256();
+    let mut out = [0u8; 32];
+    hasher.update(data);
+    hasher.finalize(&mut out);
+    out
 }
 
 impl<'de> Deserialize<'de> for PublicKey {
@@ -103,34 +743,21 @@ impl<'de> Deserialize<'de> for PublicKey {
             Type,
             Controller,
             KeyData(PublicKeyEncoding),
-        };
+            Other(String),
+        }
 
         impl<'de> Deserialize<'de> for Field {
             fn deserialize<D>(deserializer: D) -> Result<Field, D::Error>
             where
                 D: Deserializer<'de>,
             {
-                const FIELDS: &[&str] = &[
-                    "id",
-                    "type",
-                    "controller",
-                    "publicKeyUnknown",
-                    "publicKeyPem",
-                    "publicKeyJwk",
-                    "publicKeyHex",
-                    "publicKeyBase64",
-                    "publicKeyBase58",
-                    "publicKeyMultibase",
-                    "ethereumAddress",
-                ];
-
                 struct FieldVisitor;
 
                 impl<'de> Visitor<'de> for FieldVisitor {
                     type Value = Field;
 
                     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                        formatter.write_str("`id`, `type`, `controller`, or one of the key types")
+                        formatter.write_str("a PublicKey member name")
                     }
 
                     fn visit_str<E>(self, value: &str) -> Result<Field, E>
@@ -145,7 +772,7 @@ impl<'de> Deserialize<'de> for PublicKey {
                                 if let Ok(pkdt) = PublicKeyEncoding::from_str(value) {
                                     Ok(Field::KeyData(pkdt))
                                 } else {
-                                    Err(de::Error::unknown_field(value, FIELDS))
+                                    Ok(Field::Other(value.to_owned()))
                                 }
                             }
                         }
@@ -169,14 +796,8 @@ impl<'de> Deserialize<'de> for PublicKey {
             where
                 E: de::Error,
             {
-                Ok(PublicKey {
-                    id: Subject::from_str(value).unwrap(),
-                    key_type: PublicKeyType::default(),
-                    controller: Subject::default(),
-                    key_data_type: PublicKeyEncoding::Unknown,
-                    key_data: "".to_owned(),
-                    reference: true,
-                })
+                let id = Uri::from_str(value).map_err(E::custom)?;
+                Ok(PublicKey::reference_to(&id))
             }
 
             fn visit_map<V>(self, mut map: V) -> Result<PublicKey, V::Error>
@@ -188,6 +809,8 @@ impl<'de> Deserialize<'de> for PublicKey {
                 let mut controller = None;
                 let mut key_data_type = None;
                 let mut key_data = None;
+                let mut jwk = None;
+                let mut extra = IndexMap::new();
 
                 while let Some(key) = map.next_key()? {
                     match key {
@@ -214,7 +837,17 @@ impl<'de> Deserialize<'de> for PublicKey {
                                 return Err(de::Error::duplicate_field("key data"));
                             }
                             key_data_type = Some(pkdt);
-                            key_data = Some(map.next_value()?);
+                            if pkdt == PublicKeyEncoding::Jwk {
+                                // checked by validate and when the key is used,
+                                // so documents with odd keys still parse
+                                jwk = Some(Box::new(map.next_value()?));
+                                key_data = Some(String::new());
+                            } else {
+                                key_data = Some(map.next_value()?);
+                            }
+                        }
+                        Field::Other(name) => {
+                            extra.insert(name, map.next_value()?);
                         }
                     }
                 }
@@ -233,7 +866,9 @@ impl<'de> Deserialize<'de> for PublicKey {
                     controller,
                     key_data_type,
                     key_data,
+                    jwk,
                     reference: false,
+                    extra,
                 })
             }
         }
@@ -250,29 +885,16 @@ impl Serialize for PublicKey {
         if self.reference {
             self.id.serialize(serializer)
         } else {
-            let mut pk = serializer.serialize_struct("", 4)?;
-            pk.serialize_field("id", &self.id)?;
-            pk.serialize_field("type", &self.key_type)?;
-            pk.serialize_field("controller", &self.controller)?;
-            match self.key_data_type {
-                PublicKeyEncoding::Unknown => {
-                    pk.serialize_field("publicKeyUnknown", &self.key_data)?
-                }
-                PublicKeyEncoding::Pem => pk.serialize_field("publicKeyPem", &self.key_data)?,
-                PublicKeyEncoding::Jwk => pk.serialize_field("publicKeyJwk", &self.key_data)?,
-                PublicKeyEncoding::Hex => pk.serialize_field("publicKeyHex", &self.key_data)?,
-                PublicKeyEncoding::Base64 => {
-                    pk.serialize_field("publicKeyBase64", &self.key_data)?
-                }
-                PublicKeyEncoding::Base58 => {
-                    pk.serialize_field("publicKeyBase58", &self.key_data)?
-                }
-                PublicKeyEncoding::Multibase => {
-                    pk.serialize_field("publicKeyMultibase", &self.key_data)?
-                }
-                PublicKeyEncoding::EthereumAddress => {
-                    pk.serialize_field("ethereumAddress", &self.key_data)?
-                }
+            let mut pk = serializer.serialize_map(Some(4 + self.extra.len()))?;
+            pk.serialize_entry("id", &self.id)?;
+            pk.serialize_entry("type", &self.key_type)?;
+            pk.serialize_entry("controller", &self.controller)?;
+            match &self.jwk {
+                Some(jwk) => pk.serialize_entry("publicKeyJwk", jwk)?,
+                None => pk.serialize_entry(self.key_data_type.field_name(), &self.key_data)?,
+            }
+            for (k, v) in &self.extra {
+                pk.serialize_entry(k, v)?;
             }
             pk.end()
         }
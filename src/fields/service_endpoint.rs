@@ -1,41 +1,176 @@
-use crate::fields::{string_or_list, Context, Subject};
+use crate::error::DidError;
+use crate::fields::{Context, Subject};
+use crate::uri::Uri;
 use indexmap::IndexMap;
+use serde::de::{Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
 use serde_derive::{Deserialize, Serialize};
 use serde_json::Value;
+use std::str::FromStr;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ServiceEndpoint {
     #[serde(
         rename = "@context",
         skip_serializing_if = "Context::is_empty",
-        deserialize_with = "string_or_list",
         default
     )]
     context: Context,
-    #[serde(skip_serializing_if = "Subject::is_empty", default)]
-    id: Subject,
+    #[serde(skip_serializing_if = "ServiceId::is_empty", default)]
+    id: ServiceId,
     #[serde(rename = "type")]
     service_type: String,
     #[serde(rename = "serviceEndpoint")]
-    endpoint: String,
+    endpoint: Endpoint,
     #[serde(flatten)]
     pub extra: IndexMap<String, Value>,
 }
 
 impl ServiceEndpoint {
+    /// A service with no id when `id` is empty. Fails if `id` is neither a
+    /// DID URL nor a relative fragment.
+    pub fn new(id: &str, service_type: &str, endpoint: Endpoint) -> Result<Self, DidError> {
+        Ok(ServiceEndpoint::with_subject(
+            Uri::from_str(id)?.into(),
+            service_type,
+            endpoint,
+        ))
+    }
+
+    pub(crate) fn with_subject(id: Subject, service_type: &str, endpoint: Endpoint) -> Self {
+        ServiceEndpoint {
+            context: Context::default(),
+            id: ServiceId {
+                raw: id.as_uri().to_string(),
+                subject: id,
+            },
+            service_type: service_type.to_owned(),
+            endpoint,
+            extra: IndexMap::default(),
+        }
+    }
+
     pub fn context(&self) -> &Context {
         &self.context
     }
 
+    /// The id as written, which need not be a URI.
+    pub fn id(&self) -> &str {
+        &self.id.raw
+    }
+
+    /// The parsed id, empty if the service has none or one that is not a
+    /// URI.
     pub fn subject(&self) -> &Subject {
-        &self.id
+        &self.id.subject
     }
 
     pub fn kind(&self) -> &String {
         &self.service_type
     }
 
-    pub fn endpoint(&self) -> &String {
+    pub fn endpoint(&self) -> &Endpoint {
         &self.endpoint
     }
 }
+
+/// A service id, kept as written so that one that is not a URI is
+/// reported by `Document::validate` rather than failing the whole document.
+#[derive(Debug, Default, Clone)]
+struct ServiceId {
+    raw: String,
+    subject: Subject,
+}
+
+impl ServiceId {
+    fn is_empty(&self) -> bool {
+        self.raw.is_empty()
+    }
+}
+
+impl Serialize for ServiceId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.raw)
+    }
+}
+
+impl<'de> Deserialize<'de> for ServiceId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = String::deserialize(deserializer)?;
+        let subject = Uri::from_str(&raw).map(Subject::from).unwrap_or_default();
+        Ok(ServiceId { raw, subject })
+    }
+}
+
+/// The value of `serviceEndpoint`: a URI, a map such as a DIDComm v2
+/// endpoint object, or an ordered set of either.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum Endpoint {
+    Uri(String),
+    Map(IndexMap<String, Value>),
+    Set(Vec<Endpoint>),
+}
+
+impl Endpoint {
+    pub fn as_uri(&self) -> Option<&str> {
+        match self {
+            Endpoint::Uri(uri) => Some(uri),
+            _ => None,
+        }
+    }
+
+    pub fn as_map(&self) -> Option<&IndexMap<String, Value>> {
+        match self {
+            Endpoint::Map(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    pub fn as_set(&self) -> Option<&[Endpoint]> {
+        match self {
+            Endpoint::Set(set) => Some(set),
+            _ => None,
+        }
+    }
+
+    /// Every URI the endpoint holds, in order: the URI itself, the `uri`
+    /// member of a map, or those of each member of a set.
+    pub fn uris(&self) -> Vec<&str> {
+        match self {
+            Endpoint::Uri(uri) => vec![uri.as_str()],
+            Endpoint::Map(map) => map.get("uri").and_then(Value::as_str).into_iter().collect(),
+            Endpoint::Set(set) => set.iter().flat_map(Endpoint::uris).collect(),
+        }
+    }
+}
+
+impl Default for Endpoint {
+    fn default() -> Self {
+        Endpoint::Uri(String::new())
+    }
+}
+
+impl From<&str> for Endpoint {
+    fn from(uri: &str) -> Self {
+        Endpoint::Uri(uri.to_owned())
+    }
+}
+
+impl PartialEq<&str> for Endpoint {
+    fn eq(&self, rhs: &&str) -> bool {
+        self.as_uri() == Some(*rhs)
+    }
+}
+
+impl PartialEq<str> for Endpoint {
+    fn eq(&self, rhs: &str) -> bool {
+        self.as_uri() == Some(rhs)
+    }
+}
@@ -0,0 +1,255 @@
+//! Just enough DER to read and write the `SubjectPublicKeyInfo` structures found in
+//! `publicKeyPem` values.
+
+use base64::Engine;
+use std::mem;
+
+const SEQUENCE: u8 = 0x30;
+const BIT_STRING: u8 = 0x03;
+const OID: u8 = 0x06;
+const INTEGER: u8 = 0x02;
+const NULL: u8 = 0x05;
+
+pub const ID_ED25519: &[u8] = &[0x2b, 0x65, 0x70];
+pub const ID_X25519: &[u8] = &[0x2b, 0x65, 0x6e];
+pub const ID_EC_PUBLIC_KEY: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+pub const ID_RSA_ENCRYPTION: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+pub const SECP256K1: &[u8] = &[0x2b, 0x81, 0x04, 0x00, 0x0a];
+pub const SECP256R1: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+pub const SECP384R1: &[u8] = &[0x2b, 0x81, 0x04, 0x00, 0x22];
+
+#[derive(Debug, PartialEq)]
+pub struct SubjectPublicKeyInfo {
+    /// The content octets of the algorithm OID.
+    pub algorithm: Vec<u8>,
+    /// The content octets of the named curve OID, if the parameters are one.
+    pub curve: Option<Vec<u8>>,
+    /// The contents of the `subjectPublicKey` bit string.
+    pub key: Vec<u8>,
+}
+
+impl SubjectPublicKeyInfo {
+    pub fn from_der(der: &[u8]) -> Option<Self> {
+        let (tag, spki, rest) = read_tlv(der)?;
+        if tag != SEQUENCE || !rest.is_empty() {
+            return None;
+        }
+
+        let (tag, algorithm_identifier, spki) = read_tlv(spki)?;
+        if tag != SEQUENCE {
+            return None;
+        }
+        let (tag, algorithm, parameters) = read_tlv(algorithm_identifier)?;
+        if tag != OID {
+            return None;
+        }
+        let curve = match read_tlv(parameters) {
+            Some((OID, curve, _)) => Some(curve.to_vec()),
+            _ => None,
+        };
+
+        let (tag, bits, rest) = read_tlv(spki)?;
+        if tag != BIT_STRING || !rest.is_empty() || bits.first() != Some(&0) {
+            return None;
+        }
+
+        Some(SubjectPublicKeyInfo {
+            algorithm: algorithm.to_vec(),
+            curve,
+            key: bits[1..].to_vec(),
+        })
+    }
+
+    pub fn to_der(&self) -> Vec<u8> {
+        let mut algorithm_identifier = write_tlv(OID, &self.algorithm);
+        match &self.curve {
+            Some(curve) => algorithm_identifier.extend(write_tlv(OID, curve)),
+            // RSA keys carry explicit NULL parameters
+            None if self.algorithm == ID_RSA_ENCRYPTION => {
+                algorithm_identifier.extend(write_tlv(NULL, &[]))
+            }
+            None => {}
+        }
+
+        let mut bits = vec![0];
+        bits.extend_from_slice(&self.key);
+
+        let mut spki = write_tlv(SEQUENCE, &algorithm_identifier);
+        spki.extend(write_tlv(BIT_STRING, &bits));
+        write_tlv(SEQUENCE, &spki)
+    }
+}
+
+/// Encodes a PKCS#1 `RSAPublicKey` from its big-endian modulus and exponent.
+pub fn rsa_public_key(n: &[u8], e: &[u8]) -> Vec<u8> {
+    let mut integers = write_tlv(INTEGER, &unsigned(n));
+    integers.extend(write_tlv(INTEGER, &unsigned(e)));
+    write_tlv(SEQUENCE, &integers)
+}
+
+/// Splits a PKCS#1 `RSAPublicKey` into its big-endian modulus and exponent.
+pub fn rsa_components(der: &[u8]) -> Option<(Vec<u8>, Vec<u8>)> {
+    let (tag, integers, rest) = read_tlv(der)?;
+    if tag != SEQUENCE || !rest.is_empty() {
+        return None;
+    }
+    let (tag, n, integers) = read_tlv(integers)?;
+    if tag != INTEGER {
+        return None;
+    }
+    let (tag, e, rest) = read_tlv(integers)?;
+    if tag != INTEGER || !rest.is_empty() {
+        return None;
+    }
+    Some((strip_zeros(n).to_vec(), strip_zeros(e).to_vec()))
+}
+
+/// Wraps `der` in a PEM block with 64 character lines.
+pub fn pem_encode(label: &str, der: &[u8]) -> String {
+    let b64 = base64::engine::general_purpose::STANDARD.encode(der);
+    let mut pem = format!("-----BEGIN {}-----\n", label);
+    for line in b64.as_bytes().chunks(64) {
+        pem.push_str(std::str::from_utf8(line).unwrap());
+        pem.push('\n');
+    }
+    pem.push_str(&format!("-----END {}-----\n", label));
+    pem
+}
+
+/// Decodes the first PEM block in `pem`, returning its label and contents.
+pub fn pem_decode(pem: &str) -> Option<(String, Vec<u8>)> {
+    let begin = pem.find("-----BEGIN ")?;
+    let pem = &pem[begin + "-----BEGIN ".len()..];
+    let label_end = pem.find("-----")?;
+    let label = &pem[..label_end];
+    let body = &pem[label_end + "-----".len()..];
+    let end = body.find(&format!("-----END {}-----", label))?;
+
+    let b64 = body[..end]
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>();
+    let der = base64::engine::general_purpose::STANDARD
+        .decode(b64.as_bytes())
+        .ok()?;
+    Some((label.to_owned(), der))
+}
+
+/// Splits one tag-length-value off `input`, returning the tag, the value and
+/// whatever follows it.
+fn read_tlv(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let tag = *input.first()?;
+    let first = *input.get(1)? as usize;
+    let (len, header) = if first < 0x80 {
+        (first, 2)
+    } else {
+        let n = first & 0x7f;
+        // more length octets than a usize holds would overflow the fold
+        if n == 0 || n > 4 || n > mem::size_of::<usize>() || input.len() < 2 + n {
+            return None;
+        }
+        let len = input[2..2 + n]
+            .iter()
+            .fold(0usize, |acc, b| (acc << 8) | *b as usize);
+        (len, 2 + n)
+    };
+    let end = header.checked_add(len)?;
+    if input.len() < end {
+        return None;
+    }
+    Some((tag, &input[header..end], &input[end..]))
+}
+
+fn write_tlv(tag: u8, value: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    if value.len() < 0x80 {
+        out.push(value.len() as u8);
+    } else {
+        let len = value.len().to_be_bytes();
+        let len = strip_zeros(&len);
+        out.push(0x80 | len.len() as u8);
+        out.extend_from_slice(len);
+    }
+    out.extend_from_slice(value);
+    out
+}
+
+/// The content octets of a non-negative DER INTEGER.
+fn unsigned(bytes: &[u8]) -> Vec<u8> {
+    let bytes = strip_zeros(bytes);
+    let mut out = Vec::with_capacity(bytes.len() + 1);
+    if !matches!(bytes.first(), Some(b) if b & 0x80 == 0) {
+        out.push(0);
+    }
+    out.extend_from_slice(bytes);
+    out
+}
+
+fn strip_zeros(bytes: &[u8]) -> &[u8] {
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+#[cfg(test)]
+mod spki_tests {
+    use super::*;
+
+    #[test]
+    fn test_read_tlv() {
+        let (tag, value, rest) = read_tlv(&[0x04, 0x02, 0xaa, 0xbb, 0xcc]).unwrap();
+        assert_eq!(tag, 0x04);
+        assert_eq!(value, &[0xaa, 0xbb]);
+        assert_eq!(rest, &[0xcc]);
+
+        let mut long = vec![0x04, 0x81, 0x80];
+        long.extend_from_slice(&[0u8; 0x80]);
+        let (_, value, rest) = read_tlv(&long).unwrap();
+        assert_eq!(value.len(), 0x80);
+        assert!(rest.is_empty());
+
+        assert!(read_tlv(&[0x04, 0x03, 0xaa]).is_none());
+        assert!(read_tlv(&[0x04]).is_none());
+        assert!(read_tlv(&[0x04, 0x85, 0xff, 0xff, 0xff, 0xff, 0xff]).is_none());
+        assert!(read_tlv(&[0x04, 0x84, 0xff, 0xff, 0xff, 0xff, 0xaa]).is_none());
+    }
+
+    #[test]
+    fn test_rsa_public_key() {
+        let der = rsa_public_key(&[0x00, 0xc1, 0x02], &[0x01, 0x00, 0x01]);
+        assert_eq!(
+            der,
+            vec![0x30, 0x0a, 0x02, 0x03, 0x00, 0xc1, 0x02, 0x02, 0x03, 0x01, 0x00, 0x01]
+        );
+        assert_eq!(
+            rsa_components(&der).unwrap(),
+            (vec![0xc1, 0x02], vec![0x01, 0x00, 0x01])
+        );
+
+        let der = rsa_public_key(&[0xff; 256], &[0x03]);
+        assert_eq!(
+            &der[..9],
+            &[0x30, 0x82, 0x01, 0x08, 0x02, 0x82, 0x01, 0x01, 0x00]
+        );
+    }
+
+    #[test]
+    fn test_pem_decode() {
+        let pem = "-----BEGIN PUBLIC KEY-----\nAAEC\nAw==\n-----END PUBLIC KEY-----\n";
+        let (label, der) = pem_decode(pem).unwrap();
+        assert_eq!(label, "PUBLIC KEY");
+        assert_eq!(der, vec![0, 1, 2, 3]);
+
+        assert!(pem_decode("-----BEGIN PUBLIC KEY...END PUBLIC KEY-----").is_none());
+    }
+
+    #[test]
+    fn test_spki_round_trip() {
+        let pem = "-----BEGIN PUBLIC KEY-----\nMCowBQYDK2VwAyEAA6EHv/POEL4dcN0Y50vAmWfk1jCbpQ1fHdyGZBJVMbg=\n-----END PUBLIC KEY-----\n";
+        let (_, der) = pem_decode(pem).unwrap();
+        let info = SubjectPublicKeyInfo::from_der(&der).unwrap();
+        assert_eq!(info.algorithm, ID_ED25519);
+        assert_eq!(info.curve, None);
+        assert_eq!(info.key.len(), 32);
+        assert_eq!(pem_encode("PUBLIC KEY", &info.to_der()), pem);
+    }
+}
@@ -5,7 +5,7 @@ use std::str::FromStr;
 use void::Void;
 use crate::uri::Uri;
 
-#[derive(Serialize, Deserialize, Default, Debug)]
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
 #[serde(transparent)]
 pub struct Subject(Uri);
 
@@ -18,6 +18,10 @@ impl Subject {
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
+
+    pub fn as_uri(&self) -> &Uri {
+        &self.0
+    }
 }
 
 impl Eq for Subject {}
@@ -36,7 +40,7 @@ impl PartialEq<str> for Subject {
 
 impl PartialEq for Subject {
     fn eq(&self, rhs: &Subject) -> bool {
-        self == rhs
+        self.0 == rhs.0
     }
 }
 
@@ -50,6 +54,12 @@ impl Hash for Subject {
     }
 }
 
+impl From<Uri> for Subject {
+    fn from(uri: Uri) -> Self {
+        Subject(uri)
+    }
+}
+
 impl FromStr for Subject {
     type Err = Void;
 
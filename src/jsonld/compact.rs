@@ -0,0 +1,305 @@
+use crate::error::DidError;
+use crate::jsonld::context::{ActiveContext, Term};
+use crate::jsonld::expand::as_array;
+use crate::jsonld::ContextLoader;
+use serde_json::{Map, Value};
+
+/// The compaction algorithm, JSON-LD 1.1 API section 6.1.
+pub(super) fn compact<L: ContextLoader + ?Sized>(
+    active: &ActiveContext,
+    property: Option<&str>,
+    element: &Value,
+    loader: &L,
+) -> Result<Value, DidError> {
+    match element {
+        Value::Array(items) => {
+            let mut result = Vec::new();
+            for item in items {
+                match compact(active, property, item, loader)? {
+                    Value::Null => {}
+                    compacted => result.push(compacted),
+                }
+            }
+            let keep_array = property
+                .and_then(|p| active.term(p))
+                .map(|t| t.has_container("@set") || t.has_container("@list"))
+                .unwrap_or(false);
+            if result.len() == 1 && !keep_array {
+                Ok(result.remove(0))
+            } else {
+                Ok(Value::Array(result))
+            }
+        }
+        Value::Object(map) => compact_object(active, property, map, loader),
+        scalar => Ok(scalar.clone()),
+    }
+}
+
+fn compact_object<L: ContextLoader + ?Sized>(
+    active: &ActiveContext,
+    property: Option<&str>,
+    map: &Map<String, Value>,
+    loader: &L,
+) -> Result<Value, DidError> {
+    let mut active = active.clone();
+    let is_reference = map.len() == 1 && map.contains_key("@id");
+
+    if let Some(previous) = &active.previous {
+        if !map.contains_key("@value") && !is_reference {
+            active = (**previous).clone();
+        }
+    }
+    if let Some(scoped) = property
+        .and_then(|p| active.term(p))
+        .and_then(|t| t.context.clone())
+    {
+        active = active.process(&scoped, loader, true)?;
+    }
+    if map.contains_key("@value") || is_reference {
+        if let Some(value) = compact_value(&active, property, map) {
+            return Ok(value);
+        }
+    }
+
+    // the types are compacted with the context of the node, before any of
+    // their scoped contexts is applied
+    let type_scoped = active.clone();
+    if let Some(Value::Array(types)) = map.get("@type") {
+        let mut types: Vec<String> = types
+            .iter()
+            .filter_map(Value::as_str)
+            .map(|t| type_scoped.compact_iri(t, true))
+            .collect();
+        types.sort();
+        for t in types {
+            if let Some(scoped) = type_scoped.term(&t).and_then(|t| t.context.clone()) {
+                active = active.process(&scoped, loader, false)?;
+            }
+        }
+    }
+
+    let mut result = Map::new();
+    let mut keys: Vec<&String> = map.keys().collect();
+    keys.sort();
+    for key in keys {
+        let value = &map[key.as_str()];
+        match key.as_str() {
+            "@id" => {
+                let id = value.as_str().unwrap_or_default();
+                result.insert(
+                    active.compact_iri("@id", true),
+                    Value::from(active.compact_iri(id, false)),
+                );
+            }
+            "@type" => {
+                let mut types: Vec<Value> = as_array(value.clone())
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(|t| Value::from(type_scoped.compact_iri(t, true)))
+                    .collect();
+                let types = if types.len() == 1 {
+                    types.remove(0)
+                } else {
+                    Value::Array(types)
+                };
+                result.insert(active.compact_iri("@type", true), types);
+            }
+            "@graph" | "@included" => {
+                let compacted = compact(&active, Some(key), value, loader)?;
+                result.insert(active.compact_iri(key, true), compacted);
+            }
+            "@index" | "@language" | "@value" | "@direction" => {
+                result.insert(active.compact_iri(key, true), value.clone());
+            }
+            _ if key.starts_with('@') => {}
+            _ => compact_property(&active, &mut result, key, value, loader)?,
+        }
+    }
+    Ok(Value::Object(result))
+}
+
+/// Adds the values of the expanded property `iri` to `result`, each under
+/// the term that fits it best.
+fn compact_property<L: ContextLoader + ?Sized>(
+    active: &ActiveContext,
+    result: &mut Map<String, Value>,
+    iri: &str,
+    value: &Value,
+    loader: &L,
+) -> Result<(), DidError> {
+    let items = as_array(value.clone());
+    if items.is_empty() {
+        let term = active.select_term(iri, &Value::Null);
+        add_value(result, &term, Value::Array(Vec::new()), true);
+        return Ok(());
+    }
+
+    for item in &items {
+        let term = active.select_term(iri, item);
+        let container = |c: &str| {
+            active
+                .term(&term)
+                .map(|t| t.has_container(c))
+                .unwrap_or(false)
+        };
+        if let Some(list) = item.get("@list") {
+            let list = Value::Array(as_array(compact(active, Some(&term), list, loader)?));
+            if container("@list") {
+                result.insert(term, list);
+            } else {
+                let mut object = Map::new();
+                object.insert(active.compact_iri("@list", true), list);
+                add_value(result, &term, Value::Object(object), false);
+            }
+            continue;
+        }
+        let compacted = match item.get("@graph") {
+            Some(graph) if container("@graph") => compact(active, Some(&term), graph, loader)?,
+            _ => compact(active, Some(&term), item, loader)?,
+        };
+        add_value(result, &term, compacted, container("@set"));
+    }
+    Ok(())
+}
+
+/// Value compaction, JSON-LD 1.1 API section 6.3. Returns `None` when the
+/// value has to stay an object.
+fn compact_value(
+    active: &ActiveContext,
+    property: Option<&str>,
+    map: &Map<String, Value>,
+) -> Option<Value> {
+    let type_mapping = property
+        .and_then(|p| active.term(p))
+        .and_then(|t| t.type_mapping.as_deref());
+
+    if let Some(id) = map.get("@id").and_then(Value::as_str) {
+        return match type_mapping {
+            Some("@id") => Some(Value::from(active.compact_iri(id, false))),
+            Some("@vocab") => Some(Value::from(active.compact_iri(id, true))),
+            _ => None,
+        };
+    }
+    if map.contains_key("@index") || map.contains_key("@language") {
+        return None;
+    }
+    let value = map.get("@value")?;
+    match (map.get("@type").and_then(Value::as_str), type_mapping) {
+        (Some(t), Some(mapping)) if t == mapping => Some(value.clone()),
+        (None, None) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+fn add_value(result: &mut Map<String, Value>, key: &str, value: Value, set: bool) {
+    match result.get_mut(key) {
+        None if set && !value.is_array() => {
+            result.insert(key.to_owned(), Value::Array(vec![value]));
+        }
+        None => {
+            result.insert(key.to_owned(), value);
+        }
+        Some(Value::Array(values)) => values.extend(as_array(value)),
+        Some(existing) => {
+            let mut values = vec![existing.take()];
+            values.extend(as_array(value));
+            *existing = Value::Array(values);
+        }
+    }
+}
+
+impl ActiveContext {
+    /// IRI compaction, JSON-LD 1.1 API section 6.2, for IRIs that are not
+    /// the values of a property: keywords, types and `@id`s.
+    pub fn compact_iri(&self, iri: &str, vocab: bool) -> String {
+        if vocab {
+            let term = self.best_term(|_, term| {
+                (term.id == iri && term.type_mapping.is_none() && term.container.is_empty())
+                    .then_some(0)
+            });
+            if let Some(term) = term {
+                return term;
+            }
+        }
+        self.compact_without_term(iri, vocab)
+    }
+
+    /// The term to use for the property `iri` with the expanded `value`,
+    /// one whose container and type mapping keep the value as it is.
+    pub fn select_term(&self, iri: &str, value: &Value) -> String {
+        let (containers, types): (&[&str], &[Option<&str>]) = if value.get("@list").is_some() {
+            (&["@list", ""], &[None])
+        } else if value.get("@graph").is_some() {
+            (&["@graph", ""], &[None])
+        } else if value.get("@value").is_some() {
+            match value.get("@type").and_then(Value::as_str) {
+                Some(t) => (&[""], &[Some(t), None][..]),
+                None => (&[""], &[None]),
+            }
+        } else {
+            (&[""], &[Some("@id"), Some("@vocab"), None])
+        };
+
+        let term = self.best_term(|_, term| {
+            if term.id != iri {
+                return None;
+            }
+            let container = match term.container.as_slice() {
+                [] => "",
+                [c] if c == "@set" => "",
+                [c] => c.as_str(),
+                [c, set] | [set, c] if set == "@set" => c.as_str(),
+                _ => return None,
+            };
+            let container = containers.iter().position(|c| *c == container)?;
+            let type_mapping = types
+                .iter()
+                .position(|t| *t == term.type_mapping.as_deref())?;
+            Some(container * types.len() + type_mapping)
+        });
+        term.unwrap_or_else(|| self.compact_without_term(iri, true))
+    }
+
+    /// The term `rank` accepts with the lowest rank, then the shortest and
+    /// lexicographically least.
+    fn best_term<F>(&self, rank: F) -> Option<String>
+    where
+        F: Fn(&str, &Term) -> Option<usize>,
+    {
+        self.terms
+            .iter()
+            .filter_map(|(name, term)| {
+                let term = term.as_ref()?;
+                rank(name, term).map(|rank| (rank, name.len(), name))
+            })
+            .min()
+            .map(|(_, _, name)| name.clone())
+    }
+
+    /// Compacts `iri` relative to the vocabulary mapping or into a compact
+    /// IRI with a prefix.
+    fn compact_without_term(&self, iri: &str, vocab: bool) -> String {
+        if vocab {
+            if let Some(suffix) = self
+                .vocab
+                .as_ref()
+                .and_then(|v| iri.strip_prefix(v.as_str()))
+            {
+                if !suffix.is_empty() && !suffix.contains(':') && !self.terms.contains_key(suffix) {
+                    return suffix.to_owned();
+                }
+            }
+        }
+        let compact = self
+            .terms
+            .iter()
+            .filter_map(|(name, term)| {
+                let term = term.as_ref().filter(|t| t.prefix && !name.contains(':'))?;
+                let suffix = iri.strip_prefix(term.id.as_str())?;
+                let compact = format!("{}:{}", name, suffix);
+                (!suffix.is_empty() && !self.terms.contains_key(&compact)).then_some(compact)
+            })
+            .min_by(|a, b| a.len().cmp(&b.len()).then(a.cmp(b)));
+        compact.unwrap_or_else(|| iri.to_owned())
+    }
+}
@@ -0,0 +1,354 @@
+use crate::error::DidError;
+use crate::jsonld::{invalid, ContextLoader};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use url::Url;
+
+/// How deeply remote contexts may import each other.
+const MAX_REMOTE_CONTEXTS: usize = 32;
+
+/// The result of processing `@context` entries, see JSON-LD 1.1 API
+/// section 4.1.
+#[derive(Debug, Default, Clone)]
+pub(super) struct ActiveContext {
+    pub base: Option<String>,
+    pub vocab: Option<String>,
+    /// `None` for terms explicitly mapped to null.
+    pub terms: HashMap<String, Option<Term>>,
+    /// The context to go back to when leaving a node, set by type-scoped
+    /// contexts, which do not propagate.
+    pub previous: Option<Box<ActiveContext>>,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct Term {
+    /// An IRI, blank node identifier or keyword.
+    pub id: String,
+    /// `@id`, `@vocab`, `@json`, `@none` or a datatype IRI.
+    pub type_mapping: Option<String>,
+    pub container: Vec<String>,
+    /// A scoped context, processed when the term is used.
+    pub context: Option<Value>,
+    pub prefix: bool,
+}
+
+impl Term {
+    pub fn has_container(&self, container: &str) -> bool {
+        self.container.iter().any(|c| c == container)
+    }
+}
+
+impl ActiveContext {
+    pub fn term(&self, term: &str) -> Option<&Term> {
+        self.terms.get(term).and_then(Option::as_ref)
+    }
+
+    /// Processes `local`, the value of an `@context` member.
+    /// `propagate` is false for type-scoped contexts.
+    pub fn process<L: ContextLoader + ?Sized>(
+        &self,
+        local: &Value,
+        loader: &L,
+        propagate: bool,
+    ) -> Result<ActiveContext, DidError> {
+        let mut result = self.process_remote(local, loader, &mut Vec::new())?;
+        let propagate = match local {
+            Value::Object(map) => map
+                .get("@propagate")
+                .and_then(Value::as_bool)
+                .unwrap_or(propagate),
+            _ => propagate,
+        };
+        if !propagate && result.previous.is_none() {
+            result.previous = Some(Box::new(self.clone()));
+        }
+        Ok(result)
+    }
+
+    fn process_remote<L: ContextLoader + ?Sized>(
+        &self,
+        local: &Value,
+        loader: &L,
+        remote: &mut Vec<String>,
+    ) -> Result<ActiveContext, DidError> {
+        let entries = match local {
+            Value::Array(entries) => entries.clone(),
+            entry => vec![entry.clone()],
+        };
+
+        let mut result = self.clone();
+        for entry in entries {
+            match entry {
+                Value::Null => {
+                    result = ActiveContext {
+                        base: self.base.clone(),
+                        ..ActiveContext::default()
+                    }
+                }
+                Value::String(url) => {
+                    let url = match &result.base {
+                        Some(base) => Url::parse(base)
+                            .and_then(|base| base.join(&url))
+                            .map(String::from)
+                            .unwrap_or(url),
+                        None => url,
+                    };
+                    if remote.contains(&url) || remote.len() >= MAX_REMOTE_CONTEXTS {
+                        return Err(invalid(format!("recursive context inclusion of {}", url)));
+                    }
+                    let document = loader.load(&url)?;
+                    let context = document
+                        .get("@context")
+                        .ok_or_else(|| invalid(format!("{} has no @context", url)))?;
+                    remote.push(url);
+                    result = result.process_remote(context, loader, remote)?;
+                    remote.pop();
+                }
+                Value::Object(definitions) => {
+                    result.define_all(&definitions, remote.is_empty())?;
+                }
+                other => return Err(invalid(format!("invalid local context {}", other))),
+            }
+        }
+        Ok(result)
+    }
+
+    fn define_all(&mut self, local: &Map<String, Value>, is_local: bool) -> Result<(), DidError> {
+        if let Some(version) = local.get("@version") {
+            if version.as_f64() != Some(1.1) {
+                return Err(invalid(format!("invalid @version {}", version)));
+            }
+        }
+        if local.contains_key("@import") {
+            return Err(invalid("@import is not supported"));
+        }
+        if let Some(base) = local.get("@base") {
+            // remote contexts cannot change the base IRI
+            if is_local {
+                self.base = match base {
+                    Value::Null => None,
+                    Value::String(base) => Some(self.resolve(base)),
+                    other => return Err(invalid(format!("invalid @base {}", other))),
+                };
+            }
+        }
+        if let Some(vocab) = local.get("@vocab") {
+            self.vocab = match vocab {
+                Value::Null => None,
+                Value::String(vocab) => Some(
+                    self.expand_iri(vocab, true, true)
+                        .ok_or_else(|| invalid(format!("invalid @vocab {}", vocab)))?,
+                ),
+                other => return Err(invalid(format!("invalid @vocab {}", other))),
+            };
+        }
+
+        let mut defined = HashMap::new();
+        for term in local.keys() {
+            self.define(local, term, &mut defined)?;
+        }
+        Ok(())
+    }
+
+    /// Creates the definition of `term` from `local`, after those of the
+    /// terms and prefixes its IRIs depend on.
+    fn define(
+        &mut self,
+        local: &Map<String, Value>,
+        term: &str,
+        defined: &mut HashMap<String, bool>,
+    ) -> Result<(), DidError> {
+        match defined.get(term) {
+            Some(true) => return Ok(()),
+            Some(false) => return Err(invalid(format!("cyclic IRI mapping for {}", term))),
+            None => {}
+        }
+        if term.starts_with('@') || term.is_empty() {
+            // keywords are handled by define_all, other @ forms are ignored
+            return Ok(());
+        }
+        defined.insert(term.to_owned(), false);
+
+        let value = &local[term];
+        let definition = match value {
+            Value::Null => None,
+            Value::String(id) => {
+                let id = self.define_iri(local, id, defined)?;
+                let prefix = !term.contains(':')
+                    && id
+                        .chars()
+                        .last()
+                        .map(|c| ":/?#[]@".contains(c))
+                        .unwrap_or(false);
+                Some(Term {
+                    id,
+                    type_mapping: None,
+                    container: Vec::new(),
+                    context: None,
+                    prefix,
+                })
+            }
+            Value::Object(map) => self.define_expanded(local, term, map, defined)?,
+            other => {
+                return Err(invalid(format!(
+                    "invalid definition of {}: {}",
+                    term, other
+                )))
+            }
+        };
+
+        self.terms.insert(term.to_owned(), definition);
+        defined.insert(term.to_owned(), true);
+        Ok(())
+    }
+
+    fn define_expanded(
+        &mut self,
+        local: &Map<String, Value>,
+        term: &str,
+        map: &Map<String, Value>,
+        defined: &mut HashMap<String, bool>,
+    ) -> Result<Option<Term>, DidError> {
+        if map.contains_key("@reverse") {
+            return Err(invalid("reverse properties are not supported"));
+        }
+
+        let id = match map.get("@id") {
+            Some(Value::Null) => return Ok(None),
+            Some(Value::String(id)) => self.define_iri(local, id, defined)?,
+            Some(other) => return Err(invalid(format!("invalid @id {}", other))),
+            None if term.contains(':') => self.define_iri(local, term, defined)?,
+            None => match &self.vocab {
+                Some(vocab) => format!("{}{}", vocab, term),
+                None => return Err(invalid(format!("{} has no IRI mapping", term))),
+            },
+        };
+
+        let type_mapping = match map.get("@type") {
+            None => None,
+            Some(Value::String(t)) if ["@id", "@vocab", "@json", "@none"].contains(&t.as_str()) => {
+                Some(t.clone())
+            }
+            Some(Value::String(t)) => Some(self.define_iri(local, t, defined)?),
+            Some(other) => return Err(invalid(format!("invalid @type {}", other))),
+        };
+
+        let container = match map.get("@container") {
+            None | Some(Value::Null) => Vec::new(),
+            Some(Value::String(c)) => vec![c.clone()],
+            Some(Value::Array(cs)) => cs
+                .iter()
+                .map(|c| {
+                    c.as_str()
+                        .map(str::to_owned)
+                        .ok_or_else(|| invalid(format!("invalid @container {}", c)))
+                })
+                .collect::<Result<_, _>>()?,
+            Some(other) => return Err(invalid(format!("invalid @container {}", other))),
+        };
+
+        Ok(Some(Term {
+            id,
+            type_mapping,
+            container,
+            context: map.get("@context").cloned(),
+            prefix: map.get("@prefix").and_then(Value::as_bool).unwrap_or(false),
+        }))
+    }
+
+    /// Expands an IRI used in a term definition, defining the terms of
+    /// `local` it refers to first.
+    fn define_iri(
+        &mut self,
+        local: &Map<String, Value>,
+        value: &str,
+        defined: &mut HashMap<String, bool>,
+    ) -> Result<String, DidError> {
+        if local.contains_key(value) {
+            self.define(local, value, defined)?;
+        }
+        if let Some((prefix, _)) = value.split_once(':') {
+            if local.contains_key(prefix) {
+                self.define(local, prefix, defined)?;
+            }
+        }
+        self.expand_iri(value, false, true)
+            .ok_or_else(|| invalid(format!("invalid IRI mapping {}", value)))
+    }
+
+    /// IRI expansion, JSON-LD 1.1 API section 5.2. Returns `None` for
+    /// values that are explicitly mapped to null or look like keywords.
+    pub fn expand_iri(&self, value: &str, document_relative: bool, vocab: bool) -> Option<String> {
+        if value.starts_with('@') {
+            return if is_keyword(value) {
+                Some(value.to_owned())
+            } else {
+                None
+            };
+        }
+        if vocab {
+            if let Some(definition) = self.terms.get(value) {
+                return definition.as_ref().map(|term| term.id.clone());
+            }
+        }
+        if let Some((prefix, suffix)) = value.split_once(':') {
+            if prefix == "_" || suffix.starts_with("//") {
+                return Some(value.to_owned());
+            }
+            if let Some(Some(term)) = self.terms.get(prefix) {
+                if term.prefix {
+                    return Some(format!("{}{}", term.id, suffix));
+                }
+            }
+            return Some(value.to_owned());
+        }
+        if vocab {
+            if let Some(vocab) = &self.vocab {
+                return Some(format!("{}{}", vocab, value));
+            }
+        }
+        if document_relative {
+            return Some(self.resolve(value));
+        }
+        Some(value.to_owned())
+    }
+
+    /// Resolves `value` against the base IRI, if there is one.
+    fn resolve(&self, value: &str) -> String {
+        self.base
+            .as_ref()
+            .and_then(|base| Url::parse(base).ok())
+            .and_then(|base| base.join(value).ok())
+            .map(String::from)
+            .unwrap_or_else(|| value.to_owned())
+    }
+}
+
+pub(super) fn is_keyword(value: &str) -> bool {
+    [
+        "@base",
+        "@container",
+        "@context",
+        "@direction",
+        "@graph",
+        "@id",
+        "@import",
+        "@included",
+        "@index",
+        "@json",
+        "@language",
+        "@list",
+        "@nest",
+        "@none",
+        "@prefix",
+        "@propagate",
+        "@protected",
+        "@reverse",
+        "@set",
+        "@type",
+        "@value",
+        "@version",
+        "@vocab",
+    ]
+    .contains(&value)
+}
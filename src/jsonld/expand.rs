@@ -0,0 +1,287 @@
+use crate::error::DidError;
+use crate::jsonld::context::ActiveContext;
+use crate::jsonld::{invalid, ContextLoader};
+use serde_json::{Map, Value};
+
+/// The expansion algorithm, JSON-LD 1.1 API section 5.1. When `strict`
+/// is set, members that would be dropped because no context defines them
+/// are an error instead, and nodes with nothing but an id are kept.
+pub(super) fn expand<L: ContextLoader + ?Sized>(
+    active: &ActiveContext,
+    property: Option<&str>,
+    element: &Value,
+    loader: &L,
+    strict: bool,
+) -> Result<Value, DidError> {
+    match element {
+        Value::Null => Ok(Value::Null),
+        Value::Array(items) => {
+            let mut result = Vec::new();
+            for item in items {
+                match expand(active, property, item, loader, strict)? {
+                    Value::Null => {}
+                    Value::Array(expanded) => result.extend(expanded),
+                    expanded => result.push(expanded),
+                }
+            }
+            Ok(Value::Array(result))
+        }
+        Value::Object(map) => expand_object(active, property, map, loader, strict),
+        scalar => {
+            let property = match property {
+                None | Some("@graph") => return Ok(Value::Null),
+                Some(property) => property,
+            };
+            let active = match active.term(property).and_then(|t| t.context.as_ref()) {
+                Some(scoped) => active.process(scoped, loader, true)?,
+                None => active.clone(),
+            };
+            Ok(expand_value(&active, property, scalar))
+        }
+    }
+}
+
+fn expand_object<L: ContextLoader + ?Sized>(
+    active: &ActiveContext,
+    property: Option<&str>,
+    map: &Map<String, Value>,
+    loader: &L,
+    strict: bool,
+) -> Result<Value, DidError> {
+    let mut active = active.clone();
+
+    // type-scoped contexts end with the node they were set for
+    if let Some(previous) = &active.previous {
+        let is_reference = map.len() == 1 && keyword(&active, map, "@id").is_some();
+        if keyword(&active, map, "@value").is_none() && !is_reference {
+            active = (**previous).clone();
+        }
+    }
+    if let Some(scoped) = property
+        .and_then(|p| active.term(p))
+        .and_then(|t| t.context.clone())
+    {
+        active = active.process(&scoped, loader, true)?;
+    }
+    if let Some(local) = map.get("@context") {
+        active = active.process(local, loader, true)?;
+    }
+
+    // the types of the node are expanded against the context before any
+    // of their scoped contexts is applied
+    let type_scoped = active.clone();
+    let mut keys: Vec<&String> = map.keys().collect();
+    keys.sort();
+    for key in &keys {
+        if active.expand_iri(key, false, true).as_deref() != Some("@type") {
+            continue;
+        }
+        let mut types: Vec<&str> = match &map[key.as_str()] {
+            Value::String(t) => vec![t],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        types.sort_unstable();
+        for t in types {
+            if let Some(scoped) = type_scoped.term(t).and_then(|t| t.context.clone()) {
+                active = active.process(&scoped, loader, false)?;
+            }
+        }
+    }
+
+    let mut result = Map::new();
+    for key in keys {
+        let value = &map[key.as_str()];
+        if key == "@context" {
+            continue;
+        }
+        let expanded_property = match active.expand_iri(key, false, true) {
+            Some(p) if p.starts_with('@') || p.contains(':') => p,
+            _ if strict => return Err(invalid(format!("{} is not defined by any context", key))),
+            _ => continue,
+        };
+
+        if expanded_property.starts_with('@') {
+            let expanded = match expanded_property.as_str() {
+                "@id" => match value {
+                    Value::String(id) => Value::from(active.expand_iri(id, true, false)),
+                    other => return Err(invalid(format!("invalid @id {}", other))),
+                },
+                "@type" => {
+                    let expand_type = |t: &Value| match t {
+                        Value::String(t) => Ok(Value::from(type_scoped.expand_iri(t, true, true))),
+                        other => Err(invalid(format!("invalid @type {}", other))),
+                    };
+                    match value {
+                        Value::Array(ts) => {
+                            Value::Array(ts.iter().map(expand_type).collect::<Result<_, _>>()?)
+                        }
+                        t => expand_type(t)?,
+                    }
+                }
+                "@graph" | "@included" => Value::Array(as_array(expand(
+                    &active,
+                    Some("@graph"),
+                    value,
+                    loader,
+                    strict,
+                )?)),
+                "@value" => value.clone(),
+                "@language" | "@index" => match value {
+                    Value::String(_) => value.clone(),
+                    other => return Err(invalid(format!("invalid {} {}", key, other))),
+                },
+                "@list" => {
+                    Value::Array(as_array(expand(&active, property, value, loader, strict)?))
+                }
+                "@set" => expand(&active, property, value, loader, strict)?,
+                "@reverse" | "@nest" => {
+                    return Err(invalid(format!("{} is not supported", expanded_property)))
+                }
+                _ => continue,
+            };
+            result.insert(expanded_property, expanded);
+            continue;
+        }
+
+        let term = active.term(key).cloned();
+        let container = |c: &str| term.as_ref().map(|t| t.has_container(c)).unwrap_or(false);
+        let mut expanded = if term.as_ref().and_then(|t| t.type_mapping.as_deref()) == Some("@json")
+        {
+            json_value(value.clone())
+        } else {
+            if value.is_object()
+                && ["@language", "@index", "@id", "@type"]
+                    .iter()
+                    .any(|c| container(c))
+            {
+                return Err(invalid(format!(
+                    "the map container of {} is not supported",
+                    key
+                )));
+            }
+            expand(&active, Some(key), value, loader, strict)?
+        };
+        if expanded.is_null() {
+            continue;
+        }
+        if container("@list") && !is_list(&expanded) {
+            expanded = list(as_array(expanded));
+        }
+        if container("@graph") {
+            expanded = Value::Array(
+                as_array(expanded)
+                    .into_iter()
+                    .map(|item| {
+                        let mut graph = Map::new();
+                        graph.insert("@graph".to_owned(), Value::Array(as_array(item)));
+                        Value::Object(graph)
+                    })
+                    .collect(),
+            );
+        }
+        if let Value::Array(values) = result
+            .entry(expanded_property)
+            .or_insert_with(|| Value::Array(Vec::new()))
+        {
+            values.extend(as_array(expanded));
+        }
+    }
+
+    if let Some(value) = result.get("@value") {
+        if result.keys().any(|k| {
+            !["@value", "@type", "@language", "@index", "@direction"].contains(&k.as_str())
+        }) {
+            return Err(invalid("value objects cannot have other members"));
+        }
+        if value.is_null() {
+            return Ok(Value::Null);
+        }
+    } else if let Some(types) = result.get_mut("@type") {
+        if !types.is_array() {
+            *types = Value::Array(vec![types.take()]);
+        }
+    } else if let Some(set) = result.remove("@set") {
+        return Ok(set);
+    }
+
+    if result.len() == 1 && result.contains_key("@language") {
+        return Ok(Value::Null);
+    }
+    // free-floating values and bare references at the top are dropped
+    if property.is_none() || property == Some("@graph") {
+        let only = |k: &str| result.len() == 1 && result.contains_key(k);
+        if result.is_empty()
+            || result.contains_key("@value")
+            || only("@list")
+            || (only("@id") && !strict)
+        {
+            return Ok(Value::Null);
+        }
+    }
+    Ok(Value::Object(result))
+}
+
+/// Value expansion, JSON-LD 1.1 API section 5.3.
+fn expand_value(active: &ActiveContext, property: &str, value: &Value) -> Value {
+    let type_mapping = active.term(property).and_then(|t| t.type_mapping.clone());
+    let mut result = Map::new();
+    match (type_mapping.as_deref(), value) {
+        (Some("@id"), Value::String(id)) => {
+            result.insert(
+                "@id".to_owned(),
+                Value::from(active.expand_iri(id, true, false)),
+            );
+        }
+        (Some("@vocab"), Value::String(id)) => {
+            result.insert(
+                "@id".to_owned(),
+                Value::from(active.expand_iri(id, true, true)),
+            );
+        }
+        (Some(t), _) if !["@id", "@vocab", "@none"].contains(&t) => {
+            result.insert("@value".to_owned(), value.clone());
+            result.insert("@type".to_owned(), Value::from(t));
+        }
+        _ => {
+            result.insert("@value".to_owned(), value.clone());
+        }
+    }
+    Value::Object(result)
+}
+
+/// The value of the member of `map` that is an alias of `keyword`.
+fn keyword<'a>(
+    active: &ActiveContext,
+    map: &'a Map<String, Value>,
+    keyword: &str,
+) -> Option<&'a Value> {
+    map.iter()
+        .find(|(k, _)| active.expand_iri(k, false, true).as_deref() == Some(keyword))
+        .map(|(_, v)| v)
+}
+
+fn json_value(value: Value) -> Value {
+    let mut result = Map::new();
+    result.insert("@value".to_owned(), value);
+    result.insert("@type".to_owned(), Value::from("@json"));
+    Value::Object(result)
+}
+
+fn list(items: Vec<Value>) -> Value {
+    let mut result = Map::new();
+    result.insert("@list".to_owned(), Value::Array(items));
+    Value::Object(result)
+}
+
+fn is_list(value: &Value) -> bool {
+    value.get("@list").is_some()
+}
+
+pub(super) fn as_array(value: Value) -> Vec<Value> {
+    match value {
+        Value::Array(values) => values,
+        Value::Null => Vec::new(),
+        value => vec![value],
+    }
+}
@@ -0,0 +1,95 @@
+use crate::error::{DidError, DidErrorKind};
+use crate::fields::KnownContext;
+use crate::jsonld::invalid;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Loads the remote contexts a document names. `StaticLoader` serves the
+/// DID and security contexts without network access.
+pub trait ContextLoader {
+    /// Returns the document at `url`, whose `@context` member holds the
+    /// context.
+    fn load(&self, url: &str) -> Result<Value, DidError>;
+}
+
+impl<L: ContextLoader + ?Sized> ContextLoader for &L {
+    fn load(&self, url: &str) -> Result<Value, DidError> {
+        (**self).load(url)
+    }
+}
+
+impl<L: ContextLoader + ?Sized> ContextLoader for Box<L> {
+    fn load(&self, url: &str) -> Result<Value, DidError> {
+        (**self).load(url)
+    }
+}
+
+/// A `ContextLoader` with copies of the `KnownContext`s bundled into the
+/// crate. Other contexts can be added with `insert` or resolved by a
+/// fallback loader.
+#[derive(Default)]
+pub struct StaticLoader {
+    documents: HashMap<String, Value>,
+    fallback: Option<Box<dyn ContextLoader + Send + Sync>>,
+}
+
+impl StaticLoader {
+    pub fn new() -> Self {
+        StaticLoader::default()
+    }
+
+    /// Serves `document` for `url`, in place of a bundled copy if there is
+    /// one.
+    pub fn insert(&mut self, url: &str, document: Value) {
+        self.documents.insert(url.to_owned(), document);
+    }
+
+    /// Asks `fallback` for contexts that are neither bundled nor inserted.
+    pub fn with_fallback<L>(mut self, fallback: L) -> Self
+    where
+        L: ContextLoader + Send + Sync + 'static,
+    {
+        self.fallback = Some(Box::new(fallback));
+        self
+    }
+}
+
+impl ContextLoader for StaticLoader {
+    fn load(&self, url: &str) -> Result<Value, DidError> {
+        if let Some(document) = self.documents.get(url) {
+            return Ok(document.clone());
+        }
+        if let Some(known) = KnownContext::from_url(url) {
+            return serde_json::from_str(bundled(known))
+                .map_err(|e| invalid(format!("bundled context {}: {}", url, e)));
+        }
+        match &self.fallback {
+            Some(fallback) => fallback.load(url),
+            None => Err(DidError::from_kind(DidErrorKind::UnknownContext {
+                url: url.to_owned(),
+            })),
+        }
+    }
+}
+
+fn bundled(context: KnownContext) -> &'static str {
+    match context {
+        KnownContext::DidV1 => include_str!("contexts/did-v1.jsonld"),
+        KnownContext::DidCore => include_str!("contexts/did-core-v1.jsonld"),
+        KnownContext::SecurityV1 => include_str!("contexts/security-v1.jsonld"),
+        KnownContext::SecurityV2 => include_str!("contexts/security-v2.jsonld"),
+        KnownContext::Ed25519Signature2018 => include_str!("contexts/ed25519-2018-v1.jsonld"),
+        KnownContext::Ed25519Signature2020 => include_str!("contexts/ed25519-2020-v1.jsonld"),
+        KnownContext::X25519KeyAgreement2019 => include_str!("contexts/x25519-2019-v1.jsonld"),
+        KnownContext::X25519KeyAgreement2020 => include_str!("contexts/x25519-2020-v1.jsonld"),
+        KnownContext::JsonWebSignature2020 => include_str!("contexts/jws-2020-v1.jsonld"),
+        KnownContext::EcdsaSecp256k1Signature2019 => {
+            include_str!("contexts/secp256k1-2019-v1.jsonld")
+        }
+        KnownContext::EcdsaSecp256k1Recovery2020 => {
+            include_str!("contexts/secp256k1recovery-2020-v2.jsonld")
+        }
+        KnownContext::Bls12381Signature2020 => include_str!("contexts/bls12381-2020-v1.jsonld"),
+        KnownContext::Multikey => include_str!("contexts/multikey-v1.jsonld"),
+    }
+}
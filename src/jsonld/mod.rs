@@ -0,0 +1,141 @@
+//! JSON-LD expansion and compaction of documents.
+//!
+//! This implements the parts of the JSON-LD 1.1 algorithms DID documents
+//! use: remote, inline, property-scoped and type-scoped contexts, keyword
+//! aliases, compact IRIs, typed values and `@set`, `@list`, `@graph` and
+//! `@json` containers. Reverse properties, `@import`, `@nest` and map
+//! containers are rejected with `InvalidJsonLd`, and `@protected` terms
+//! are not enforced.
+
+mod compact;
+mod context;
+mod expand;
+mod loader;
+
+pub use self::loader::{ContextLoader, StaticLoader};
+
+use self::context::ActiveContext;
+use self::expand::as_array;
+use crate::doc::Document;
+use crate::error::{err_msg, DidError, DidErrorKind};
+use crate::fields::ContextEntry;
+use serde_json::{Map, Value};
+
+/// The members of a `Document` that are always lists, although contexts
+/// need not make them `@set`s.
+const LIST_MEMBERS: &[&str] = &[PUBLIC_KEY, "authentication", "keyAgreement", "service"];
+
+const PUBLIC_KEY: &str = "publicKey";
+const VERIFICATION_METHOD: &str = "verificationMethod";
+
+pub(super) fn invalid<D>(msg: D) -> DidError
+where
+    D: std::fmt::Display + std::fmt::Debug + Send + Sync + 'static,
+{
+    err_msg(DidErrorKind::InvalidJsonLd, msg)
+}
+
+/// Expands `input` into an array of node objects in expanded form.
+pub fn expand<L: ContextLoader + ?Sized>(input: &Value, loader: &L) -> Result<Value, DidError> {
+    expand_with(input, loader, false)
+}
+
+fn expand_with<L: ContextLoader + ?Sized>(
+    input: &Value,
+    loader: &L,
+    strict: bool,
+) -> Result<Value, DidError> {
+    let expanded = expand::expand(&ActiveContext::default(), None, input, loader, strict)?;
+    let expanded = match expanded {
+        Value::Object(mut map) if map.len() == 1 && map.contains_key("@graph") => {
+            map.remove("@graph").unwrap_or_default()
+        }
+        expanded => expanded,
+    };
+    Ok(Value::Array(as_array(expanded)))
+}
+
+/// Compacts the expanded form `expanded` with `context`, the value of the
+/// `@context` member of the result.
+pub fn compact<L: ContextLoader + ?Sized>(
+    expanded: &Value,
+    context: &Value,
+    loader: &L,
+) -> Result<Value, DidError> {
+    let active = ActiveContext::default().process(context, loader, true)?;
+    let mut result = match compact::compact(&active, None, expanded, loader)? {
+        Value::Object(map) => map,
+        Value::Array(nodes) if !nodes.is_empty() => {
+            let mut map = Map::new();
+            map.insert(active.compact_iri("@graph", true), Value::Array(nodes));
+            map
+        }
+        _ => Map::new(),
+    };
+    let empty = match context {
+        Value::Null => true,
+        Value::Array(entries) => entries.is_empty(),
+        Value::Object(definitions) => definitions.is_empty(),
+        _ => false,
+    };
+    if !empty {
+        result.insert("@context".to_owned(), context.clone());
+    }
+    Ok(Value::Object(result))
+}
+
+/// Expands `doc`, failing rather than dropping members none of its
+/// contexts define. `publicKey` is expanded as `verificationMethod` when
+/// the contexts only know the latter, as DID Core does.
+pub(crate) fn expand_document<L: ContextLoader + ?Sized>(
+    doc: &Document,
+    loader: &L,
+) -> Result<Value, DidError> {
+    let mut input = serde_json::to_value(doc).map_err(|e| invalid(e.to_string()))?;
+    if let Value::Object(map) = &mut input {
+        let active = ActiveContext::default().process(&map["@context"], loader, true)?;
+        if active.term(PUBLIC_KEY).is_none() {
+            if let Some(keys) = map.remove(PUBLIC_KEY) {
+                if map.contains_key(VERIFICATION_METHOD) {
+                    return Err(invalid(
+                        "publicKey cannot be expanded as verificationMethod, which is taken",
+                    ));
+                }
+                map.insert(VERIFICATION_METHOD.to_owned(), keys);
+            }
+        }
+    }
+    expand_with(&input, loader, true)
+}
+
+/// Compacts `expanded` with `context` into a `Document`, reversing the
+/// renaming of `expand_document`.
+pub(crate) fn compact_document<L: ContextLoader + ?Sized>(
+    expanded: &Value,
+    context: &[ContextEntry],
+    loader: &L,
+) -> Result<Document, DidError> {
+    let context = match context {
+        [entry] => serde_json::to_value(entry),
+        entries => serde_json::to_value(entries),
+    }
+    .map_err(|e| invalid(e.to_string()))?;
+
+    let mut compacted = compact(expanded, &context, loader)?;
+    if let Value::Object(map) = &mut compacted {
+        let active = ActiveContext::default().process(&context, loader, true)?;
+        if active.term(PUBLIC_KEY).is_none() {
+            if let Some(keys) = map.remove(VERIFICATION_METHOD) {
+                map.insert(PUBLIC_KEY.to_owned(), keys);
+            }
+        }
+        for member in LIST_MEMBERS {
+            if let Some(value) = map.get_mut(*member) {
+                if !value.is_array() {
+                    *value = Value::Array(vec![value.take()]);
+                }
+            }
+        }
+    }
+    serde_json::from_value(compacted).map_err(|e| invalid(e.to_string()))
+}
@@ -1,8 +1,16 @@
-pub use self::doc::Document;
+pub use self::doc::{Document, Resource};
 pub use self::uri::Uri;
 pub use self::error::{DidError, DidErrorKind};
+pub use self::resolver::{ResolutionResult, Resolver};
+pub use self::validate::{Diagnostic, DiagnosticKind};
 
+pub mod crypto;
+mod datetime;
 pub mod doc;
+pub mod domain_linkage;
 pub mod error;
 pub mod fields;
+pub mod jsonld;
+pub mod resolver;
 pub mod uri;
+pub mod validate;
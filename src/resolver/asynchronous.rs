@@ -0,0 +1,170 @@
+//! Non-blocking variants of `Resolver` and `Dereferencer`.
+
+use crate::resolver::dereference::{resolution_options, select};
+use crate::resolver::{
+    DereferencingOptions, DereferencingResult, ResolutionError, ResolutionOptions,
+    ResolutionResult, Resolver,
+};
+use crate::uri::Uri;
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Resolves a DID into its `Document` and metadata without blocking.
+#[async_trait]
+pub trait AsyncResolver: Send + Sync {
+    async fn resolve(&self, did: &Uri, options: &ResolutionOptions) -> ResolutionResult;
+
+    /// Parses `did` before resolving it, reporting `invalidDid` if it is not
+    /// a DID.
+    async fn resolve_str(&self, did: &str, options: &ResolutionOptions) -> ResolutionResult {
+        match Uri::from_str(did) {
+            Ok(uri) => self.resolve(&uri, options).await,
+            Err(_) => ResolutionResult::from_error(ResolutionError::InvalidDid),
+        }
+    }
+}
+
+#[async_trait]
+impl<R: AsyncResolver + ?Sized> AsyncResolver for &R {
+    async fn resolve(&self, did: &Uri, options: &ResolutionOptions) -> ResolutionResult {
+        (**self).resolve(did, options).await
+    }
+}
+
+#[async_trait]
+impl<R: AsyncResolver + ?Sized> AsyncResolver for Box<R> {
+    async fn resolve(&self, did: &Uri, options: &ResolutionOptions) -> ResolutionResult {
+        (**self).resolve(did, options).await
+    }
+}
+
+#[async_trait]
+impl<R: AsyncResolver + ?Sized> AsyncResolver for Arc<R> {
+    async fn resolve(&self, did: &Uri, options: &ResolutionOptions) -> ResolutionResult {
+        (**self).resolve(did, options).await
+    }
+}
+
+/// Runs a blocking `Resolver` on tokio's blocking thread pool.
+pub struct Blocking<R> {
+    resolver: Arc<R>,
+}
+
+impl<R: Resolver + 'static> Blocking<R> {
+    pub fn new(resolver: R) -> Self {
+        Blocking {
+            resolver: Arc::new(resolver),
+        }
+    }
+
+    pub fn resolver(&self) -> &R {
+        &self.resolver
+    }
+}
+
+#[async_trait]
+impl<R: Resolver + 'static> AsyncResolver for Blocking<R> {
+    async fn resolve(&self, did: &Uri, options: &ResolutionOptions) -> ResolutionResult {
+        let resolver = self.resolver.clone();
+        let did = did.clone();
+        let options = options.clone();
+        tokio::task::spawn_blocking(move || resolver.resolve(&did, &options))
+            .await
+            .unwrap_or_else(|_| ResolutionResult::from_error(ResolutionError::InternalError))
+    }
+}
+
+/// Fails resolutions that take longer than a fixed duration, or the
+/// `timeout` of their `ResolutionOptions`, with `ResolutionError::Timeout`.
+pub struct Timeout<R> {
+    resolver: R,
+    timeout: Duration,
+}
+
+impl<R: AsyncResolver> Timeout<R> {
+    pub fn new(resolver: R, timeout: Duration) -> Self {
+        Timeout { resolver, timeout }
+    }
+
+    pub fn resolver(&self) -> &R {
+        &self.resolver
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+}
+
+#[async_trait]
+impl<R: AsyncResolver> AsyncResolver for Timeout<R> {
+    async fn resolve(&self, did: &Uri, options: &ResolutionOptions) -> ResolutionResult {
+        let timeout = options.timeout.unwrap_or(self.timeout);
+        match tokio::time::timeout(timeout, self.resolver.resolve(did, options)).await {
+            Ok(result) => result,
+            Err(_) => ResolutionResult::from_error(ResolutionError::Timeout),
+        }
+    }
+}
+
+/// The non-blocking counterpart of `Dereferencer`.
+pub struct AsyncDereferencer<R> {
+    resolver: R,
+}
+
+impl<R: AsyncResolver> AsyncDereferencer<R> {
+    pub fn new(resolver: R) -> Self {
+        AsyncDereferencer { resolver }
+    }
+
+    pub fn resolver(&self) -> &R {
+        &self.resolver
+    }
+
+    pub async fn dereference_str(
+        &self,
+        did_url: &str,
+        options: &DereferencingOptions,
+    ) -> DereferencingResult {
+        match Uri::from_str(did_url) {
+            Ok(uri) => self.dereference(&uri, options).await,
+            Err(_) => DereferencingResult::from_error(ResolutionError::InvalidDidUrl),
+        }
+    }
+
+    pub async fn dereference(
+        &self,
+        did_url: &Uri,
+        options: &DereferencingOptions,
+    ) -> DereferencingResult {
+        let resolution_options = match resolution_options(did_url, options) {
+            Ok(resolution_options) => resolution_options,
+            Err(error) => return DereferencingResult::from_error(error),
+        };
+        let resolved = self
+            .resolver
+            .resolve(&did_url.did(), &resolution_options)
+            .await;
+        select(did_url, resolved)
+    }
+}
+
+/// Resolves every DID in `dids` with at most `concurrency` resolutions in
+/// flight at once. Results are returned in the same order as `dids`.
+pub async fn resolve_many<R>(
+    resolver: &R,
+    dids: &[Uri],
+    options: &ResolutionOptions,
+    concurrency: usize,
+) -> Vec<ResolutionResult>
+where
+    R: AsyncResolver + ?Sized,
+{
+    stream::iter(dids)
+        .map(|did| resolver.resolve(did, options))
+        .buffered(concurrency.max(1))
+        .collect()
+        .await
+}
@@ -0,0 +1,185 @@
+use crate::resolver::{is_did, ResolutionError, ResolutionOptions, ResolutionResult, Resolver};
+use crate::uri::Uri;
+use indexmap::IndexMap;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_TTL: Duration = Duration::from_secs(300);
+pub const DEFAULT_NOT_FOUND_TTL: Duration = Duration::from_secs(30);
+
+/// Cache counters reported by `CachingResolver::stats`.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+/// The DID and the `accept` option it was resolved with.
+type CacheKey = (String, Option<String>);
+
+struct CacheEntry {
+    result: ResolutionResult,
+    /// `None` if the TTL is too long for an `Instant`.
+    expires: Option<Instant>,
+}
+
+impl CacheEntry {
+    fn fresh(&self, now: Instant) -> bool {
+        match self.expires {
+            Some(expires) => expires > now,
+            None => true,
+        }
+    }
+}
+
+/// Wraps a `Resolver` and caches its results keyed on the DID and the
+/// requested representation.
+///
+/// Resolved documents are kept for the TTL of their method, or the default
+/// TTL if none was set. `notFound` results are kept for the (usually
+/// shorter) negative TTL. A TTL too long to add to the current time
+/// never expires. Other errors and requests for a specific version
+/// are never cached.
+///
+/// Expired entries are dropped when they are looked up; call
+/// `evict_expired` periodically to bound the size of a cache that sees
+/// many distinct DIDs.
+pub struct CachingResolver<R> {
+    resolver: R,
+    default_ttl: Duration,
+    not_found_ttl: Duration,
+    method_ttls: IndexMap<String, Duration>,
+    entries: Mutex<HashMap<CacheKey, CacheEntry>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<R: Resolver> CachingResolver<R> {
+    pub fn new(resolver: R) -> Self {
+        CachingResolver {
+            resolver,
+            default_ttl: DEFAULT_TTL,
+            not_found_ttl: DEFAULT_NOT_FOUND_TTL,
+            method_ttls: IndexMap::default(),
+            entries: Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn resolver(&self) -> &R {
+        &self.resolver
+    }
+
+    pub fn set_default_ttl(&mut self, ttl: Duration) {
+        self.default_ttl = ttl;
+    }
+
+    pub fn set_not_found_ttl(&mut self, ttl: Duration) {
+        self.not_found_ttl = ttl;
+    }
+
+    /// Overrides the TTL for documents of `method`. A zero TTL disables
+    /// caching for that method.
+    pub fn set_method_ttl(&mut self, method: &str, ttl: Duration) {
+        self.method_ttls.insert(method.to_owned(), ttl);
+    }
+
+    pub fn ttl(&self, method: &str) -> Duration {
+        self.method_ttls
+            .get(method)
+            .cloned()
+            .unwrap_or(self.default_ttl)
+    }
+
+    /// Drops the cached results for `did`, in every representation,
+    /// returning whether one was present.
+    pub fn invalidate(&self, did: &Uri) -> bool {
+        let did = did.did().to_string();
+        let mut entries = self.entries.lock().unwrap();
+        let len = entries.len();
+        entries.retain(|(cached, _), _| *cached != did);
+        entries.len() != len
+    }
+
+    /// Drops the entries whose TTL has passed, returning how many there
+    /// were.
+    pub fn evict_expired(&self) -> usize {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        let len = entries.len();
+        entries.retain(|_, e| e.fresh(now));
+        len - entries.len()
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.entries.lock().unwrap().len(),
+        }
+    }
+
+    pub fn reset_stats(&self) {
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
+
+    fn lookup(&self, key: &CacheKey) -> Option<ResolutionResult> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some(entry) if entry.fresh(Instant::now()) => Some(entry.result.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn store(&self, key: CacheKey, did: &Uri, result: &ResolutionResult) {
+        let ttl = match result.error() {
+            None => self.ttl(&did.method),
+            Some(ResolutionError::NotFound) => self.not_found_ttl,
+            Some(_) => return,
+        };
+        if ttl == Duration::from_secs(0) {
+            return;
+        }
+
+        let now = Instant::now();
+        self.entries.lock().unwrap().insert(
+            key,
+            CacheEntry {
+                result: result.clone(),
+                expires: now.checked_add(ttl),
+            },
+        );
+    }
+}
+
+impl<R: Resolver> Resolver for CachingResolver<R> {
+    fn resolve(&self, did: &Uri, options: &ResolutionOptions) -> ResolutionResult {
+        if !is_did(did) || options.version_id.is_some() || options.version_time.is_some() {
+            return self.resolver.resolve(did, options);
+        }
+
+        let key = (did.did().to_string(), options.accept.clone());
+        if let Some(result) = self.lookup(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return result;
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let result = self.resolver.resolve(did, options);
+        self.store(key, did, &result);
+        result
+    }
+}
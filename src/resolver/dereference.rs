@@ -0,0 +1,200 @@
+use crate::doc::{Document, Resource};
+use crate::error::DidErrorKind;
+use crate::fields::{PublicKey, ServiceEndpoint};
+use crate::resolver::{
+    DocumentMetadata, ResolutionError, ResolutionMetadata, ResolutionOptions, ResolutionResult,
+    Resolver, DID_JSON,
+};
+use crate::uri::Uri;
+use indexmap::IndexMap;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Dereferencing metadata has the same shape as resolution metadata.
+pub type DereferencingMetadata = ResolutionMetadata;
+
+/// Input options passed to a `Dereferencer`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DereferencingOptions {
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub accept: Option<String>,
+    /// Passed on to the resolver as `ResolutionOptions::timeout`.
+    #[serde(skip)]
+    pub timeout: Option<Duration>,
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
+}
+
+/// The resource a DID URL dereferenced to.
+#[derive(Serialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum Content {
+    Document(Document),
+    PublicKey(PublicKey),
+    Service(ServiceEndpoint),
+    Url(String),
+}
+
+#[derive(Serialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DereferencingResult {
+    #[serde(rename = "contentStream")]
+    pub content: Option<Content>,
+    pub dereferencing_metadata: DereferencingMetadata,
+    pub content_metadata: DocumentMetadata,
+}
+
+impl DereferencingResult {
+    pub fn new(content: Content, content_type: &str, metadata: DocumentMetadata) -> Self {
+        DereferencingResult {
+            content: Some(content),
+            dereferencing_metadata: DereferencingMetadata {
+                content_type: Some(content_type.to_owned()),
+                ..DereferencingMetadata::default()
+            },
+            content_metadata: metadata,
+        }
+    }
+
+    pub fn from_error(error: ResolutionError) -> Self {
+        DereferencingResult {
+            content: None,
+            dereferencing_metadata: DereferencingMetadata {
+                error: Some(error),
+                ..DereferencingMetadata::default()
+            },
+            content_metadata: DocumentMetadata::default(),
+        }
+    }
+
+    pub fn error(&self) -> Option<ResolutionError> {
+        self.dereferencing_metadata.error
+    }
+}
+
+/// Dereferences DID URLs by resolving the DID they are rooted at and then
+/// selecting the part of the `Document` the URL points to.
+///
+/// * `did:example:123` returns the whole document
+/// * `did:example:123#keys-1` returns the embedded key or service
+/// * `did:example:123?service=files&relativeRef=/a.pdf` returns a URL built
+///   from the service endpoint
+/// * `versionId` and `versionTime` query parameters select historical
+///   versions through the resolver
+pub struct Dereferencer<R> {
+    resolver: R,
+}
+
+impl<R: Resolver> Dereferencer<R> {
+    pub fn new(resolver: R) -> Self {
+        Dereferencer { resolver }
+    }
+
+    pub fn resolver(&self) -> &R {
+        &self.resolver
+    }
+
+    pub fn dereference_str(
+        &self,
+        did_url: &str,
+        options: &DereferencingOptions,
+    ) -> DereferencingResult {
+        match Uri::from_str(did_url) {
+            Ok(uri) => self.dereference(&uri, options),
+            Err(_) => DereferencingResult::from_error(ResolutionError::InvalidDidUrl),
+        }
+    }
+
+    pub fn dereference(
+        &self,
+        did_url: &Uri,
+        options: &DereferencingOptions,
+    ) -> DereferencingResult {
+        let resolution_options = match resolution_options(did_url, options) {
+            Ok(resolution_options) => resolution_options,
+            Err(error) => return DereferencingResult::from_error(error),
+        };
+        let resolved = self.resolver.resolve(&did_url.did(), &resolution_options);
+        select(did_url, resolved)
+    }
+}
+
+/// Checks that `did_url` can be dereferenced and derives the options used to
+/// resolve its DID.
+pub(crate) fn resolution_options(
+    did_url: &Uri,
+    options: &DereferencingOptions,
+) -> Result<ResolutionOptions, ResolutionError> {
+    if did_url.is_empty() || did_url.is_relative() || did_url.id.is_empty() {
+        return Err(ResolutionError::InvalidDidUrl);
+    }
+
+    let query = did_url.query.clone().unwrap_or_default();
+    Ok(ResolutionOptions {
+        accept: options.accept.clone(),
+        version_id: query.get("versionId").cloned(),
+        version_time: query.get("versionTime").cloned(),
+        timeout: options.timeout,
+        ..ResolutionOptions::default()
+    })
+}
+
+/// Selects the part of the resolved document that `did_url` points to.
+pub(crate) fn select(did_url: &Uri, resolved: ResolutionResult) -> DereferencingResult {
+    if let Some(error) = resolved.error() {
+        return DereferencingResult::from_error(error);
+    }
+    let document = match resolved.document {
+        Some(document) => document,
+        None => return DereferencingResult::from_error(ResolutionError::NotFound),
+    };
+    let metadata = resolved.did_document_metadata;
+
+    let query = did_url.query.clone().unwrap_or_default();
+    if let Some(service) = query.get("service") {
+        return match service_url(&document, service, query.get("relativeRef"), did_url) {
+            Ok(url) => DereferencingResult::new(Content::Url(url), "text/uri-list", metadata),
+            Err(error) => DereferencingResult::from_error(error),
+        };
+    }
+
+    if did_url.fragment.is_some() {
+        // the query only selected the document version
+        let mut target = did_url.did();
+        target.fragment = did_url.fragment.clone();
+        return match document.dereference(&target) {
+            Some(Resource::PublicKey(pk)) => {
+                DereferencingResult::new(Content::PublicKey(pk.clone()), DID_JSON, metadata)
+            }
+            Some(Resource::Service(s)) => {
+                DereferencingResult::new(Content::Service(s.clone()), DID_JSON, metadata)
+            }
+            None => DereferencingResult::from_error(ResolutionError::NotFound),
+        };
+    }
+
+    DereferencingResult::new(Content::Document(document), DID_JSON, metadata)
+}
+
+/// Maps the errors of `Document::service_url` onto resolution errors.
+fn service_url(
+    document: &Document,
+    service: &str,
+    relative_ref: Option<&String>,
+    did_url: &Uri,
+) -> Result<String, ResolutionError> {
+    document
+        .build_service_url(
+            service,
+            relative_ref.map(String::as_str),
+            did_url.fragment.as_deref(),
+        )
+        .map_err(|e| match e.kind() {
+            DidErrorKind::DanglingReference => ResolutionError::NotFound,
+            DidErrorKind::InvalidDidUrl => ResolutionError::InvalidDidUrl,
+            _ => ResolutionError::InternalError,
+        })
+}
@@ -0,0 +1,177 @@
+use crate::doc::Document;
+use crate::resolver::{
+    is_did, DocumentMetadata, ResolutionError, ResolutionMetadata, ResolutionOptions,
+    ResolutionResult, Resolver, DID_RESOLUTION,
+};
+use crate::uri::Uri;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use std::error::Error;
+use std::io;
+use std::time::Duration;
+
+/// What is escaped when a DID becomes a path segment: everything but
+/// unreserved characters and the colons separating its parts.
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~')
+    .remove(b':');
+
+/// A `Resolver` that calls the HTTP(S) binding of a DID resolver such as
+/// the Universal Resolver, i.e. `GET {base}/1.0/identifiers/{did}`.
+///
+/// The `Accept` header comes from `ResolutionOptions::accept` or, if that
+/// is unset, from the resolver's own default, which asks for the complete
+/// resolution result. HTTP status codes are mapped back onto
+/// `ResolutionError` codes when the body does not carry one itself.
+pub struct UniversalResolver {
+    base_url: String,
+    accept: String,
+    agent: ureq::Agent,
+}
+
+impl UniversalResolver {
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+    pub fn new(base_url: &str) -> Self {
+        UniversalResolver::with_timeout(base_url, Self::DEFAULT_TIMEOUT)
+    }
+
+    pub fn with_timeout(base_url: &str, timeout: Duration) -> Self {
+        UniversalResolver {
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            accept: DID_RESOLUTION.to_owned(),
+            agent: ureq::AgentBuilder::new().timeout(timeout).build(),
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Sets the media type requested when the options do not specify one.
+    pub fn set_accept(&mut self, media_type: &str) {
+        self.accept = media_type.to_owned();
+    }
+
+    fn url(&self, did: &Uri) -> String {
+        format!(
+            "{}/1.0/identifiers/{}",
+            self.base_url,
+            utf8_percent_encode(&did.to_string(), PATH_SEGMENT)
+        )
+    }
+}
+
+impl Resolver for UniversalResolver {
+    fn resolve(&self, did: &Uri, options: &ResolutionOptions) -> ResolutionResult {
+        if !is_did(did) {
+            return ResolutionResult::from_error(ResolutionError::InvalidDid);
+        }
+
+        let accept = options.accept.as_ref().unwrap_or(&self.accept);
+        let mut request = self.agent.get(&self.url(did)).set("Accept", accept);
+        if let Some(version_id) = &options.version_id {
+            request = request.query("versionId", version_id);
+        }
+        if let Some(version_time) = &options.version_time {
+            request = request.query("versionTime", version_time);
+        }
+
+        match request.call() {
+            Ok(response) => from_response(response, None),
+            Err(ureq::Error::Status(status, response)) => {
+                from_response(response, Some(status_error(status)))
+            }
+            Err(ureq::Error::Transport(transport)) => {
+                ResolutionResult::from_error(io_error(transport.source()))
+            }
+        }
+    }
+}
+
+/// Maps the status codes of the DID Resolution HTTP(S) binding onto
+/// resolution errors.
+fn status_error(status: u16) -> ResolutionError {
+    match status {
+        400 => ResolutionError::InvalidDid,
+        404 | 410 => ResolutionError::NotFound,
+        406 => ResolutionError::RepresentationNotSupported,
+        501 => ResolutionError::MethodNotSupported,
+        504 => ResolutionError::Timeout,
+        _ => ResolutionError::InternalError,
+    }
+}
+
+/// Maps a failed connection or read onto `Timeout` if it timed out and
+/// `InternalError` otherwise.
+fn io_error(source: Option<&(dyn Error + 'static)>) -> ResolutionError {
+    match source.and_then(|e| e.downcast_ref::<io::Error>()) {
+        Some(e) if e.kind() == io::ErrorKind::TimedOut => ResolutionError::Timeout,
+        _ => ResolutionError::InternalError,
+    }
+}
+
+fn from_response(
+    response: ureq::Response,
+    status_error: Option<ResolutionError>,
+) -> ResolutionResult {
+    let deactivated = response.status() == 410;
+    let content_type = response
+        .header("Content-Type")
+        .map(|h| h.to_owned())
+        .unwrap_or_default();
+    let body = match response.into_string() {
+        Ok(body) => body,
+        Err(e) => return ResolutionResult::from_error(io_error(Some(&e))),
+    };
+
+    // a resolution result may be returned for errors too, in which case its
+    // own error code is more precise than the status code
+    if is_resolution_result(&content_type) {
+        if let Ok(mut result) = serde_json::from_str::<ResolutionResult>(&body) {
+            if result.error().is_none() && !deactivated {
+                result.did_resolution_metadata.error = status_error;
+            }
+            return result;
+        }
+    }
+
+    if deactivated {
+        return ResolutionResult {
+            document: serde_json::from_str::<Document>(&body).ok(),
+            did_resolution_metadata: ResolutionMetadata::default(),
+            did_document_metadata: DocumentMetadata {
+                deactivated: Some(true),
+                ..DocumentMetadata::default()
+            },
+        };
+    }
+
+    if let Some(error) = status_error {
+        return ResolutionResult::from_error(error);
+    }
+
+    match serde_json::from_str::<Document>(&body) {
+        Ok(document) => {
+            let mut result = ResolutionResult::new(document, DocumentMetadata::default());
+            result.did_resolution_metadata.content_type = Some(media_type(&content_type));
+            result
+        }
+        Err(_) => ResolutionResult::from_error(ResolutionError::InternalError),
+    }
+}
+
+fn media_type(content_type: &str) -> String {
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_owned()
+}
+
+fn is_resolution_result(content_type: &str) -> bool {
+    content_type.contains("did-resolution")
+}
@@ -0,0 +1,92 @@
+use crate::datetime;
+use crate::doc::Document;
+use crate::resolver::{
+    is_did, DocumentMetadata, ResolutionError, ResolutionOptions, ResolutionResult, Resolver,
+};
+use crate::uri::Uri;
+use indexmap::IndexMap;
+
+/// A `Resolver` backed by a map of documents, mostly useful for tests.
+///
+/// Every inserted document is kept as a new version of its DID. Resolving
+/// without a `versionId` returns the most recent one. A `versionTime`
+/// selects the newest version `updated`, or failing that `created`, at or
+/// before that time; versions with neither date never match one.
+#[derive(Default)]
+pub struct InMemoryResolver {
+    documents: IndexMap<String, Vec<(Document, DocumentMetadata)>>,
+}
+
+impl InMemoryResolver {
+    pub fn new() -> Self {
+        InMemoryResolver::default()
+    }
+
+    pub fn insert(&mut self, document: Document) {
+        self.insert_with_metadata(document, DocumentMetadata::default());
+    }
+
+    pub fn insert_with_metadata(&mut self, document: Document, metadata: DocumentMetadata) {
+        let did = document.subject().as_uri().did().to_string();
+        self.documents
+            .entry(did)
+            .or_default()
+            .push((document, metadata));
+    }
+
+    pub fn remove(&mut self, did: &Uri) -> bool {
+        self.documents
+            .shift_remove(&did.did().to_string())
+            .is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+}
+
+impl Resolver for InMemoryResolver {
+    fn resolve(&self, did: &Uri, options: &ResolutionOptions) -> ResolutionResult {
+        if !is_did(did) {
+            return ResolutionResult::from_error(ResolutionError::InvalidDid);
+        }
+
+        let versions = match self.documents.get(&did.to_string()) {
+            Some(versions) => versions,
+            None => return ResolutionResult::from_error(ResolutionError::NotFound),
+        };
+
+        let time = match options.version_time.as_deref() {
+            Some(date) => match datetime::parse(date) {
+                Some(time) => Some(time),
+                None => return ResolutionResult::from_error(ResolutionError::InvalidOptions),
+            },
+            None => None,
+        };
+
+        let mut candidates = versions
+            .iter()
+            .filter(|(_, m)| options.version_id.is_none() || m.version_id == options.version_id);
+        let found = match time {
+            Some(time) => candidates
+                .filter_map(|v| {
+                    let date = v.1.updated.as_ref().or(v.1.created.as_ref())?;
+                    Some((datetime::parse(date)?, v))
+                })
+                .filter(|(t, _)| *t <= time)
+                .max_by_key(|(t, _)| *t)
+                .map(|(_, v)| v),
+            None if options.version_id.is_some() => candidates.next(),
+            None => candidates.next_back(),
+        };
+
+        match found {
+            Some((document, metadata)) => ResolutionResult::new(document.clone(), metadata.clone()),
+            None => ResolutionResult::from_error(ResolutionError::NotFound),
+        }
+    }
+}
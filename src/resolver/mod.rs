@@ -0,0 +1,203 @@
+#[cfg(feature = "async")]
+pub use self::asynchronous::{resolve_many, AsyncDereferencer, AsyncResolver, Blocking, Timeout};
+pub use self::cache::{CacheStats, CachingResolver};
+pub use self::dereference::{
+    Content, Dereferencer, DereferencingMetadata, DereferencingOptions, DereferencingResult,
+};
+#[cfg(feature = "http-client")]
+pub use self::http::UniversalResolver;
+pub use self::memory::InMemoryResolver;
+pub use self::registry::MethodRegistry;
+#[cfg(feature = "http-server")]
+pub use self::server::Server;
+
+#[cfg(feature = "async")]
+mod asynchronous;
+pub mod cache;
+mod dereference;
+#[cfg(feature = "http-client")]
+mod http;
+mod memory;
+mod registry;
+#[cfg(feature = "http-server")]
+mod server;
+
+use crate::doc::Document;
+use crate::uri::Uri;
+use indexmap::IndexMap;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Media type of a plain JSON `Document` representation.
+pub const DID_JSON: &str = "application/did+json";
+/// Media type of a JSON-LD `Document` representation.
+pub const DID_LD_JSON: &str = "application/did+ld+json";
+/// Media type of a complete `ResolutionResult`.
+pub const DID_RESOLUTION: &str = "application/ld+json;profile=\"https://w3id.org/did-resolution\"";
+
+/// The error codes defined by DID Core for `didResolutionMetadata.error` and
+/// `dereferencingMetadata.error`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum ResolutionError {
+    InvalidDid,
+    InvalidDidUrl,
+    NotFound,
+    RepresentationNotSupported,
+    MethodNotSupported,
+    InternalError,
+    /// An option, e.g. a malformed `versionTime`, cannot be honored. This is
+    /// a DID Resolution rather than a DID Core error code.
+    InvalidOptions,
+    /// The resolver did not answer within the time allowed for the call.
+    /// This is not a DID Core error code.
+    Timeout,
+}
+
+impl fmt::Display for ResolutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let code = match self {
+            ResolutionError::InvalidDid => "invalidDid",
+            ResolutionError::InvalidDidUrl => "invalidDidUrl",
+            ResolutionError::NotFound => "notFound",
+            ResolutionError::RepresentationNotSupported => "representationNotSupported",
+            ResolutionError::MethodNotSupported => "methodNotSupported",
+            ResolutionError::InternalError => "internalError",
+            ResolutionError::InvalidOptions => "invalidOptions",
+            ResolutionError::Timeout => "timeout",
+        };
+        f.write_str(code)
+    }
+}
+
+/// Input options passed to a `Resolver`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolutionOptions {
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub accept: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub version_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub version_time: Option<String>,
+    /// How long this call may take, in place of the default of a `Timeout`
+    /// wrapper. Other resolvers ignore it, and it is never sent to a remote
+    /// resolver.
+    #[serde(skip)]
+    pub timeout: Option<Duration>,
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
+}
+
+/// Metadata about the resolution process itself.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolutionMetadata {
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub content_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub error: Option<ResolutionError>,
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
+}
+
+/// Metadata about the resolved `Document`, as opposed to its contents.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentMetadata {
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub created: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub updated: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub deactivated: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub next_update: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub version_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub next_version_id: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub equivalent_id: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub canonical_id: Option<String>,
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
+}
+
+/// The output of `Resolver::resolve`. On failure `document` is `None` and
+/// `did_resolution_metadata.error` carries the reason.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolutionResult {
+    #[serde(rename = "didDocument", default)]
+    pub document: Option<Document>,
+    #[serde(default)]
+    pub did_resolution_metadata: ResolutionMetadata,
+    #[serde(default)]
+    pub did_document_metadata: DocumentMetadata,
+}
+
+impl ResolutionResult {
+    pub fn new(document: Document, metadata: DocumentMetadata) -> Self {
+        ResolutionResult {
+            document: Some(document),
+            did_resolution_metadata: ResolutionMetadata::default(),
+            did_document_metadata: metadata,
+        }
+    }
+
+    pub fn from_error(error: ResolutionError) -> Self {
+        ResolutionResult {
+            document: None,
+            did_resolution_metadata: ResolutionMetadata {
+                error: Some(error),
+                ..ResolutionMetadata::default()
+            },
+            did_document_metadata: DocumentMetadata::default(),
+        }
+    }
+
+    pub fn error(&self) -> Option<ResolutionError> {
+        self.did_resolution_metadata.error
+    }
+}
+
+/// Resolves a DID into its `Document` and metadata.
+pub trait Resolver: Send + Sync {
+    fn resolve(&self, did: &Uri, options: &ResolutionOptions) -> ResolutionResult;
+
+    /// Parses `did` before resolving it, reporting `invalidDid` if it is not
+    /// a DID.
+    fn resolve_str(&self, did: &str, options: &ResolutionOptions) -> ResolutionResult {
+        match Uri::from_str(did) {
+            Ok(uri) => self.resolve(&uri, options),
+            Err(_) => ResolutionResult::from_error(ResolutionError::InvalidDid),
+        }
+    }
+}
+
+impl<R: Resolver + ?Sized> Resolver for &R {
+    fn resolve(&self, did: &Uri, options: &ResolutionOptions) -> ResolutionResult {
+        (**self).resolve(did, options)
+    }
+}
+
+impl<R: Resolver + ?Sized> Resolver for Box<R> {
+    fn resolve(&self, did: &Uri, options: &ResolutionOptions) -> ResolutionResult {
+        (**self).resolve(did, options)
+    }
+}
+
+/// A resolvable DID has a method and an id but no query or fragment.
+pub(crate) fn is_did(uri: &Uri) -> bool {
+    !uri.is_empty()
+        && !uri.is_relative()
+        && !uri.method.is_empty()
+        && !uri.id.is_empty()
+        && uri.query.is_none()
+        && uri.fragment.is_none()
+}
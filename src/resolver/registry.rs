@@ -0,0 +1,48 @@
+use crate::resolver::{is_did, ResolutionError, ResolutionOptions, ResolutionResult, Resolver};
+use crate::uri::Uri;
+use indexmap::IndexMap;
+
+/// Dispatches resolution to the `Resolver` registered for a DID's method.
+#[derive(Default)]
+pub struct MethodRegistry {
+    resolvers: IndexMap<String, Box<dyn Resolver>>,
+}
+
+impl MethodRegistry {
+    pub fn new() -> Self {
+        MethodRegistry::default()
+    }
+
+    /// Registers `resolver` for `method`, replacing any previous one.
+    pub fn register<R>(&mut self, method: &str, resolver: R)
+    where
+        R: Resolver + 'static,
+    {
+        self.resolvers.insert(method.to_owned(), Box::new(resolver));
+    }
+
+    pub fn unregister(&mut self, method: &str) -> bool {
+        self.resolvers.shift_remove(method).is_some()
+    }
+
+    pub fn supports(&self, method: &str) -> bool {
+        self.resolvers.contains_key(method)
+    }
+
+    pub fn methods(&self) -> impl Iterator<Item = &str> {
+        self.resolvers.keys().map(String::as_str)
+    }
+}
+
+impl Resolver for MethodRegistry {
+    fn resolve(&self, did: &Uri, options: &ResolutionOptions) -> ResolutionResult {
+        if !is_did(did) {
+            return ResolutionResult::from_error(ResolutionError::InvalidDid);
+        }
+
+        match self.resolvers.get(&did.method) {
+            Some(resolver) => resolver.resolve(did, options),
+            None => ResolutionResult::from_error(ResolutionError::MethodNotSupported),
+        }
+    }
+}
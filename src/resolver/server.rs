@@ -0,0 +1,169 @@
+use crate::error::{DidError, DidErrorExt, DidErrorKind};
+use crate::resolver::{
+    ResolutionError, ResolutionOptions, ResolutionResult, Resolver, DID_JSON, DID_LD_JSON,
+    DID_RESOLUTION,
+};
+use percent_encoding::percent_decode_str;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+use tiny_http::{Header, Method, Request, Response};
+
+const IDENTIFIERS: &str = "/1.0/identifiers/";
+
+/// Serves the DID Resolution HTTP(S) binding on top of a `Resolver`.
+///
+/// `GET /1.0/identifiers/{did}` returns the plain `Document` for
+/// `application/did+json` (the default) and `application/did+ld+json`, or the
+/// complete resolution result when that media type is accepted. Failures
+/// are always reported as a resolution result with the matching status.
+pub struct Server<R> {
+    resolver: R,
+    http: tiny_http::Server,
+}
+
+impl<R: Resolver> Server<R> {
+    pub fn bind<A: ToSocketAddrs>(addr: A, resolver: R) -> Result<Self, DidError> {
+        let http = tiny_http::Server::http(addr)
+            .map_err(|e| DidError::from_msg(DidErrorKind::Io, e.to_string()))?;
+        Ok(Server { resolver, http })
+    }
+
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.http.server_addr().to_ip()
+    }
+
+    pub fn resolver(&self) -> &R {
+        &self.resolver
+    }
+
+    /// Handles requests until `shutdown` is called.
+    pub fn run(&self) {
+        for request in self.http.incoming_requests() {
+            if let Err(e) = self.handle(request) {
+                log::warn!("failed to send response: {}", e);
+            }
+        }
+    }
+
+    /// Makes `run` return once the request in progress has been handled.
+    pub fn shutdown(&self) {
+        self.http.unblock();
+    }
+
+    pub fn handle(&self, request: Request) -> Result<(), DidError> {
+        let accept = request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("Accept"))
+            .map(|h| h.value.as_str().to_owned());
+
+        let (status, content_type, body) = if *request.method() != Method::Get {
+            (405, "text/plain", "method not allowed".to_owned())
+        } else {
+            self.respond(request.url(), accept.as_deref())
+        };
+
+        let header = Header::from_bytes("Content-Type", content_type).unwrap();
+        let response = Response::from_string(body)
+            .with_status_code(status)
+            .with_header(header);
+        request
+            .respond(response)
+            .map_err(|e: io::Error| e.to_did(DidErrorKind::Io, "responding to request"))
+    }
+
+    /// Produces the status, content type and body for a request line.
+    pub fn respond(&self, url: &str, accept: Option<&str>) -> (u16, &'static str, String) {
+        if !url.starts_with(IDENTIFIERS) {
+            return (404, "text/plain", "not found".to_owned());
+        }
+
+        let (path, query) = match url.find('?') {
+            Some(i) => (&url[IDENTIFIERS.len()..i], Some(&url[i + 1..])),
+            None => (&url[IDENTIFIERS.len()..], None),
+        };
+        let did = percent_decode_str(path).decode_utf8_lossy();
+
+        let mut options = ResolutionOptions::default();
+        for (k, v) in url::form_urlencoded::parse(query.unwrap_or("").as_bytes()) {
+            match k.as_ref() {
+                "versionId" => options.version_id = Some(v.into_owned()),
+                "versionTime" => options.version_time = Some(v.into_owned()),
+                _ => {}
+            }
+        }
+
+        let representation = match representation(accept) {
+            Some(r) => r,
+            None => {
+                let result =
+                    ResolutionResult::from_error(ResolutionError::RepresentationNotSupported);
+                return (406, DID_RESOLUTION, json(&result));
+            }
+        };
+        options.accept = Some(representation.to_owned());
+
+        let mut result = self.resolver.resolve_str(&did, &options);
+        if let Some(error) = result.error() {
+            return (status(error), DID_RESOLUTION, json(&result));
+        }
+
+        let status = if result.did_document_metadata.deactivated == Some(true) {
+            410
+        } else {
+            200
+        };
+        if representation == DID_RESOLUTION {
+            result.did_resolution_metadata.content_type = Some(DID_JSON.to_owned());
+            return (status, DID_RESOLUTION, json(&result));
+        }
+
+        match &result.document {
+            Some(document) => (status, representation, json(document)),
+            None => {
+                let result = ResolutionResult::from_error(ResolutionError::NotFound);
+                (404, DID_RESOLUTION, json(&result))
+            }
+        }
+    }
+}
+
+/// Picks the representation to return for an `Accept` header.
+fn representation(accept: Option<&str>) -> Option<&'static str> {
+    let accept = match accept {
+        Some(accept) => accept,
+        None => return Some(DID_JSON),
+    };
+
+    for media_type in accept.split(',') {
+        let media_type = media_type.trim();
+        if media_type.contains("did-resolution") {
+            return Some(DID_RESOLUTION);
+        }
+        match media_type.split(';').next().unwrap_or("").trim() {
+            "application/did+ld+json" | "application/ld+json" => return Some(DID_LD_JSON),
+            "application/did+json" | "application/json" | "application/*" | "*/*" => {
+                return Some(DID_JSON)
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn status(error: ResolutionError) -> u16 {
+    match error {
+        ResolutionError::InvalidDid
+        | ResolutionError::InvalidDidUrl
+        | ResolutionError::InvalidOptions => 400,
+        ResolutionError::NotFound => 404,
+        ResolutionError::RepresentationNotSupported => 406,
+        ResolutionError::MethodNotSupported => 501,
+        ResolutionError::InternalError => 500,
+        ResolutionError::Timeout => 504,
+    }
+}
+
+fn json<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap()
+}
@@ -37,6 +37,41 @@ impl Uri {
     pub fn is_empty(&self) -> bool {
         self.empty
     }
+
+    /// A relative DID URL only carries a fragment (e.g. `#keys-1`) and is
+    /// interpreted against the DID of the document it appears in.
+    pub fn is_relative(&self) -> bool {
+        !self.empty && self.method.is_empty() && self.id.is_empty()
+    }
+
+    /// Returns this DID URL with the method and id filled in from `base` if
+    /// it is relative, otherwise returns a copy of itself.
+    pub fn to_absolute(&self, base: &Uri) -> Uri {
+        if !self.is_relative() {
+            return self.clone();
+        }
+
+        Uri {
+            empty: base.empty,
+            id: base.id.clone(),
+            method: base.method.clone(),
+            params: base.params.clone(),
+            query: self.query.clone(),
+            fragment: self.fragment.clone(),
+        }
+    }
+
+    /// Returns the DID this URL is rooted at, i.e. without query or fragment.
+    pub fn did(&self) -> Uri {
+        Uri {
+            empty: self.empty,
+            id: self.id.clone(),
+            method: self.method.clone(),
+            params: self.params.clone(),
+            query: None,
+            fragment: None,
+        }
+    }
 }
 
 impl PartialEq<&str> for Uri {
@@ -129,6 +164,10 @@ impl std::fmt::Display for Uri {
             fragment = format!("#{}", f);
         }
 
+        if self.is_relative() {
+            return write!(f, "{}{}", query, fragment);
+        }
+
         write!(
             f,
             "did:{}:{}{}{}{}",
@@ -138,7 +177,7 @@ impl std::fmt::Display for Uri {
 }
 
 fn parse_did_string(i: &[u8]) -> IResult<&[u8], Uri> {
-    if i.len() == 0 {
+    if i.is_empty() {
         return Ok((i, Uri {
             empty: true,
             id: String::default(),
@@ -149,6 +188,18 @@ fn parse_did_string(i: &[u8]) -> IResult<&[u8], Uri> {
         }));
     }
 
+    if i[0] == b'#' {
+        let (i, fragment) = did_fragment(i)?;
+        return Ok((i, Uri {
+            empty: false,
+            id: String::default(),
+            method: String::default(),
+            params: None,
+            query: None,
+            fragment: Some(fragment.to_string()),
+        }));
+    }
+
     let (i, _) = tag("did:")(i)?;
     let (i, method) = map(take_while(is_did_method_char), std::str::from_utf8)(i)?;
     let (i, _) = char(':')(i)?;
@@ -0,0 +1,210 @@
+//! Checks a `Document` against the rules of the DID data model that are
+//! not enforced while parsing it.
+
+use crate::doc::Document;
+use crate::fields::{Endpoint, Jwk, KnownContext, PublicKey, ServiceEndpoint};
+use crate::uri::Uri;
+use std::collections::HashSet;
+use std::fmt;
+use url::Url;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DiagnosticKind {
+    /// The key data uses an encoding its verification method type does not
+    /// permit.
+    EncodingNotPermitted,
+    /// A `publicKeyJwk` has a `kty` that cannot describe a public key, or
+    /// lacks a member its `kty` requires.
+    InvalidJwk,
+    /// A `serviceEndpoint` is not an absolute URI, or lists none.
+    InvalidServiceEndpoint,
+    /// A service has no id, or one that is neither a DID URL nor a relative
+    /// fragment.
+    InvalidServiceId,
+    /// A service has the same id as another service or a key.
+    DuplicateId,
+    /// A service has an empty `type`.
+    MissingServiceType,
+    /// `@context` does not start with a DID context, or lacks one that
+    /// defines the type of a key.
+    MissingContext,
+}
+
+/// A problem found by `Document::validate`, located by the path of the
+/// offending member, e.g. `publicKey[1]`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+pub(crate) fn validate(doc: &Document) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    check_keys(&mut diagnostics, "publicKey", doc.public_key());
+    check_keys(&mut diagnostics, "authentication", doc.authentication());
+    check_keys(&mut diagnostics, "keyAgreement", doc.key_agreement());
+    check_services(&mut diagnostics, doc);
+    diagnostics
+}
+
+pub(crate) fn validate_contexts(doc: &Document) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let first = doc.context().first().and_then(|entry| entry.as_uri());
+    if !first
+        .and_then(KnownContext::from_url)
+        .map(KnownContext::is_did)
+        .unwrap_or(false)
+    {
+        diagnostics.push(Diagnostic {
+            kind: DiagnosticKind::MissingContext,
+            path: "@context".to_owned(),
+            message: format!(
+                "the first context has to be {}",
+                KnownContext::DidCore.url()
+            ),
+        });
+    }
+
+    let context = doc.contexts();
+    for (member, keys) in &[
+        ("publicKey", doc.public_key()),
+        ("authentication", doc.authentication()),
+        ("keyAgreement", doc.key_agreement()),
+    ] {
+        for (i, key) in keys.iter().enumerate() {
+            let required = match key.key_type().context() {
+                Some(required) if !key.reference() => required,
+                _ => continue,
+            };
+            if !context.defines(key.key_type().as_str()) {
+                diagnostics.push(Diagnostic {
+                    kind: DiagnosticKind::MissingContext,
+                    path: format!("{}[{}]", member, i),
+                    message: format!(
+                        "{} keys need the context {}",
+                        key.key_type(),
+                        required.url()
+                    ),
+                });
+            }
+        }
+    }
+    diagnostics
+}
+
+fn check_keys(diagnostics: &mut Vec<Diagnostic>, member: &str, keys: &[PublicKey]) {
+    for (i, key) in keys.iter().enumerate() {
+        if key.reference() {
+            continue;
+        }
+        if !key.key_type().permits(key.encoding()) {
+            diagnostics.push(Diagnostic {
+                kind: DiagnosticKind::EncodingNotPermitted,
+                path: format!("{}[{}]", member, i),
+                message: format!(
+                    "{} keys cannot use {}",
+                    key.key_type(),
+                    key.encoding().field_name()
+                ),
+            });
+        }
+        if let Some(defect) = key.jwk().and_then(Jwk::defect) {
+            diagnostics.push(Diagnostic {
+                kind: DiagnosticKind::InvalidJwk,
+                path: format!("{}[{}].publicKeyJwk", member, i),
+                message: defect,
+            });
+        }
+    }
+}
+
+fn check_services(diagnostics: &mut Vec<Diagnostic>, doc: &Document) {
+    let base = doc.subject().as_uri();
+    let mut ids: HashSet<String> = doc
+        .public_key()
+        .iter()
+        .chain(doc.authentication())
+        .chain(doc.key_agreement())
+        .filter(|k| !k.reference())
+        .map(|k| k.subject().as_uri().to_absolute(base).to_string())
+        .collect();
+
+    for (i, service) in doc.service().iter().enumerate() {
+        let mut report = |kind, member: &str, message: String| {
+            diagnostics.push(Diagnostic {
+                kind,
+                path: format!("service[{}].{}", i, member),
+                message,
+            })
+        };
+
+        let id = service.subject().as_uri();
+        if id.is_empty() && !service.id().is_empty() {
+            report(
+                DiagnosticKind::InvalidServiceId,
+                "id",
+                format!("{:?} is not a URI", service.id()),
+            );
+        } else if let Some(message) = invalid_id(id) {
+            report(DiagnosticKind::InvalidServiceId, "id", message);
+        } else if !ids.insert(id.to_absolute(base).to_string()) {
+            report(
+                DiagnosticKind::DuplicateId,
+                "id",
+                format!("{} is used more than once", id),
+            );
+        }
+
+        if service.kind().trim().is_empty() {
+            report(
+                DiagnosticKind::MissingServiceType,
+                "type",
+                "services need a type".to_owned(),
+            );
+        }
+
+        for message in invalid_endpoint(service) {
+            report(
+                DiagnosticKind::InvalidServiceEndpoint,
+                "serviceEndpoint",
+                message,
+            );
+        }
+    }
+}
+
+fn invalid_id(id: &Uri) -> Option<String> {
+    if id.is_empty() {
+        return Some("services need an id".to_owned());
+    }
+    if id.is_relative() {
+        if id.fragment.is_none() {
+            return Some(format!("{} is not a relative fragment", id));
+        }
+    } else if id.method.is_empty() || id.id.is_empty() {
+        return Some(format!("{} is not a DID URL", id));
+    }
+    None
+}
+
+fn invalid_endpoint(service: &ServiceEndpoint) -> Vec<String> {
+    let endpoint = service.endpoint();
+    if let Endpoint::Set(set) = endpoint {
+        if set.is_empty() {
+            return vec!["the set of endpoints is empty".to_owned()];
+        }
+    }
+    endpoint
+        .uris()
+        .into_iter()
+        .filter(|uri| Url::parse(uri).is_err())
+        .map(|uri| format!("{:?} is not an absolute URI", uri))
+        .collect()
+}
@@ -0,0 +1,177 @@
+#![cfg(feature = "async")]
+
+extern crate did_doc as did;
+
+mod common;
+
+use async_trait::async_trait;
+use did::{
+    resolver::{
+        resolve_many, AsyncDereferencer, AsyncResolver, Blocking, Content, DereferencingOptions,
+        InMemoryResolver, ResolutionError, ResolutionOptions, Timeout,
+    },
+    ResolutionResult, Uri,
+};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+fn example_resolver() -> Blocking<InMemoryResolver> {
+    Blocking::new(common::example_resolver())
+}
+
+/// Sleeps before resolving and records how many calls overlapped.
+struct SlowResolver {
+    inner: Blocking<InMemoryResolver>,
+    delay: Duration,
+    in_flight: AtomicUsize,
+    max_in_flight: AtomicUsize,
+}
+
+impl SlowResolver {
+    fn new(delay: Duration) -> Self {
+        SlowResolver {
+            inner: example_resolver(),
+            delay,
+            in_flight: AtomicUsize::new(0),
+            max_in_flight: AtomicUsize::new(0),
+        }
+    }
+}
+
+#[async_trait]
+impl AsyncResolver for SlowResolver {
+    async fn resolve(&self, did: &Uri, options: &ResolutionOptions) -> ResolutionResult {
+        let n = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_in_flight.fetch_max(n, Ordering::SeqCst);
+        tokio::time::sleep(self.delay).await;
+        let result = self.inner.resolve(did, options).await;
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        result
+    }
+}
+
+#[tokio::test]
+async fn did_async_resolve_0() {
+    let resolver = example_resolver();
+    let options = ResolutionOptions::default();
+
+    let res = resolver.resolve_str("did:example:1", &options).await;
+    assert_eq!(res.document.unwrap().subject(), "did:example:1");
+
+    let res = resolver.resolve_str("did:example:4", &options).await;
+    assert_eq!(res.error(), Some(ResolutionError::NotFound));
+
+    let res = resolver.resolve_str("https://example.org", &options).await;
+    assert_eq!(res.error(), Some(ResolutionError::InvalidDid));
+}
+
+#[tokio::test]
+async fn did_async_resolve_1() {
+    let resolver = SlowResolver::new(Duration::from_millis(20));
+    let dids = [
+        "did:example:1",
+        "did:example:4",
+        "did:example:2",
+        "did:example:3",
+    ]
+    .iter()
+    .map(|s| Uri::from_str(s).unwrap())
+    .collect::<Vec<_>>();
+
+    let results = resolve_many(&resolver, &dids, &ResolutionOptions::default(), 2).await;
+    assert_eq!(results.len(), 4);
+    assert_eq!(
+        results[0].document.as_ref().unwrap().subject(),
+        "did:example:1"
+    );
+    assert_eq!(results[1].error(), Some(ResolutionError::NotFound));
+    assert_eq!(
+        results[2].document.as_ref().unwrap().subject(),
+        "did:example:2"
+    );
+    assert_eq!(
+        results[3].document.as_ref().unwrap().subject(),
+        "did:example:3"
+    );
+    assert_eq!(resolver.max_in_flight.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn did_async_resolve_2() {
+    let options = ResolutionOptions::default();
+
+    let resolver = Timeout::new(
+        SlowResolver::new(Duration::from_millis(200)),
+        Duration::from_millis(20),
+    );
+    let res = resolver.resolve_str("did:example:1", &options).await;
+    assert_eq!(res.error(), Some(ResolutionError::Timeout));
+    assert!(res.document.is_none());
+
+    let resolver = Timeout::new(
+        SlowResolver::new(Duration::from_millis(1)),
+        Duration::from_secs(5),
+    );
+    let res = resolver.resolve_str("did:example:1", &options).await;
+    assert!(res.error().is_none());
+
+    // a timeout in the options takes the place of the default
+    let resolver = Timeout::new(
+        SlowResolver::new(Duration::from_millis(200)),
+        Duration::from_secs(5),
+    );
+    let options = ResolutionOptions {
+        timeout: Some(Duration::from_millis(20)),
+        ..ResolutionOptions::default()
+    };
+    let res = resolver.resolve_str("did:example:1", &options).await;
+    assert_eq!(res.error(), Some(ResolutionError::Timeout));
+
+    let resolver = Timeout::new(
+        SlowResolver::new(Duration::from_millis(50)),
+        Duration::from_millis(20),
+    );
+    let options = ResolutionOptions {
+        timeout: Some(Duration::from_secs(5)),
+        ..ResolutionOptions::default()
+    };
+    let res = resolver.resolve_str("did:example:1", &options).await;
+    assert!(res.error().is_none());
+}
+
+#[tokio::test]
+async fn did_async_dereference_0() {
+    let dereferencer = AsyncDereferencer::new(example_resolver());
+    let options = DereferencingOptions::default();
+
+    let res = dereferencer
+        .dereference_str("did:example:2", &options)
+        .await;
+    match res.content {
+        Some(Content::Document(doc)) => assert_eq!(doc.subject(), "did:example:2"),
+        c => panic!("unexpected content {:?}", c),
+    }
+
+    let res = dereferencer
+        .dereference_str("did:example:2#keys-1", &options)
+        .await;
+    assert_eq!(res.error(), Some(ResolutionError::NotFound));
+
+    let res = dereferencer.dereference_str("#keys-1", &options).await;
+    assert_eq!(res.error(), Some(ResolutionError::InvalidDidUrl));
+
+    // the timeout reaches the resolver
+    let dereferencer = AsyncDereferencer::new(Timeout::new(
+        SlowResolver::new(Duration::from_millis(200)),
+        Duration::from_secs(5),
+    ));
+    let options = DereferencingOptions {
+        timeout: Some(Duration::from_millis(20)),
+        ..DereferencingOptions::default()
+    };
+    let res = dereferencer
+        .dereference_str("did:example:2", &options)
+        .await;
+    assert_eq!(res.error(), Some(ResolutionError::Timeout));
+}
@@ -0,0 +1,34 @@
+//! Fixtures shared by the integration tests. Every test crate that declares
+//! `mod common` gets all of them, whether it uses them or not.
+#![allow(dead_code)]
+
+use did::{fields::PublicKey, resolver::InMemoryResolver, Document};
+use serde::Serialize;
+use serde_json::json;
+
+/// An embedded key of `key_type` with `data` in the member `field`, e.g.
+/// `publicKeyHex`.
+pub fn key<V: Serialize>(key_type: &str, field: &str, data: V) -> PublicKey {
+    let json = json!({
+        "id": "did:example:123456789abcdefghi#keys-1",
+        "type": key_type,
+        "controller": "did:example:123456789abcdefghi",
+        field: data,
+    });
+    serde_json::from_value(json).unwrap()
+}
+
+/// A resolver with bare documents for `did:example:123456789abcdefghi` and
+/// `did:example:1` to `did:example:3`.
+pub fn example_resolver() -> InMemoryResolver {
+    let mut resolver = InMemoryResolver::new();
+    for id in &[
+        "did:example:123456789abcdefghi",
+        "did:example:1",
+        "did:example:2",
+        "did:example:3",
+    ] {
+        resolver.insert(Document::new("https://w3id.org/did/v1", id));
+    }
+    resolver
+}
@@ -1,8 +1,8 @@
 extern crate did_doc as did;
 
 use did::{
-    fields::{PublicKeyEncoding, PublicKeyType},
-    Document,
+    fields::{Endpoint, KnownContext, PublicKeyEncoding, PublicKeyType, ServiceEndpoint},
+    DiagnosticKind, DidErrorKind, Document, Resource, Uri,
 };
 
 use std::str::FromStr;
@@ -27,7 +27,7 @@ fn did_parse_document_0() {
 
     let flat = r#"{"@context":"https://w3id.org/did/v1","id":"did:example:123456789abcdefghi"}"#;
 
-    let doc = Document::from_str(&jstr).unwrap();
+    let doc = Document::from_str(jstr).unwrap();
     assert_eq!(doc.context().len(), 1);
     assert_eq!(doc.subject(), "did:example:123456789abcdefghi");
     assert_eq!(doc.public_key().len(), 0);
@@ -49,7 +49,7 @@ fn did_parse_document_1() {
 
     let flat = r#"{"@context":["https://w3id.org/did/v1","https://w3id.org/security/v1"],"id":"did:example:123456789abcdefghi"}"#;
 
-    let doc = Document::from_str(&jstr).unwrap();
+    let doc = Document::from_str(jstr).unwrap();
     assert_eq!(doc.context().len(), 2);
     assert_eq!(doc.subject(), "did:example:123456789abcdefghi");
     assert_eq!(doc.public_key().len(), 0);
@@ -87,7 +87,7 @@ fn did_parse_document_2() {
 
     let flat = r#"{"@context":["https://w3id.org/did/v1","https://w3id.org/security/v1"],"id":"did:example:123456789abcdefghi","publicKey":[{"id":"did:example:123456789abcdefghi#keys-1","type":"RsaVerificationKey2018","controller":"did:example:123456789abcdefghi","publicKeyPem":"-----BEGIN PUBLIC KEY...END PUBLIC KEY-----"},{"id":"did:example:123456789abcdefghi#keys-2","type":"Ed25519VerificationKey2018","controller":"did:example:pqrstuvwxyz0987654321","publicKeyBase58":"H3C2AVvLMv6gmMNam3uVAjZpfkcJCwDwnZn6z3wXmqPV"},{"id":"did:example:123456789abcdefghi#keys-3","type":"EcdsaSecp256k1VerificationKey2019","controller":"did:example:123456789abcdefghi","publicKeyHex":"02b97c30de767f084ce3080168ee293053ba33b235d7116a3263d29f1450936b71"}]}"#;
 
-    let doc = Document::from_str(&jstr).unwrap();
+    let doc = Document::from_str(jstr).unwrap();
     assert_eq!(doc.context().len(), 2);
     assert_eq!(doc.subject(), "did:example:123456789abcdefghi");
     assert_eq!(doc.public_key().len(), 3);
@@ -98,7 +98,7 @@ fn did_parse_document_2() {
     assert_eq!(k1.subject(), "did:example:123456789abcdefghi#keys-1");
     assert_eq!(k1.kind(), PublicKeyType::RsaVerificationKey2018);
     assert_eq!(k1.controller(), "did:example:123456789abcdefghi");
-    assert_eq!(k1.reference(), false);
+    assert!(!k1.reference());
     assert_eq!(k1.encoding(), PublicKeyEncoding::Pem);
     assert_eq!(
         k1.data().as_str(),
@@ -109,7 +109,7 @@ fn did_parse_document_2() {
     assert_eq!(k2.subject(), "did:example:123456789abcdefghi#keys-2");
     assert_eq!(k2.kind(), PublicKeyType::Ed25519VerificationKey2018);
     assert_eq!(k2.controller(), "did:example:pqrstuvwxyz0987654321");
-    assert_eq!(k2.reference(), false);
+    assert!(!k2.reference());
     assert_eq!(k2.encoding(), PublicKeyEncoding::Base58);
     assert_eq!(
         k2.data().as_str(),
@@ -120,7 +120,7 @@ fn did_parse_document_2() {
     assert_eq!(k3.subject(), "did:example:123456789abcdefghi#keys-3");
     assert_eq!(k3.kind(), PublicKeyType::EcdsaSecp256k1VerificationKey2019);
     assert_eq!(k3.controller(), "did:example:123456789abcdefghi");
-    assert_eq!(k3.reference(), false);
+    assert!(!k3.reference());
     assert_eq!(k3.encoding(), PublicKeyEncoding::Hex);
     assert_eq!(
         k3.data().as_str(),
@@ -152,7 +152,7 @@ fn did_parse_document_3() {
 
     let flat = r#"{"@context":["https://w3id.org/did/v1","https://w3id.org/security/v1"],"id":"did:example:123456789abcdefghi","authentication":["did:example:123456789abcdefghi#keys-1","did:example:123456789abcdefghi#biometric-1",{"id":"did:example:123456789abcdefghi#keys-2","type":"Ed25519VerificationKey2018","controller":"did:example:123456789abcdefghi","publicKeyBase58":"H3C2AVvLMv6gmMNam3uVAjZpfkcJCwDwnZn6z3wXmqPV"}]}"#;
 
-    let doc = Document::from_str(&jstr).unwrap();
+    let doc = Document::from_str(jstr).unwrap();
     assert_eq!(doc.context().len(), 2);
     assert_eq!(doc.subject(), "did:example:123456789abcdefghi");
     assert_eq!(doc.public_key().len(), 0);
@@ -181,7 +181,7 @@ fn did_parse_document_3() {
     assert_eq!(k3.controller(), "did:example:123456789abcdefghi");
     assert_eq!(k3.encoding(), PublicKeyEncoding::Base58);
     assert_eq!(k3.data(), "H3C2AVvLMv6gmMNam3uVAjZpfkcJCwDwnZn6z3wXmqPV");
-    assert_eq!(k3.reference(), false);
+    assert!(!k3.reference());
 
     let s = doc.to_string();
     assert_eq!(s.as_str(), flat);
@@ -279,7 +279,7 @@ fn did_parse_document_5() {
 
     let flat = r#"{"@context":"https://example.org/example-method/v1","id":"did:example:123456789abcdefghi","service":[{"@context":"did:example:contexts:987654321","id":"did:example:123456789abcdefghi#photos","type":"PhotoStreamService","serviceEndpoint":"https://example.org/photos/379283"}]}"#;
 
-    let doc = Document::from_str(&jstr).unwrap();
+    let doc = Document::from_str(jstr).unwrap();
     assert_eq!(doc.context().len(), 1);
     assert_eq!(doc.subject(), "did:example:123456789abcdefghi");
     assert_eq!(doc.public_key().len(), 0);
@@ -287,7 +287,7 @@ fn did_parse_document_5() {
     assert_eq!(doc.service().len(), 1);
 
     let s1 = &doc.service()[0];
-    assert_eq!(s1.context().is_empty(), false);
+    assert!(!s1.context().is_empty());
     assert_eq!(s1.context().as_vec()[0], "did:example:contexts:987654321");
     assert_eq!(s1.subject(), "did:example:123456789abcdefghi#photos");
     assert_eq!(s1.kind(), "PhotoStreamService");
@@ -331,7 +331,7 @@ fn did_parse_document_6() {
     assert_eq!(a1.controller(), "did:example:123456789abcdefghi");
     assert_eq!(a1.encoding(), PublicKeyEncoding::Pem);
     assert_eq!(a1.data(), "-----BEGIN PUBLIC KEY...END PUBLIC KEY-----\r\n");
-    assert_eq!(a1.reference(), false);
+    assert!(!a1.reference());
 
     let s1 = &doc.service()[0];
     assert!(s1.context().is_empty());
@@ -341,3 +341,304 @@ fn did_parse_document_6() {
     let s = doc.to_string();
     assert_eq!(s.as_str(), flat);
 }
+
+#[test]
+fn did_dereference_document_0() {
+    let jstr = r##"
+    {
+        "@context": "https://w3id.org/did/v1",
+        "id": "did:example:123456789abcdefghi",
+        "publicKey": [{
+            "id": "did:example:123456789abcdefghi#keys-1",
+            "type": "RsaVerificationKey2018",
+            "controller": "did:example:123456789abcdefghi",
+            "publicKeyPem": "-----BEGIN PUBLIC KEY...END PUBLIC KEY-----"
+        }, {
+            "id": "#keys-2",
+            "type": "Ed25519VerificationKey2018",
+            "controller": "did:example:123456789abcdefghi",
+            "publicKeyBase58": "H3C2AVvLMv6gmMNam3uVAjZpfkcJCwDwnZn6z3wXmqPV"
+        }],
+        "authentication": [
+            "did:example:123456789abcdefghi#keys-1",
+            "#keys-2",
+            {
+                "id": "did:example:123456789abcdefghi#keys-3",
+                "type": "EcdsaSecp256k1VerificationKey2019",
+                "controller": "did:example:123456789abcdefghi",
+                "publicKeyHex": "02b97c30de767f084ce3080168ee293053ba33b235d7116a3263d29f1450936b71"
+            }
+        ],
+        "service": [{
+            "id": "did:example:123456789abcdefghi#messages",
+            "type": "MessagingService",
+            "serviceEndpoint": "https://example.com/messages/8377464"
+        }]
+    }
+    "##;
+
+    let doc = Document::from_str(jstr).unwrap();
+
+    let uri = Uri::from_str("#keys-1").unwrap();
+    match doc.dereference(&uri) {
+        Some(Resource::PublicKey(pk)) => {
+            assert_eq!(pk.subject(), "did:example:123456789abcdefghi#keys-1")
+        }
+        r => panic!("unexpected resource {:?}", r),
+    }
+
+    let uri = Uri::from_str("did:example:123456789abcdefghi#keys-2").unwrap();
+    match doc.dereference(&uri) {
+        Some(Resource::PublicKey(pk)) => assert_eq!(pk.subject(), "#keys-2"),
+        r => panic!("unexpected resource {:?}", r),
+    }
+
+    let uri = Uri::from_str("#keys-3").unwrap();
+    match doc.dereference(&uri) {
+        Some(Resource::PublicKey(pk)) => assert_eq!(pk.encoding(), PublicKeyEncoding::Hex),
+        r => panic!("unexpected resource {:?}", r),
+    }
+
+    let uri = Uri::from_str("did:example:123456789abcdefghi#messages").unwrap();
+    match doc.dereference(&uri) {
+        Some(Resource::Service(s)) => assert_eq!(s.kind(), "MessagingService"),
+        r => panic!("unexpected resource {:?}", r),
+    }
+
+    assert!(doc
+        .dereference(&Uri::from_str("#keys-4").unwrap())
+        .is_none());
+    assert!(doc
+        .dereference(&Uri::from_str("did:example:pqrstuvwxyz0987654321#keys-1").unwrap())
+        .is_none());
+
+    let keys = doc.authentication_keys().unwrap();
+    assert_eq!(keys.len(), 3);
+    assert_eq!(keys[0].kind(), PublicKeyType::RsaVerificationKey2018);
+    assert_eq!(keys[1].kind(), PublicKeyType::Ed25519VerificationKey2018);
+    assert_eq!(
+        keys[2].kind(),
+        PublicKeyType::EcdsaSecp256k1VerificationKey2019
+    );
+    assert!(keys.iter().all(|k| !k.reference()));
+}
+
+#[test]
+fn did_dereference_document_1() {
+    let jstr = r#"
+    {
+        "@context": "https://w3id.org/did/v1",
+        "id": "did:example:123456789abcdefghi",
+        "authentication": [
+            "did:example:123456789abcdefghi#biometric-1"
+        ]
+    }
+    "#;
+
+    let doc = Document::from_str(jstr).unwrap();
+    let err = doc.authentication_keys().unwrap_err();
+    assert_eq!(err.kind(), DidErrorKind::DanglingReference);
+}
+
+#[test]
+fn did_key_agreement_0() {
+    let jstr = r##"
+    {
+        "@context": "https://w3id.org/did/v1",
+        "id": "did:example:123456789abcdefghi",
+        "publicKey": [{
+            "id": "#keys-1",
+            "type": "Ed25519VerificationKey2018",
+            "controller": "did:example:123456789abcdefghi",
+            "publicKeyBase58": "FAe4sisG95oZ42w7buUn5qEE4TAnfTTFPiguZUHmhiF"
+        }],
+        "authentication": [
+            "#keys-1"
+        ]
+    }
+    "##;
+
+    let flat = r##"{"@context":"https://w3id.org/did/v1","id":"did:example:123456789abcdefghi","publicKey":[{"id":"#keys-1","type":"Ed25519VerificationKey2018","controller":"did:example:123456789abcdefghi","publicKeyBase58":"FAe4sisG95oZ42w7buUn5qEE4TAnfTTFPiguZUHmhiF"}],"authentication":["#keys-1"],"keyAgreement":[{"id":"#z6LSgTMiVvjkfQd8CF1kWasYZKBqtAYf6h8TC3yDfjPgDbWQ","type":"X25519KeyAgreementKey2019","controller":"did:example:123456789abcdefghi","publicKeyBase58":"5nBYycvtZwuP6rdyywMbEiyN321YQ5xJK5FYBGk9WDje"}]}"##;
+
+    let mut doc = Document::from_str(jstr).unwrap();
+    assert!(doc.key_agreement().is_empty());
+    doc.derive_key_agreement().unwrap();
+    doc.derive_key_agreement().unwrap();
+    assert_eq!(doc.key_agreement().len(), 1);
+    assert_eq!(doc.to_string(), flat);
+
+    let uri = Uri::from_str(
+        "did:example:123456789abcdefghi#z6LSgTMiVvjkfQd8CF1kWasYZKBqtAYf6h8TC3yDfjPgDbWQ",
+    )
+    .unwrap();
+    match doc.dereference(&uri) {
+        Some(Resource::PublicKey(pk)) => {
+            assert_eq!(pk.kind(), PublicKeyType::X25519KeyAgreementKey2019)
+        }
+        _ => panic!("key agreement key not found"),
+    }
+
+    let doc = Document::from_str(flat).unwrap();
+    assert_eq!(doc.key_agreement()[0].encoding(), PublicKeyEncoding::Base58);
+    assert!(doc.validate().is_empty());
+}
+
+#[test]
+fn did_service_endpoint_0() {
+    let jstr = r##"
+    {
+        "@context": "https://www.w3.org/ns/did/v1",
+        "id": "did:example:123456789abcdefghi",
+        "service": [{
+            "id": "#didcomm-1",
+            "type": "DIDCommMessaging",
+            "serviceEndpoint": {
+                "uri": "https://example.com/path",
+                "accept": ["didcomm/v2", "didcomm/aip2;env=rfc587"],
+                "routingKeys": ["did:example:somemediator#somekey"]
+            }
+        }, {
+            "id": "#hub",
+            "type": "IdentityHub",
+            "serviceEndpoint": [
+                "https://hub.example.com/",
+                {"uri": "https://backup.example.com/", "priority": 1}
+            ]
+        }, {
+            "id": "#linked-domain",
+            "type": "LinkedDomains",
+            "serviceEndpoint": "https://bar.example.com"
+        }]
+    }
+    "##;
+
+    let flat = r##"{"@context":"https://www.w3.org/ns/did/v1","id":"did:example:123456789abcdefghi","service":[{"id":"#didcomm-1","type":"DIDCommMessaging","serviceEndpoint":{"uri":"https://example.com/path","accept":["didcomm/v2","didcomm/aip2;env=rfc587"],"routingKeys":["did:example:somemediator#somekey"]}},{"id":"#hub","type":"IdentityHub","serviceEndpoint":["https://hub.example.com/",{"uri":"https://backup.example.com/","priority":1}]},{"id":"#linked-domain","type":"LinkedDomains","serviceEndpoint":"https://bar.example.com"}]}"##;
+
+    let doc = Document::from_str(jstr).unwrap();
+    let service = doc.service();
+
+    let map = service[0].endpoint().as_map().unwrap();
+    assert_eq!(map["accept"][0], "didcomm/v2");
+    assert_eq!(service[0].endpoint().as_uri(), None);
+    assert_eq!(
+        service[0].endpoint().uris(),
+        vec!["https://example.com/path"]
+    );
+
+    let set = service[1].endpoint().as_set().unwrap();
+    assert_eq!(set.len(), 2);
+    assert_eq!(set[0], "https://hub.example.com/");
+    assert!(set[1].as_map().is_some());
+    assert_eq!(
+        service[1].endpoint().uris(),
+        vec!["https://hub.example.com/", "https://backup.example.com/"]
+    );
+
+    assert_eq!(
+        service[2].endpoint(),
+        &Endpoint::from("https://bar.example.com")
+    );
+    assert_eq!(
+        service[2].endpoint().uris(),
+        vec!["https://bar.example.com"]
+    );
+
+    assert_eq!(doc.to_string(), flat);
+
+    let endpoint = Endpoint::from("https://example.com/files");
+    let service = ServiceEndpoint::new("#files", "FileService", endpoint.clone()).unwrap();
+    assert_eq!(service.subject(), "#files");
+    let err = ServiceEndpoint::new("files", "FileService", endpoint).unwrap_err();
+    assert_eq!(err.kind(), DidErrorKind::InvalidUri);
+}
+
+#[test]
+fn did_context_0() {
+    let jstr = r##"
+    {
+        "@context": [
+            "https://www.w3.org/ns/did/v1",
+            {
+                "@base": "did:example:123",
+                "FileService": "https://example.com/vocab#FileService"
+            }
+        ],
+        "id": "did:example:123",
+        "service": [{
+            "@context": {"@vocab": "https://example.com/vocab#"},
+            "id": "#files",
+            "type": "FileService",
+            "serviceEndpoint": "https://example.com/files/"
+        }]
+    }
+    "##;
+
+    let flat = r##"{"@context":["https://www.w3.org/ns/did/v1",{"@base":"did:example:123","FileService":"https://example.com/vocab#FileService"}],"id":"did:example:123","service":[{"@context":{"@vocab":"https://example.com/vocab#"},"id":"#files","type":"FileService","serviceEndpoint":"https://example.com/files/"}]}"##;
+
+    let doc = Document::from_str(jstr).unwrap();
+    assert_eq!(doc.context().len(), 2);
+    assert_eq!(doc.context()[0], "https://www.w3.org/ns/did/v1");
+    assert_eq!(doc.context()[0].as_uri(), Some(KnownContext::DidCore.url()));
+    let terms = doc.context()[1].as_object().unwrap();
+    assert_eq!(terms["@base"], "did:example:123");
+
+    let service_context = doc.service()[0].context();
+    assert_eq!(service_context.len(), 1);
+    assert!(service_context.as_vec()[0].as_object().is_some());
+    assert!(service_context.defines("@vocab"));
+    assert_eq!(doc.to_string(), flat);
+}
+
+#[test]
+fn did_context_1() {
+    let jstr = r##"
+    {
+        "@context": "https://www.w3.org/ns/did/v1",
+        "id": "did:example:123",
+        "publicKey": [{
+            "id": "#key-1",
+            "type": "Ed25519VerificationKey2020",
+            "controller": "did:example:123",
+            "publicKeyMultibase": "z6MkehRgf7yJbgaGfYsdoAsKdBPE3dj2CYhowQdcjqSJgvVd"
+        }],
+        "authentication": ["#key-1"],
+        "keyAgreement": [{
+            "id": "#key-2",
+            "type": "X25519KeyAgreementKey2019",
+            "controller": "did:example:123",
+            "publicKeyBase58": "5nBYycvtZwuP6rdyywMbEiyN321YQ5xJK5FYBGk9WDje"
+        }]
+    }
+    "##;
+
+    let mut doc = Document::from_str(jstr).unwrap();
+    assert!(doc.validate().is_empty());
+    let diagnostics = doc.validate_contexts();
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].kind, DiagnosticKind::MissingContext);
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "publicKey[0]: Ed25519VerificationKey2020 keys need the context https://w3id.org/security/suites/ed25519-2020/v1"
+    );
+    assert_eq!(diagnostics[1].path, "keyAgreement[0]");
+
+    doc.add_context(KnownContext::Ed25519Signature2020);
+    doc.add_context(KnownContext::Ed25519Signature2020);
+    // security v2 defines the 2019 X25519 keys as well
+    doc.add_context(KnownContext::SecurityV2);
+    assert_eq!(doc.context().len(), 3);
+    assert!(doc.validate_contexts().is_empty());
+
+    let doc = Document::new("https://example.com/context", "did:example:123");
+    let diagnostics = doc.validate_contexts();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].path, "@context");
+    assert_eq!(
+        PublicKeyType::Multikey.context(),
+        Some(KnownContext::Multikey)
+    );
+    assert_eq!(
+        PublicKeyType::EcdsaSecp256r1VerificationKey2019.context(),
+        None
+    );
+}
@@ -0,0 +1,234 @@
+// most of these tests need the ed25519 feature
+#![cfg_attr(not(feature = "ed25519"), allow(dead_code, unused_imports))]
+
+extern crate did_doc as did;
+
+use did::{
+    crypto::{InMemoryKeyStore, KeyPair, KeyStore},
+    domain_linkage::{
+        verify_linked_domains, verify_origin, ConfigurationFetcher, DidConfiguration,
+        DomainLinkageCredential, LinkedDid,
+    },
+    fields::{KeyAlgorithm, LinkedDomains, PublicKeyEncoding, ServiceEndpoint},
+    DidError, DidErrorKind, Document, Uri,
+};
+use indexmap::IndexMap;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// 2020-12-04T14:08:28-06:00 and five years later
+const ISSUED: u64 = 1_607_112_508;
+const EXPIRES: u64 = 1_764_878_908;
+
+fn at(seconds: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(seconds)
+}
+
+/// Serves DID configurations from memory instead of over HTTPS.
+struct Fetcher(IndexMap<String, String>);
+
+impl ConfigurationFetcher for Fetcher {
+    fn fetch(&self, url: &str) -> Result<String, DidError> {
+        self.0
+            .get(url)
+            .cloned()
+            .ok_or_else(|| DidError::from_msg(DidErrorKind::Io, url.to_owned()))
+    }
+}
+
+#[test]
+fn did_linked_domains_0() {
+    let jstr = r##"
+    {
+        "@context": "https://www.w3.org/ns/did/v1",
+        "id": "did:example:123",
+        "service": [{
+            "id": "#domains",
+            "type": "LinkedDomains",
+            "serviceEndpoint": "https://foo.example.com"
+        }, {
+            "id": "#more-domains",
+            "type": "LinkedDomains",
+            "serviceEndpoint": {"origins": ["https://bar.example.com", "https://baz.example.com"]}
+        }, {
+            "id": "#hub",
+            "type": "IdentityHub",
+            "serviceEndpoint": "https://hub.example.com"
+        }]
+    }
+    "##;
+    let doc = Document::from_str(jstr).unwrap();
+
+    assert_eq!(
+        doc.linked_domains(),
+        vec![
+            "https://foo.example.com",
+            "https://bar.example.com",
+            "https://baz.example.com"
+        ]
+    );
+    assert!(doc.service()[2].linked_domains().is_none());
+
+    let domains = doc.service()[1].linked_domains().unwrap();
+    assert_eq!(domains.id, "#more-domains");
+    let service: ServiceEndpoint = domains.to_service();
+    assert_eq!(
+        serde_json::to_value(&service).unwrap(),
+        serde_json::to_value(&doc.service()[1]).unwrap()
+    );
+
+    let origins = vec!["https://foo.example.com".to_owned()];
+    let single = LinkedDomains::new("#domains", origins.clone()).unwrap();
+    assert_eq!(single.to_service().endpoint(), "https://foo.example.com");
+    let err = LinkedDomains::new("domains", origins).unwrap_err();
+    assert_eq!(err.kind(), DidErrorKind::InvalidUri);
+}
+
+#[test]
+fn did_domain_linkage_0() {
+    let jstr = r#"
+    {
+        "@context": "https://identity.foundation/.well-known/did-configuration/v1",
+        "linked_dids": [
+            "eyJhbGciOiJFZERTQSJ9.e30.c2ln",
+            {
+                "@context": [
+                    "https://www.w3.org/2018/credentials/v1",
+                    "https://identity.foundation/.well-known/did-configuration/v1"
+                ],
+                "issuer": "did:example:123",
+                "issuanceDate": "2020-12-04T14:08:28-06:00",
+                "expirationDate": "2025-12-04T14:08:28-06:00",
+                "type": ["VerifiableCredential", "DomainLinkageCredential"],
+                "credentialSubject": {
+                    "id": "did:example:123",
+                    "origin": "https://identity.foundation"
+                },
+                "proof": {"type": "Ed25519Signature2018"}
+            }
+        ]
+    }
+    "#;
+    let configuration = DidConfiguration::from_str(jstr).unwrap();
+    assert_eq!(configuration.linked_dids.len(), 2);
+    assert!(matches!(configuration.linked_dids[0], LinkedDid::Jwt(_)));
+    assert_eq!(
+        configuration.linked_dids[1].issuer().as_deref(),
+        Some("did:example:123")
+    );
+
+    // the claims hold, but the proof cannot be checked
+    let doc = Document::new("https://www.w3.org/ns/did/v1", "did:example:123");
+    let err = configuration
+        .verify(&doc, "https://identity.foundation/", at(ISSUED))
+        .unwrap_err();
+    assert_eq!(
+        err.kind(),
+        DidErrorKind::UnsupportedAlgorithm {
+            algorithm: "Ed25519Signature2018".to_owned(),
+        }
+    );
+
+    // the dates carry a time zone
+    let err = configuration
+        .verify(&doc, "https://identity.foundation", at(ISSUED - 1))
+        .unwrap_err();
+    assert_eq!(err.kind(), DidErrorKind::InvalidDomainLinkage);
+    let err = configuration
+        .verify(&doc, "https://identity.foundation", at(EXPIRES))
+        .unwrap_err();
+    assert_eq!(err.kind(), DidErrorKind::InvalidDomainLinkage);
+
+    assert_eq!(
+        DidConfiguration::url("https://identity.foundation/some/page").unwrap(),
+        "https://identity.foundation/.well-known/did-configuration.json"
+    );
+}
+
+#[cfg(feature = "ed25519")]
+#[test]
+fn did_domain_linkage_1() {
+    let seed: Vec<u8> = (0..32).collect();
+    let pair = KeyPair::from_secret_key(KeyAlgorithm::Ed25519, &seed).unwrap();
+    let mut doc = pair.did_key(PublicKeyEncoding::Multibase).unwrap();
+    doc.add_service(
+        LinkedDomains::new("#domains", vec!["https://example.com".to_owned()])
+            .unwrap()
+            .to_service(),
+    );
+    let key_id = doc.public_key()[0].subject().as_uri().clone();
+    let mut store = InMemoryKeyStore::new();
+    store.insert(&key_id, pair).unwrap();
+
+    let credential = DomainLinkageCredential::new(
+        doc.subject().as_uri(),
+        "https://example.com/",
+        at(ISSUED),
+        at(EXPIRES),
+    )
+    .unwrap();
+    assert_eq!(credential.issuance_date, "2020-12-04T20:08:28Z");
+    assert_eq!(credential.credential_subject.origin, "https://example.com");
+    let jwt = credential.to_jwt(&doc, &store, &key_id).unwrap();
+
+    let mut configuration = DidConfiguration::new();
+    configuration.linked_dids.push(LinkedDid::Jwt(jwt.clone()));
+    let mut served = IndexMap::new();
+    served.insert(
+        "https://example.com/.well-known/did-configuration.json".to_owned(),
+        configuration.to_string(),
+    );
+    let fetcher = Fetcher(served);
+
+    let now = at(ISSUED + 60);
+    let verified = verify_origin(&doc, "https://example.com", &fetcher, now).unwrap();
+    assert_eq!(verified, credential);
+
+    let results = verify_linked_domains(&doc, &fetcher, now);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].0, "https://example.com");
+    assert!(results[0].1.is_ok());
+
+    // nothing is served for another origin
+    let err = verify_origin(&doc, "https://other.example.com", &fetcher, now).unwrap_err();
+    assert_eq!(err.kind(), DidErrorKind::Io);
+
+    // the credential is for another origin
+    let err = configuration
+        .verify(&doc, "https://other.example.com", now)
+        .unwrap_err();
+    assert_eq!(err.kind(), DidErrorKind::InvalidDomainLinkage);
+
+    // expired
+    let err = configuration
+        .verify(&doc, "https://example.com", at(EXPIRES))
+        .unwrap_err();
+    assert_eq!(err.kind(), DidErrorKind::InvalidDomainLinkage);
+
+    // signed by someone else
+    let other = KeyPair::from_secret_key(KeyAlgorithm::Ed25519, &[7u8; 32])
+        .unwrap()
+        .did_key(PublicKeyEncoding::Multibase)
+        .unwrap();
+    let err = configuration
+        .verify(&other, "https://example.com", now)
+        .unwrap_err();
+    assert_eq!(err.kind(), DidErrorKind::InvalidDomainLinkage);
+
+    // tampered with
+    let mut parts: Vec<String> = jwt.split('.').map(str::to_owned).collect();
+    let mut signature = parts[2].clone().into_bytes();
+    signature[0] = if signature[0] == b'A' { b'B' } else { b'A' };
+    parts[2] = String::from_utf8(signature).unwrap();
+    let tampered = LinkedDid::Jwt(parts.join("."));
+    let err = tampered
+        .verify(&doc, "https://example.com", now)
+        .unwrap_err();
+    assert_eq!(err.kind(), DidErrorKind::InvalidSignature);
+
+    // the key has to be in the document
+    let err = credential
+        .to_jwt(&doc, &store, &Uri::from_str("#keys-2").unwrap())
+        .unwrap_err();
+    assert_eq!(err.kind(), DidErrorKind::DanglingReference);
+}
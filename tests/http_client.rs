@@ -0,0 +1,215 @@
+#![cfg(feature = "http-client")]
+
+extern crate did_doc as did;
+
+use did::{
+    resolver::{
+        ResolutionError, ResolutionOptions, UniversalResolver, DID_JSON, DID_LD_JSON,
+        DID_RESOLUTION,
+    },
+    Resolver,
+};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+const DOC: &str = r#"{"@context":"https://w3id.org/did/v1","id":"did:example:123456789abcdefghi"}"#;
+
+/// Serves a single canned response on a random local port and reports the
+/// request line and `Accept` header it received.
+fn mock_server(
+    status: &str,
+    content_type: &str,
+    body: &str,
+) -> (String, mpsc::Receiver<(String, String)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let mut accept = String::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line.trim().is_empty() {
+                break;
+            }
+            if line.to_lowercase().starts_with("accept:") {
+                accept = line["accept:".len()..].trim().to_owned();
+            }
+        }
+        stream.write_all(response.as_bytes()).unwrap();
+        tx.send((request_line.trim().to_owned(), accept)).unwrap();
+    });
+
+    (base, rx)
+}
+
+#[test]
+fn did_universal_resolver_0() {
+    let (base, rx) = mock_server("200 OK", DID_JSON, DOC);
+    let mut resolver = UniversalResolver::new(&base);
+    resolver.set_accept(DID_JSON);
+
+    let res = resolver.resolve_str(
+        "did:example:123456789abcdefghi",
+        &ResolutionOptions::default(),
+    );
+    assert!(res.error().is_none());
+    assert_eq!(
+        res.did_resolution_metadata.content_type,
+        Some(DID_JSON.to_string())
+    );
+    assert_eq!(
+        res.document.unwrap().subject(),
+        "did:example:123456789abcdefghi"
+    );
+
+    let (request_line, accept) = rx.recv().unwrap();
+    assert_eq!(
+        request_line,
+        "GET /1.0/identifiers/did:example:123456789abcdefghi HTTP/1.1"
+    );
+    assert_eq!(accept, DID_JSON);
+}
+
+#[test]
+fn did_universal_resolver_1() {
+    let body = format!(
+        r#"{{"didDocument":{},"didResolutionMetadata":{{"contentType":"{}"}},"didDocumentMetadata":{{"versionId":"7"}}}}"#,
+        DOC, DID_LD_JSON
+    );
+    let (base, rx) = mock_server("200 OK", DID_RESOLUTION, &body);
+    let resolver = UniversalResolver::new(&base);
+
+    let options = ResolutionOptions {
+        version_id: Some("7".to_string()),
+        ..ResolutionOptions::default()
+    };
+    let res = resolver.resolve_str("did:example:123456789abcdefghi", &options);
+    assert!(res.error().is_none());
+    assert!(res.document.is_some());
+    assert_eq!(res.did_document_metadata.version_id, Some("7".to_string()));
+    assert_eq!(
+        res.did_resolution_metadata.content_type,
+        Some(DID_LD_JSON.to_string())
+    );
+
+    let (request_line, accept) = rx.recv().unwrap();
+    assert_eq!(
+        request_line,
+        "GET /1.0/identifiers/did:example:123456789abcdefghi?versionId=7 HTTP/1.1"
+    );
+    assert_eq!(accept, DID_RESOLUTION);
+}
+
+#[test]
+fn did_universal_resolver_2() {
+    let cases = [
+        ("400 Bad Request", ResolutionError::InvalidDid),
+        ("404 Not Found", ResolutionError::NotFound),
+        (
+            "406 Not Acceptable",
+            ResolutionError::RepresentationNotSupported,
+        ),
+        ("500 Internal Server Error", ResolutionError::InternalError),
+        ("501 Not Implemented", ResolutionError::MethodNotSupported),
+    ];
+
+    for (status, error) in cases.iter() {
+        let (base, _rx) = mock_server(status, "text/plain", "nope");
+        let resolver = UniversalResolver::new(&base);
+        let res = resolver.resolve_str(
+            "did:example:123456789abcdefghi",
+            &ResolutionOptions::default(),
+        );
+        assert_eq!(res.error(), Some(*error));
+        assert!(res.document.is_none());
+    }
+}
+
+#[test]
+fn did_universal_resolver_3() {
+    // the error code in a resolution result body wins over the status code
+    let body = r#"{"didDocument":null,"didResolutionMetadata":{"error":"invalidDid"},"didDocumentMetadata":{}}"#;
+    let (base, _rx) = mock_server("500 Internal Server Error", DID_RESOLUTION, body);
+    let resolver = UniversalResolver::new(&base);
+    let res = resolver.resolve_str(
+        "did:example:123456789abcdefghi",
+        &ResolutionOptions::default(),
+    );
+    assert_eq!(res.error(), Some(ResolutionError::InvalidDid));
+
+    let (base, _rx) = mock_server("410 Gone", DID_JSON, DOC);
+    let resolver = UniversalResolver::new(&base);
+    let res = resolver.resolve_str(
+        "did:example:123456789abcdefghi",
+        &ResolutionOptions::default(),
+    );
+    assert!(res.error().is_none());
+    assert_eq!(res.did_document_metadata.deactivated, Some(true));
+
+    // nothing is listening here any more
+    let base = {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    };
+    let resolver = UniversalResolver::new(&base);
+    let res = resolver.resolve_str(
+        "did:example:123456789abcdefghi",
+        &ResolutionOptions::default(),
+    );
+    assert_eq!(res.error(), Some(ResolutionError::InternalError));
+}
+
+#[test]
+fn did_universal_resolver_4() {
+    // DID URLs are not sent to the resolver at all
+    let resolver = UniversalResolver::new("http://127.0.0.1:9");
+    for s in &[
+        "did:example:123456789abcdefghi#keys-1",
+        "did:example:123456789abcdefghi?service=agent",
+        "did:example:",
+    ] {
+        let res = resolver.resolve_str(s, &ResolutionOptions::default());
+        assert_eq!(res.error(), Some(ResolutionError::InvalidDid));
+    }
+
+    let (base, rx) = mock_server("200 OK", DID_JSON, DOC);
+    let resolver = UniversalResolver::new(&base);
+    resolver.resolve_str(
+        "did:example:123456789abcdefghi;version-id=2",
+        &ResolutionOptions::default(),
+    );
+    let (request_line, _) = rx.recv().unwrap();
+    assert_eq!(
+        request_line,
+        "GET /1.0/identifiers/did:example:123456789abcdefghi%3Bversion-id%3D2 HTTP/1.1"
+    );
+}
+
+#[test]
+fn did_universal_resolver_5() {
+    // the connection is accepted but never answered
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let resolver = UniversalResolver::with_timeout(&base, Duration::from_millis(100));
+    let res = resolver.resolve_str(
+        "did:example:123456789abcdefghi",
+        &ResolutionOptions::default(),
+    );
+    assert_eq!(res.error(), Some(ResolutionError::Timeout));
+}
@@ -0,0 +1,173 @@
+#![cfg(feature = "http-server")]
+
+extern crate did_doc as did;
+
+use did::{
+    resolver::{
+        DocumentMetadata, InMemoryResolver, MethodRegistry, Server, DID_JSON, DID_LD_JSON,
+        DID_RESOLUTION,
+    },
+    Document,
+};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::sync::Arc;
+use std::thread;
+
+fn start_server() -> (SocketAddr, Arc<Server<MethodRegistry>>) {
+    let mut example = InMemoryResolver::new();
+    example.insert(Document::new(
+        "https://w3id.org/did/v1",
+        "did:example:123456789abcdefghi",
+    ));
+    example.insert_with_metadata(
+        Document::new("https://w3id.org/did/v1", "did:example:deactivated"),
+        DocumentMetadata {
+            deactivated: Some(true),
+            ..DocumentMetadata::default()
+        },
+    );
+    let mut registry = MethodRegistry::new();
+    registry.register("example", example);
+
+    let server = Arc::new(Server::bind("127.0.0.1:0", registry).unwrap());
+    let addr = server.local_addr().unwrap();
+    let runner = server.clone();
+    thread::spawn(move || runner.run());
+    (addr, server)
+}
+
+/// Sends a raw HTTP/1.0 request and returns the status, content type and body.
+fn get(addr: SocketAddr, method: &str, path: &str, accept: Option<&str>) -> (u16, String, String) {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    let mut request = format!("{} {} HTTP/1.0\r\nHost: localhost\r\n", method, path);
+    if let Some(accept) = accept {
+        request.push_str(&format!("Accept: {}\r\n", accept));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_at(response.find("\r\n\r\n").unwrap());
+    let status = head[9..12].parse().unwrap();
+    let content_type = head
+        .lines()
+        .find(|l| l.to_lowercase().starts_with("content-type:"))
+        .map(|l| l["content-type:".len()..].trim().to_owned())
+        .unwrap_or_default();
+    (status, content_type, body[4..].to_owned())
+}
+
+#[test]
+fn did_resolution_server_0() {
+    let (addr, server) = start_server();
+    let path = "/1.0/identifiers/did:example:123456789abcdefghi";
+    let flat = r#"{"@context":"https://w3id.org/did/v1","id":"did:example:123456789abcdefghi"}"#;
+
+    let (status, content_type, body) = get(addr, "GET", path, None);
+    assert_eq!(status, 200);
+    assert_eq!(content_type, DID_JSON);
+    assert_eq!(body, flat);
+
+    let (status, content_type, body) = get(addr, "GET", path, Some(DID_LD_JSON));
+    assert_eq!(status, 200);
+    assert_eq!(content_type, DID_LD_JSON);
+    assert_eq!(body, flat);
+
+    let (status, content_type, body) = get(addr, "GET", path, Some(DID_RESOLUTION));
+    assert_eq!(status, 200);
+    assert_eq!(content_type, DID_RESOLUTION);
+    let result: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        result["didDocument"]["id"],
+        "did:example:123456789abcdefghi"
+    );
+    assert_eq!(result["didResolutionMetadata"]["contentType"], DID_JSON);
+
+    let (status, _, _) = get(addr, "GET", path, Some("text/html"));
+    assert_eq!(status, 406);
+
+    server.shutdown();
+}
+
+#[test]
+fn did_resolution_server_1() {
+    let (addr, server) = start_server();
+
+    let cases = [
+        (
+            "/1.0/identifiers/did:example:pqrstuvwxyz0987654321",
+            404,
+            "notFound",
+        ),
+        (
+            "/1.0/identifiers/did:git:123456789abcdefghi",
+            501,
+            "methodNotSupported",
+        ),
+        ("/1.0/identifiers/example", 400, "invalidDid"),
+    ];
+    for (path, expected, error) in cases.iter() {
+        let (status, content_type, body) = get(addr, "GET", path, None);
+        assert_eq!(status, *expected);
+        assert_eq!(content_type, DID_RESOLUTION);
+        let result: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(result["didResolutionMetadata"]["error"], *error);
+    }
+
+    let (status, _, body) = get(
+        addr,
+        "GET",
+        "/1.0/identifiers/did:example:deactivated",
+        None,
+    );
+    assert_eq!(status, 410);
+    assert!(body.contains("did:example:deactivated"));
+
+    let (status, _, _) = get(
+        addr,
+        "GET",
+        "/2.0/identifiers/did:example:123456789abcdefghi",
+        None,
+    );
+    assert_eq!(status, 404);
+
+    let (status, _, _) = get(
+        addr,
+        "POST",
+        "/1.0/identifiers/did:example:123456789abcdefghi",
+        None,
+    );
+    assert_eq!(status, 405);
+
+    server.shutdown();
+}
+
+#[cfg(feature = "http-client")]
+#[test]
+fn did_resolution_server_2() {
+    use did::{
+        resolver::{ResolutionError, ResolutionOptions, UniversalResolver},
+        Resolver,
+    };
+
+    let (addr, server) = start_server();
+    let client = UniversalResolver::new(&format!("http://{}", addr));
+    let options = ResolutionOptions::default();
+
+    let res = client.resolve_str("did:example:123456789abcdefghi", &options);
+    assert!(res.error().is_none());
+    assert_eq!(
+        res.document.unwrap().subject(),
+        "did:example:123456789abcdefghi"
+    );
+
+    let res = client.resolve_str("did:example:pqrstuvwxyz0987654321", &options);
+    assert_eq!(res.error(), Some(ResolutionError::NotFound));
+
+    let res = client.resolve_str("did:example:deactivated", &options);
+    assert_eq!(res.did_document_metadata.deactivated, Some(true));
+
+    server.shutdown();
+}
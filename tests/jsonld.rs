@@ -0,0 +1,300 @@
+extern crate did_doc as did;
+
+use did::{
+    fields::{ContextEntry, KnownContext},
+    jsonld::{self, ContextLoader, StaticLoader},
+    DidError, DidErrorKind, Document,
+};
+use serde_json::{json, Value};
+use std::str::FromStr;
+
+const SEC: &str = "https://w3id.org/security#";
+
+/// Serves a single context, the way a loader going over the network would.
+struct ExampleLoader;
+
+impl ContextLoader for ExampleLoader {
+    fn load(&self, url: &str) -> Result<Value, DidError> {
+        match url {
+            "https://example.com/context/v1" => Ok(json!({
+                "@context": {"nickname": "https://example.com/vocab#nickname"}
+            })),
+            _ => Err(DidError::from_kind(DidErrorKind::UnknownContext {
+                url: url.to_owned(),
+            })),
+        }
+    }
+}
+
+fn roundtrip(doc: &Document, loader: &StaticLoader) -> Value {
+    let expanded = doc.expand(loader).unwrap();
+    let compacted = Document::compact(&expanded, doc.context(), loader).unwrap();
+    assert_eq!(
+        serde_json::to_value(&compacted).unwrap(),
+        serde_json::to_value(doc).unwrap()
+    );
+    expanded
+}
+
+#[test]
+fn did_jsonld_0() {
+    let jstr = r#"
+    {
+        "@context": "https://w3id.org/did/v1",
+        "id": "did:example:123456789abcdefghi",
+        "created": "2002-10-10T17:00:00Z",
+        "publicKey": [{
+            "id": "did:example:123456789abcdefghi#keys-1",
+            "type": "Ed25519VerificationKey2018",
+            "controller": "did:example:123456789abcdefghi",
+            "publicKeyBase58": "H3C2AVvLMv6gmMNam3uVAjZpfkcJCwDwnZn6z3wXmqPV"
+        }],
+        "authentication": ["did:example:123456789abcdefghi#keys-1"],
+        "service": [{
+            "id": "did:example:123456789abcdefghi#agent",
+            "type": "AgentService",
+            "serviceEndpoint": "https://agent.example.com/8377464"
+        }]
+    }
+    "#;
+    let doc = Document::from_str(jstr).unwrap();
+    let expanded = roundtrip(&doc, &StaticLoader::new());
+
+    let node = &expanded[0];
+    assert_eq!(node["@id"], "did:example:123456789abcdefghi");
+    assert_eq!(
+        node["http://purl.org/dc/terms/created"],
+        json!([{
+            "@type": "http://www.w3.org/2001/XMLSchema#dateTime",
+            "@value": "2002-10-10T17:00:00Z"
+        }])
+    );
+    let key = &node[format!("{}publicKey", SEC)][0];
+    assert_eq!(
+        key["@type"],
+        json!([format!("{}Ed25519VerificationKey2018", SEC)])
+    );
+    assert_eq!(
+        key[format!("{}controller", SEC)],
+        json!([{"@id": "did:example:123456789abcdefghi"}])
+    );
+    assert_eq!(
+        node[format!("{}authenticationMethod", SEC)],
+        json!([{"@id": "did:example:123456789abcdefghi#keys-1"}])
+    );
+    assert_eq!(
+        node["https://w3id.org/did#service"][0]["https://w3id.org/did#serviceEndpoint"],
+        json!([{"@id": "https://agent.example.com/8377464"}])
+    );
+}
+
+#[test]
+fn did_jsonld_1() {
+    let jstr = r#"
+    {
+        "@context": [
+            "https://www.w3.org/ns/did/v1",
+            "https://w3id.org/security/suites/ed25519-2020/v1",
+            "https://w3id.org/security/suites/x25519-2020/v1"
+        ],
+        "id": "did:example:123",
+        "authentication": [{
+            "id": "did:example:123#key-1",
+            "type": "Ed25519VerificationKey2020",
+            "controller": "did:example:123",
+            "publicKeyMultibase": "z6MkpTHR8VNsBxYAAWHut2Geadd9jSwuBV8xRoAnwWsdvktH"
+        }],
+        "keyAgreement": [{
+            "id": "did:example:123#key-2",
+            "type": "X25519KeyAgreementKey2020",
+            "controller": "did:example:123",
+            "publicKeyMultibase": "z6LSbysY2xFMRpGMhb7tFTLMpeuPRaqaWM1yECx2AtzE3KCc"
+        }],
+        "service": [{
+            "id": "did:example:123#linked-domain",
+            "type": "LinkedDomains",
+            "serviceEndpoint": "https://bar.example.com"
+        }]
+    }
+    "#;
+    let doc = Document::from_str(jstr).unwrap();
+    let expanded = roundtrip(&doc, &StaticLoader::new());
+
+    // the key types bring their own terms
+    let key = &expanded[0][format!("{}authenticationMethod", SEC)][0];
+    assert_eq!(
+        key[format!("{}publicKeyMultibase", SEC)],
+        json!([{
+            "@type": format!("{}multibase", SEC),
+            "@value": "z6MkpTHR8VNsBxYAAWHut2Geadd9jSwuBV8xRoAnwWsdvktH"
+        }])
+    );
+    let key = &expanded[0][format!("{}keyAgreementMethod", SEC)][0];
+    assert_eq!(
+        key["@type"],
+        json!([format!("{}X25519KeyAgreementKey2020", SEC)])
+    );
+
+    // DID Core does not make the services a set, they still compact to one
+    let compacted = jsonld::compact(
+        &expanded,
+        &json!(KnownContext::DidCore.url()),
+        &StaticLoader::new(),
+    )
+    .unwrap();
+    assert_eq!(compacted["service"]["id"], "did:example:123#linked-domain");
+    assert_eq!(
+        compacted["service"]["serviceEndpoint"],
+        "https://bar.example.com"
+    );
+
+    // plain expansion drops terms no context defines, and with them nodes
+    // left with nothing but an id, while documents refuse to lose them
+    let mut doc = Document::new(KnownContext::DidCore.url(), "did:example:123");
+    doc.extra.insert("nickname".to_owned(), json!("example"));
+    let input = serde_json::to_value(&doc).unwrap();
+    assert_eq!(
+        jsonld::expand(&input, &StaticLoader::new()).unwrap(),
+        json!([])
+    );
+    let err = doc.expand(&StaticLoader::new()).unwrap_err();
+    assert_eq!(err.kind(), DidErrorKind::InvalidJsonLd);
+
+    let doc = Document::new(KnownContext::DidCore.url(), "did:example:123");
+    assert_eq!(
+        roundtrip(&doc, &StaticLoader::new()),
+        json!([{"@id": "did:example:123"}])
+    );
+}
+
+#[test]
+fn did_jsonld_2() {
+    let mut doc = Document::new(KnownContext::DidCore.url(), "did:example:123");
+    doc.add_context("https://example.com/context/v1");
+    doc.extra.insert("nickname".to_owned(), json!("example"));
+
+    let err = doc.expand(&StaticLoader::new()).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        DidErrorKind::UnknownContext {
+            url: "https://example.com/context/v1".to_owned(),
+        }
+    );
+
+    let loader = StaticLoader::new().with_fallback(ExampleLoader);
+    let expanded = roundtrip(&doc, &loader);
+    assert_eq!(
+        expanded[0]["https://example.com/vocab#nickname"],
+        json!([{"@value": "example"}])
+    );
+
+    let mut loader = StaticLoader::new();
+    loader.insert(
+        "https://example.com/context/v1",
+        ExampleLoader
+            .load("https://example.com/context/v1")
+            .unwrap(),
+    );
+    assert_eq!(roundtrip(&doc, &loader), expanded);
+}
+
+#[test]
+fn did_jsonld_3() {
+    let jstr = r#"
+    {
+        "@context": [
+            "https://www.w3.org/ns/did/v1",
+            {
+                "@vocab": "https://example.com/vocab#",
+                "ex": "https://example.com/terms#",
+                "tags": {"@id": "ex:tags", "@container": "@list"}
+            }
+        ],
+        "id": "did:example:123",
+        "nickname": "example",
+        "tags": ["a", "b"]
+    }
+    "#;
+    let doc = Document::from_str(jstr).unwrap();
+    assert!(doc.context()[1].as_object().is_some());
+    let expanded = roundtrip(&doc, &StaticLoader::new());
+    assert_eq!(
+        expanded,
+        json!([{
+            "@id": "did:example:123",
+            "https://example.com/vocab#nickname": [{"@value": "example"}],
+            "https://example.com/terms#tags": [{"@list": [{"@value": "a"}, {"@value": "b"}]}]
+        }])
+    );
+
+    // without the inline context the IRIs stay as they are
+    let context = vec![ContextEntry::from(KnownContext::DidCore)];
+    let compacted = Document::compact(&expanded, &context, &StaticLoader::new()).unwrap();
+    assert_eq!(
+        compacted.extra["https://example.com/vocab#nickname"],
+        "example"
+    );
+    assert_eq!(
+        compacted.extra["https://example.com/terms#tags"],
+        json!({"@list": ["a", "b"]})
+    );
+
+    // a context that is not valid JSON-LD
+    let err = jsonld::compact(&expanded, &json!([{"ex": 5}]), &StaticLoader::new()).unwrap_err();
+    assert_eq!(err.kind(), DidErrorKind::InvalidJsonLd);
+}
+
+#[test]
+fn did_jsonld_4() {
+    let jstr = r#"
+    {
+        "@context": [
+            "https://www.w3.org/ns/did/v1",
+            "https://w3id.org/security/suites/ed25519-2020/v1"
+        ],
+        "id": "did:example:123",
+        "publicKey": [{
+            "id": "did:example:123#key-1",
+            "type": "Ed25519VerificationKey2020",
+            "controller": "did:example:123",
+            "publicKeyMultibase": "z6MkpTHR8VNsBxYAAWHut2Geadd9jSwuBV8xRoAnwWsdvktH"
+        }],
+        "authentication": ["did:example:123#key-1"]
+    }
+    "#;
+    let doc = Document::from_str(jstr).unwrap();
+    let loader = StaticLoader::new();
+
+    // DID Core has no publicKey, the keys become verification methods
+    let expanded = roundtrip(&doc, &loader);
+    let key = &expanded[0][format!("{}verificationMethod", SEC)][0];
+    assert_eq!(key["@id"], "did:example:123#key-1");
+    assert_eq!(
+        key["@type"],
+        json!([format!("{}Ed25519VerificationKey2020", SEC)])
+    );
+
+    let mut two = doc.clone();
+    two.add_public_key(
+        doc.public_key()[0]
+            .clone()
+            .with_id("did:example:123#key-2")
+            .unwrap(),
+    );
+    roundtrip(&two, &loader);
+
+    // the keys cannot take the place of verification methods already there
+    let mut doc = doc;
+    doc.extra.insert("verificationMethod".to_owned(), json!([]));
+    let err = doc.expand(&loader).unwrap_err();
+    assert_eq!(err.kind(), DidErrorKind::InvalidJsonLd);
+
+    // without the suite context the key material has no term
+    let jstr = jstr.replace(
+        r#""https://w3id.org/security/suites/ed25519-2020/v1""#,
+        r#""https://w3id.org/security/suites/x25519-2020/v1""#,
+    );
+    let doc = Document::from_str(&jstr).unwrap();
+    let err = doc.expand(&loader).unwrap_err();
+    assert_eq!(err.kind(), DidErrorKind::InvalidJsonLd);
+}
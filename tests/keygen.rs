@@ -0,0 +1,199 @@
+// most of these tests need one of the key algorithm features
+#![cfg_attr(
+    not(all(
+        feature = "ed25519",
+        feature = "secp256k1",
+        feature = "p256",
+        feature = "x25519"
+    )),
+    allow(dead_code, unused_imports)
+)]
+
+extern crate did_doc as did;
+
+use did::{
+    crypto::KeyPair,
+    fields::{KeyAlgorithm, PublicKeyEncoding, PublicKeyType},
+    DidErrorKind, Resource, Uri,
+};
+use std::str::FromStr;
+
+fn secret(hex: &str) -> Vec<u8> {
+    hex::decode(hex).unwrap()
+}
+
+#[cfg(feature = "ed25519")]
+#[test]
+fn did_keygen_ed25519_0() {
+    let seed: Vec<u8> = (0..32).collect();
+    let pair = KeyPair::from_secret_key(KeyAlgorithm::Ed25519, &seed).unwrap();
+    assert_eq!(
+        hex::encode(pair.public_key_bytes()),
+        "03a107bff3ce10be1d70dd18e74bc09967e4d6309ba50d5f1ddc8664125531b8"
+    );
+    assert_eq!(&pair.secret_key()[..], &seed[..]);
+
+    let pk = pair
+        .public_key(
+            "did:example:123#key-1",
+            "did:example:123",
+            PublicKeyEncoding::Base58,
+        )
+        .unwrap();
+    assert_eq!(pk.kind(), PublicKeyType::Ed25519VerificationKey2018);
+    assert_eq!(pk.data(), "FAe4sisG95oZ42w7buUn5qEE4TAnfTTFPiguZUHmhiF");
+
+    let pk = pair
+        .public_key(
+            "did:example:123#key-1",
+            "did:example:123",
+            PublicKeyEncoding::Jwk,
+        )
+        .unwrap();
+    assert_eq!(pk.kind(), PublicKeyType::JsonWebKey2020);
+    assert_eq!(pk.jwk().unwrap().crv.as_deref(), Some("Ed25519"));
+
+    // Ed25519VerificationKey2018 only comes in base58 and JWK
+    let err = pair
+        .public_key(
+            "did:example:123#key-1",
+            "did:example:123",
+            PublicKeyEncoding::Hex,
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.kind(),
+        DidErrorKind::UnsupportedConversion {
+            from: "publicKeyMultibase".to_owned(),
+            to: "publicKeyHex".to_owned(),
+        }
+    );
+
+    let flat = r#"{"@context":"https://www.w3.org/ns/did/v1","id":"did:key:z6MkehRgf7yJbgaGfYsdoAsKdBPE3dj2CYhowQdcjqSJgvVd","publicKey":[{"id":"did:key:z6MkehRgf7yJbgaGfYsdoAsKdBPE3dj2CYhowQdcjqSJgvVd#z6MkehRgf7yJbgaGfYsdoAsKdBPE3dj2CYhowQdcjqSJgvVd","type":"Ed25519VerificationKey2020","controller":"did:key:z6MkehRgf7yJbgaGfYsdoAsKdBPE3dj2CYhowQdcjqSJgvVd","publicKeyMultibase":"z6MkehRgf7yJbgaGfYsdoAsKdBPE3dj2CYhowQdcjqSJgvVd"}],"authentication":["did:key:z6MkehRgf7yJbgaGfYsdoAsKdBPE3dj2CYhowQdcjqSJgvVd#z6MkehRgf7yJbgaGfYsdoAsKdBPE3dj2CYhowQdcjqSJgvVd"],"keyAgreement":[{"id":"did:key:z6MkehRgf7yJbgaGfYsdoAsKdBPE3dj2CYhowQdcjqSJgvVd#z6LSgTMiVvjkfQd8CF1kWasYZKBqtAYf6h8TC3yDfjPgDbWQ","type":"X25519KeyAgreementKey2020","controller":"did:key:z6MkehRgf7yJbgaGfYsdoAsKdBPE3dj2CYhowQdcjqSJgvVd","publicKeyMultibase":"z6LSgTMiVvjkfQd8CF1kWasYZKBqtAYf6h8TC3yDfjPgDbWQ"}]}"#;
+
+    let doc = pair.did_key(PublicKeyEncoding::Multibase).unwrap();
+    assert_eq!(doc.to_string(), flat);
+    assert!(doc.validate().is_empty());
+    assert_eq!(doc.authentication_keys().unwrap().len(), 1);
+
+    let doc = pair.did_peer(PublicKeyEncoding::Base58).unwrap();
+    assert_eq!(
+        doc.subject(),
+        "did:peer:0z6MkehRgf7yJbgaGfYsdoAsKdBPE3dj2CYhowQdcjqSJgvVd"
+    );
+    assert_eq!(doc.public_key()[0].encoding(), PublicKeyEncoding::Base58);
+    assert!(doc.validate().is_empty());
+}
+
+#[cfg(feature = "secp256k1")]
+#[test]
+fn did_keygen_secp256k1_0() {
+    let one = secret("0000000000000000000000000000000000000000000000000000000000000001");
+    let pair = KeyPair::from_secret_key(KeyAlgorithm::Secp256k1, &one).unwrap();
+    assert_eq!(
+        hex::encode(pair.public_key_bytes()),
+        "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+    );
+
+    let doc = pair.did_key(PublicKeyEncoding::Jwk).unwrap();
+    assert_eq!(
+        doc.subject(),
+        "did:key:zQ3shVc2UkAfJCdc1TR8E66J85h48P43r93q8jGPkPpjF9Ef9"
+    );
+    let pk = &doc.public_key()[0];
+    assert_eq!(pk.kind(), PublicKeyType::JsonWebKey2020);
+    assert_eq!(pk.algorithm(), Some(KeyAlgorithm::Secp256k1));
+    assert!(doc.key_agreement().is_empty());
+
+    let pk = pair
+        .public_key(
+            "did:example:123#key-1",
+            "did:example:123",
+            PublicKeyEncoding::EthereumAddress,
+        )
+        .unwrap();
+    assert_eq!(pk.kind(), PublicKeyType::EcdsaSecp256k1RecoveryMethod2020);
+    assert_eq!(pk.data(), "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf");
+
+    let zero = vec![0u8; 32];
+    let err = KeyPair::from_secret_key(KeyAlgorithm::Secp256k1, &zero).unwrap_err();
+    assert_eq!(err.kind(), DidErrorKind::InvalidSecretKey);
+}
+
+#[cfg(feature = "p256")]
+#[test]
+fn did_keygen_p256_0() {
+    let one = secret("0000000000000000000000000000000000000000000000000000000000000001");
+    let pair = KeyPair::from_secret_key(KeyAlgorithm::P256, &one).unwrap();
+    assert_eq!(
+        hex::encode(pair.public_key_bytes()),
+        "036b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296"
+    );
+
+    let doc = pair.did_key(PublicKeyEncoding::Multibase).unwrap();
+    assert_eq!(
+        doc.subject(),
+        "did:key:zDnaepsL7AXenJkVYdkh5KuKsSU7Ykh7kyXaLLU7auN9FWSiZ"
+    );
+    assert_eq!(
+        doc.public_key()[0].kind(),
+        PublicKeyType::EcdsaSecp256r1VerificationKey2019
+    );
+    assert!(doc.validate().is_empty());
+}
+
+#[cfg(feature = "x25519")]
+#[test]
+fn did_keygen_x25519_0() {
+    let scalar = secret("3d94eea49c580aef816935762be049559d6d1440dede12e6a125f1841fff8e6f");
+    let pair = KeyPair::from_secret_key(KeyAlgorithm::X25519, &scalar).unwrap();
+    assert_eq!(
+        hex::encode(pair.public_key_bytes()),
+        "4701d08488451f545a409fb58ae3e58581ca40ac3f7f114698cd71deac73ca01"
+    );
+
+    let doc = pair.did_key(PublicKeyEncoding::Base58).unwrap();
+    assert!(doc.public_key().is_empty());
+    assert!(doc.authentication().is_empty());
+    let ka = &doc.key_agreement()[0];
+    assert_eq!(ka.kind(), PublicKeyType::X25519KeyAgreementKey2019);
+    assert_eq!(ka.data(), "5nBYycvtZwuP6rdyywMbEiyN321YQ5xJK5FYBGk9WDje");
+
+    let uri = Uri::from_str("#z6LSgTMiVvjkfQd8CF1kWasYZKBqtAYf6h8TC3yDfjPgDbWQ").unwrap();
+    assert!(matches!(
+        doc.dereference(&uri),
+        Some(Resource::PublicKey(_))
+    ));
+}
+
+#[cfg(all(feature = "ed25519", feature = "secp256k1", feature = "p256"))]
+#[test]
+fn did_keygen_generate_0() {
+    for &algorithm in &[
+        KeyAlgorithm::Ed25519,
+        KeyAlgorithm::Secp256k1,
+        KeyAlgorithm::P256,
+    ] {
+        let pair = KeyPair::generate(algorithm).unwrap();
+        let other = KeyPair::generate(algorithm).unwrap();
+        assert_ne!(pair.public_key_bytes(), other.public_key_bytes());
+
+        let again = KeyPair::from_secret_key(algorithm, &pair.secret_key()).unwrap();
+        assert_eq!(again.public_key_bytes(), pair.public_key_bytes());
+        assert!(!format!("{:?}", pair).contains(&hex::encode(&pair.secret_key()[..])));
+    }
+}
+
+#[test]
+fn did_keygen_unsupported_0() {
+    let err = KeyPair::generate(KeyAlgorithm::Rsa).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        DidErrorKind::UnsupportedKeyAlgorithm {
+            algorithm: "Rsa".to_owned(),
+        }
+    );
+
+    let err = KeyPair::from_secret_key(KeyAlgorithm::Ed25519, &[1, 2, 3]).unwrap_err();
+    assert_eq!(err.kind(), DidErrorKind::InvalidSecretKey);
+}
@@ -0,0 +1,146 @@
+// most of these tests need one of the key algorithm features
+#![cfg_attr(
+    not(all(feature = "ed25519", feature = "secp256k1", feature = "p256")),
+    allow(dead_code, unused_imports)
+)]
+
+extern crate did_doc as did;
+
+use did::{
+    crypto::{sign, InMemoryKeyStore, KeyPair, KeyStore, Signer},
+    fields::{KeyAlgorithm, PublicKeyEncoding},
+    DidErrorKind, Uri,
+};
+use std::str::FromStr;
+
+const MESSAGE: &[u8] = b"did:example:123 says hello";
+
+fn seed() -> Vec<u8> {
+    (0..32).collect()
+}
+
+#[cfg(all(feature = "ed25519", feature = "secp256k1", feature = "p256"))]
+#[test]
+fn did_sign_0() {
+    for &algorithm in &[
+        KeyAlgorithm::Ed25519,
+        KeyAlgorithm::Secp256k1,
+        KeyAlgorithm::P256,
+    ] {
+        let pair = KeyPair::from_secret_key(algorithm, &seed()).unwrap();
+        let doc = pair.did_key(PublicKeyEncoding::Jwk).unwrap();
+        let key_id = doc.public_key()[0].subject().as_uri().clone();
+
+        let mut store = InMemoryKeyStore::new();
+        store.insert(&key_id, pair).unwrap();
+
+        let relative = Uri::from_str(&format!("#{}", key_id.fragment.clone().unwrap())).unwrap();
+        let signature = sign(&doc, &store, &relative, MESSAGE).unwrap();
+        doc.public_key()[0].verify(MESSAGE, &signature).unwrap();
+    }
+}
+
+#[cfg(feature = "ed25519")]
+#[test]
+fn did_sign_1() {
+    let pair = KeyPair::from_secret_key(KeyAlgorithm::Ed25519, &seed()).unwrap();
+    let doc = pair.did_key(PublicKeyEncoding::Base58).unwrap();
+    let store = InMemoryKeyStore::new();
+
+    // no such key in the document
+    let err = sign(&doc, &store, &Uri::from_str("#keys-2").unwrap(), MESSAGE).unwrap_err();
+    assert_eq!(err.kind(), DidErrorKind::DanglingReference);
+
+    // the key is in the document but not in the store
+    let key_id = doc.public_key()[0].subject().as_uri();
+    let err = sign(&doc, &store, key_id, MESSAGE).unwrap_err();
+    assert_eq!(err.kind(), DidErrorKind::MissingSecretKey);
+
+    // X25519 keys cannot sign
+    let signer: Box<dyn Signer> = Box::new(store);
+    let key_id = doc.key_agreement()[0].subject().as_uri();
+    assert!(sign(&doc, &signer, key_id, MESSAGE).is_err());
+}
+
+#[test]
+fn did_keystore_memory_0() {
+    let mut store = InMemoryKeyStore::new();
+    assert!(store.is_empty());
+
+    let key_id = Uri::from_str("did:example:123#keys-1").unwrap();
+    match KeyPair::from_secret_key(KeyAlgorithm::Ed25519, &seed()) {
+        Ok(pair) => {
+            store.insert(&key_id, pair.clone()).unwrap();
+            assert_eq!(store.len(), 1);
+            assert_eq!(store.key_ids().unwrap(), vec![key_id.clone()]);
+            let found = store.get(&key_id).unwrap().unwrap();
+            assert_eq!(found.public_key_bytes(), pair.public_key_bytes());
+            assert!(store.remove(&key_id).unwrap());
+        }
+        Err(e) => assert_eq!(
+            e.kind(),
+            DidErrorKind::UnsupportedKeyAlgorithm {
+                algorithm: "Ed25519".to_owned(),
+            }
+        ),
+    }
+    assert!(!store.remove(&key_id).unwrap());
+    assert!(store.get(&key_id).unwrap().is_none());
+}
+
+#[cfg(all(feature = "file-keystore", feature = "ed25519", feature = "p256"))]
+#[test]
+fn did_keystore_file_0() {
+    use did::crypto::FileKeyStore;
+
+    let path = std::env::temp_dir().join(format!("did-keystore-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let ed25519 = KeyPair::from_secret_key(KeyAlgorithm::Ed25519, &seed()).unwrap();
+    let p256 = KeyPair::generate(KeyAlgorithm::P256).unwrap();
+    let id1 = Uri::from_str("did:example:123#keys-1").unwrap();
+    let id2 = Uri::from_str("did:example:123#keys-2").unwrap();
+    {
+        let iterations = FileKeyStore::MIN_ITERATIONS;
+        let mut store =
+            FileKeyStore::open_with_iterations(&path, "correct horse", iterations).unwrap();
+        assert!(store.is_empty());
+        store.insert(&id1, ed25519.clone()).unwrap();
+        store.insert(&id2, p256.clone()).unwrap();
+    }
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert!(contents.contains(&ed25519.fingerprint()));
+    assert!(!contents.contains(&hex::encode(&ed25519.secret_key()[..])));
+
+    let err = FileKeyStore::open(&path, "wrong horse").err().unwrap();
+    assert_eq!(err.kind(), DidErrorKind::WrongPassphrase);
+
+    let mut store = FileKeyStore::open(&path, "correct horse").unwrap();
+    assert_eq!(store.key_ids().unwrap(), vec![id1.clone(), id2.clone()]);
+    let found = store.get(&id2).unwrap().unwrap();
+    assert_eq!(&found.secret_key()[..], &p256.secret_key()[..]);
+    assert_eq!(
+        store.sign(&id1, MESSAGE).unwrap(),
+        ed25519.sign(MESSAGE).unwrap()
+    );
+
+    assert!(store.remove(&id1).unwrap());
+    let store = FileKeyStore::open(&path, "correct horse").unwrap();
+    assert_eq!(store.len(), 1);
+
+    // a store that barely stretches the passphrase is refused
+    let mut contents: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    contents["iterations"] = 1.into();
+    std::fs::write(&path, contents.to_string()).unwrap();
+    let err = FileKeyStore::open(&path, "correct horse").err().unwrap();
+    assert_eq!(err.kind(), DidErrorKind::Io);
+    std::fs::remove_file(&path).unwrap();
+
+    let err = FileKeyStore::open_with_iterations(&path, "correct horse", 1)
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), DidErrorKind::Io);
+    assert!(!path.exists());
+}
@@ -0,0 +1,912 @@
+extern crate did_doc as did;
+
+mod common;
+
+use common::key;
+use did::{
+    fields::{Jwk, KeyAlgorithm, PublicKey, PublicKeyEncoding, PublicKeyType},
+    DiagnosticKind, DidErrorKind, Document,
+};
+use serde_json::{json, Value};
+use std::str::FromStr;
+
+const ED25519_KEY: &str = "03a107bff3ce10be1d70dd18e74bc09967e4d6309ba50d5f1ddc8664125531b8";
+
+const RSA_SPKI: &str = "-----BEGIN PUBLIC KEY-----\nMIGfMA0GCSqGSIb3DQEBAQUAA4GNADCBiQKBgQC8QfrC0Yt0WrVN4yBFx44evlt8\nQ6VR6wKKYLLXeR2a0mlpdeReUf2NYVQi/WYMsIU70PxJJE9NVeVxLrBqDWozuybN\nUzfK+1wsYWhihe6Hx601VNWd0EHcYntZrvRLxjy0+gNR0DPAt/mV9xfCWUlIYVS+\n7XgFF9CSuiakWxPH/QIDAQAB\n-----END PUBLIC KEY-----\n";
+
+const RSA_PKCS1: &str = "-----BEGIN RSA PUBLIC KEY-----\nMIGJAoGBALxB+sLRi3RatU3jIEXHjh6+W3xDpVHrAopgstd5HZrSaWl15F5R/Y1h\nVCL9ZgywhTvQ/EkkT01V5XEusGoNajO7Js1TN8r7XCxhaGKF7ofHrTVU1Z3QQdxi\ne1mu9EvGPLT6A1HQM8C3+ZX3F8JZSUhhVL7teAUX0JK6JqRbE8f9AgMBAAE=\n-----END RSA PUBLIC KEY-----\n";
+
+#[test]
+fn did_key_bytes_0() {
+    let expected = hex::decode(ED25519_KEY).unwrap();
+    let encodings = [
+        ("publicKeyHex", ED25519_KEY),
+        (
+            "publicKeyBase64",
+            "A6EHv/POEL4dcN0Y50vAmWfk1jCbpQ1fHdyGZBJVMbg=",
+        ),
+        ("publicKeyBase64", "A6EHv/POEL4dcN0Y50vAmWfk1jCbpQ1fHdyGZBJVMbg"),
+        (
+            "publicKeyBase58",
+            "FAe4sisG95oZ42w7buUn5qEE4TAnfTTFPiguZUHmhiF",
+        ),
+        (
+            "publicKeyMultibase",
+            "z6MkehRgf7yJbgaGfYsdoAsKdBPE3dj2CYhowQdcjqSJgvVd",
+        ),
+        (
+            "publicKeyPem",
+            "-----BEGIN PUBLIC KEY-----\nMCowBQYDK2VwAyEAA6EHv/POEL4dcN0Y50vAmWfk1jCbpQ1fHdyGZBJVMbg=\n-----END PUBLIC KEY-----\n",
+        ),
+    ];
+
+    for (field, data) in encodings.iter() {
+        let pk = key("Ed25519VerificationKey2018", field, data);
+        assert_eq!(pk.key_bytes().unwrap(), expected, "{}", field);
+    }
+}
+
+#[test]
+fn did_key_bytes_1() {
+    let pk = key(
+        "EcdsaSecp256k1VerificationKey2019",
+        "publicKeyHex",
+        "02b97c30de767f084ce3080168ee293053ba33b235d7116a3263d29f1450936b71",
+    );
+    let bytes = pk.key_bytes().unwrap();
+    assert_eq!(bytes.len(), 33);
+    assert_eq!(bytes[0], 0x02);
+
+    let pem = "-----BEGIN PUBLIC KEY-----\r\nMFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAEJu/OvQ7p40pmkYfhizqRIrL3M5Rb\r\nZJzJ+fkh6fna2BKQI4venMe7Mw0VDGdwTdJa5wVSBXRLbzG/QHB0WHLQ5g==\r\n-----END PUBLIC KEY-----\r\n";
+    let pk = key("UnknownKey", "publicKeyPem", pem);
+    assert_eq!(
+        hex::encode(pk.key_bytes().unwrap()),
+        "0426efcebd0ee9e34a669187e18b3a9122b2f733945b649cc9f9f921e9f9dad81290238bde9cc7bb330d150c67704dd25ae7055205744b6f31bf4070745872d0e6"
+    );
+}
+
+#[test]
+fn did_key_bytes_2() {
+    let spki = key("RsaVerificationKey2018", "publicKeyPem", RSA_SPKI);
+    let pkcs1 = key("RsaVerificationKey2018", "publicKeyPem", RSA_PKCS1);
+    let bytes = spki.key_bytes().unwrap();
+    assert_eq!(bytes[0], 0x30);
+    assert_eq!(bytes, pkcs1.key_bytes().unwrap());
+}
+
+#[test]
+fn did_key_bytes_3() {
+    let malformed = [
+        ("publicKeyHex", "02b97c30zz"),
+        ("publicKeyBase64", "not base64!"),
+        ("publicKeyBase58", "0OIl"),
+        (
+            "publicKeyMultibase",
+            "6MkehRgf7yJbgaGfYsdoAsKdBPE3dj2CYhowQdcjqSJgvVd",
+        ),
+        // an Ed25519 multicodec prefix on 31 bytes
+        (
+            "publicKeyMultibase",
+            "z2DQUz8yxybcgY49o2TDENNPqPQBbVynuU6CcNCWtSMrwMx",
+        ),
+        (
+            "publicKeyPem",
+            "-----BEGIN PUBLIC KEY...END PUBLIC KEY-----",
+        ),
+        (
+            "publicKeyPem",
+            "-----BEGIN PUBLIC KEY-----\nAAEC\n-----END PUBLIC KEY-----",
+        ),
+        ("publicKeyHex", ""),
+    ];
+
+    for (field, data) in malformed.iter() {
+        let pk = key("UnknownKey", field, data);
+        let err = pk.key_bytes().unwrap_err();
+        assert_eq!(
+            err.kind(),
+            DidErrorKind::MalformedKeyData {
+                encoding: field.to_string()
+            },
+            "{} {}",
+            field,
+            data
+        );
+    }
+
+    let pk = key(
+        "UnknownKey",
+        "ethereumAddress",
+        "0xF3beAC30C498D9E26865F34fCAa57dBB935b0D74",
+    );
+    assert_eq!(
+        pk.key_bytes().unwrap_err().kind(),
+        DidErrorKind::UnsupportedEncoding {
+            encoding: "ethereumAddress".to_owned()
+        }
+    );
+}
+
+#[test]
+fn did_jwk_0() {
+    let jstr = r##"
+    {
+        "@context": ["https://www.w3.org/ns/did/v1", "https://w3id.org/security/suites/jws-2020/v1"],
+        "id": "did:example:123",
+        "publicKey": [{
+            "id": "did:example:123#_Qq0UL2Fq651Q0Fjd6TvnYE-faHiOpRlPVQcY_-tA4A",
+            "type": "UnknownKey",
+            "controller": "did:example:123",
+            "publicKeyJwk": {
+                "crv": "Ed25519",
+                "x": "VCpo2LMLhn6iWku8MKvSLg2ZAoC-nlOyPVQaO3FxVeQ",
+                "kty": "OKP",
+                "kid": "_Qq0UL2Fq651Q0Fjd6TvnYE-faHiOpRlPVQcY_-tA4A"
+            }
+        }, {
+            "id": "did:example:123#4SZ-StXrp5Yd4_4rxHVTCYTHyt4zyPfN1fIuYsm6k3A",
+            "type": "UnknownKey",
+            "controller": "did:example:123",
+            "publicKeyJwk": {
+                "kty": "EC",
+                "crv": "secp256k1",
+                "x": "Z4Y3NNOxv0J6tCgqOBFnHnaZhJF6LdulT7z8A-2D5_8",
+                "y": "i5a2NtJoUKXkLm6q8nOEu9WOkso1Ag6FTUT6k_LMnGk",
+                "use": "sig",
+                "alg": "ES256K",
+                "x5t": "not-a-thumbprint"
+            }
+        }]
+    }
+    "##;
+
+    let doc = Document::from_str(jstr).unwrap();
+    let k1 = &doc.public_key()[0];
+    assert_eq!(k1.encoding(), PublicKeyEncoding::Jwk);
+    assert_eq!(k1.data().as_str(), "");
+    let jwk = k1.jwk().unwrap();
+    assert_eq!(jwk.kty, "OKP");
+    assert_eq!(jwk.crv.as_deref(), Some("Ed25519"));
+    assert_eq!(
+        jwk.kid.as_deref(),
+        Some("_Qq0UL2Fq651Q0Fjd6TvnYE-faHiOpRlPVQcY_-tA4A")
+    );
+    assert_eq!(k1.key_bytes().unwrap().len(), 32);
+
+    let jwk = doc.public_key()[1].jwk().unwrap();
+    assert_eq!(jwk.key_use.as_deref(), Some("sig"));
+    assert_eq!(jwk.alg.as_deref(), Some("ES256K"));
+    assert_eq!(jwk.extra["x5t"], "not-a-thumbprint");
+
+    let original: Value = serde_json::from_str(jstr).unwrap();
+    let serialized: Value = serde_json::from_str(&doc.to_string()).unwrap();
+    assert_eq!(original, serialized);
+}
+
+#[test]
+fn did_jwk_1() {
+    let pk = key(
+        "UnknownKey",
+        "publicKeyPem",
+        "-----BEGIN PUBLIC KEY-----\nMFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAEJu/OvQ7p40pmkYfhizqRIrL3M5Rb\nZJzJ+fkh6fna2BKQI4venMe7Mw0VDGdwTdJa5wVSBXRLbzG/QHB0WHLQ5g==\n-----END PUBLIC KEY-----\n",
+    );
+    let jwk: Jwk = serde_json::from_value(json!({
+        "kty": "EC",
+        "crv": "P-256",
+        "x": "Ju_OvQ7p40pmkYfhizqRIrL3M5RbZJzJ-fkh6fna2BI",
+        "y": "kCOL3pzHuzMNFQxncE3SWucFUgV0S28xv0BwdFhy0OY"
+    }))
+    .unwrap();
+    assert_eq!(jwk.key_bytes().unwrap(), pk.key_bytes().unwrap());
+
+    let pk = key("RsaVerificationKey2018", "publicKeyPem", RSA_SPKI);
+    let jwk: Jwk = serde_json::from_value(json!({
+        "kty": "RSA",
+        "n": "vEH6wtGLdFq1TeMgRceOHr5bfEOlUesCimCy13kdmtJpaXXkXlH9jWFUIv1mDLCFO9D8SSRPTVXlcS6wag1qM7smzVM3yvtcLGFoYoXuh8etNVTVndBB3GJ7Wa70S8Y8tPoDUdAzwLf5lfcXwllJSGFUvu14BRfQkrompFsTx_0",
+        "e": "AQAB"
+    }))
+    .unwrap();
+    assert_eq!(jwk.key_bytes().unwrap(), pk.key_bytes().unwrap());
+}
+
+#[test]
+fn did_jwk_2() {
+    // these parse, but cannot be used as keys
+    let unusable = [
+        json!({"kty": "EC", "crv": "P-256", "x": "Ju_OvQ7p40pmkYfhizqRIrL3M5RbZJzJ-fkh6fna2BI"}),
+        json!({"kty": "OKP", "x": "VCpo2LMLhn6iWku8MKvSLg2ZAoC-nlOyPVQaO3FxVeQ"}),
+        json!({"kty": "RSA", "e": "AQAB"}),
+        json!({"kty": "oct", "k": "c2VjcmV0"}),
+    ];
+
+    for jwk in unusable.iter() {
+        let json = json!({
+            "@context": "https://www.w3.org/ns/did/v1",
+            "id": "did:example:123",
+            "publicKey": [{
+                "id": "did:example:123#keys-1",
+                "type": "JsonWebKey2020",
+                "controller": "did:example:123",
+                "publicKeyJwk": jwk,
+            }],
+        });
+        let doc = Document::from_str(&json.to_string()).unwrap();
+        assert_eq!(
+            doc.public_key()[0].key_bytes().unwrap_err().kind(),
+            DidErrorKind::MalformedKeyData {
+                encoding: "publicKeyJwk".to_owned()
+            }
+        );
+        let diagnostics = doc.validate();
+        assert_eq!(diagnostics.len(), 1, "{}", jwk);
+        assert_eq!(diagnostics[0].kind, DiagnosticKind::InvalidJwk);
+        assert_eq!(diagnostics[0].path, "publicKey[0].publicKeyJwk");
+    }
+
+    let invalid = [
+        json!({"crv": "Ed25519", "x": "VCpo2LMLhn6iWku8MKvSLg2ZAoC-nlOyPVQaO3FxVeQ"}),
+        json!("not an object"),
+    ];
+
+    for jwk in invalid.iter() {
+        let json = json!({
+            "id": "did:example:123#keys-1",
+            "type": "UnknownKey",
+            "controller": "did:example:123",
+            "publicKeyJwk": jwk,
+        });
+        assert!(
+            serde_json::from_value::<PublicKey>(json).is_err(),
+            "{}",
+            jwk
+        );
+    }
+
+    let jwk: Jwk =
+        serde_json::from_value(json!({"kty": "OKP", "crv": "Ed25519", "x": "*"})).unwrap();
+    assert_eq!(
+        jwk.key_bytes().unwrap_err().kind(),
+        DidErrorKind::MalformedKeyData {
+            encoding: "publicKeyJwk".to_owned()
+        }
+    );
+}
+
+#[test]
+fn did_convert_encoding_0() {
+    let pk = key(
+        "Ed25519VerificationKey2018",
+        "publicKeyBase58",
+        "FAe4sisG95oZ42w7buUn5qEE4TAnfTTFPiguZUHmhiF",
+    );
+
+    // the 2018 type has no multibase form, the 2020 one does
+    let mb = pk.to_encoding(PublicKeyEncoding::Multibase).unwrap();
+    assert_eq!(mb.encoding(), PublicKeyEncoding::Multibase);
+    assert_eq!(
+        mb.data().as_str(),
+        "z6MkehRgf7yJbgaGfYsdoAsKdBPE3dj2CYhowQdcjqSJgvVd"
+    );
+    assert_eq!(mb.subject(), pk.subject());
+    assert_eq!(mb.kind(), PublicKeyType::Ed25519VerificationKey2020);
+
+    let jwk = pk.to_encoding(PublicKeyEncoding::Jwk).unwrap();
+    assert_eq!(
+        serde_json::to_value(&jwk).unwrap()["publicKeyJwk"],
+        json!({"kty": "OKP", "crv": "Ed25519", "x": "A6EHv_POEL4dcN0Y50vAmWfk1jCbpQ1fHdyGZBJVMbg"})
+    );
+    assert_eq!(jwk.kind(), pk.kind());
+
+    let b58 = mb.to_encoding(PublicKeyEncoding::Base58).unwrap();
+    assert_eq!(b58.data(), pk.data());
+    assert_eq!(b58.kind(), pk.kind());
+
+    let mut doc = Document::new(
+        "https://www.w3.org/ns/did/v1",
+        "did:example:123456789abcdefghi",
+    );
+    for (i, key) in [mb, jwk, b58].iter().enumerate() {
+        doc.add_public_key(key.clone().with_id(&format!("#keys-{}", i)).unwrap());
+    }
+    assert!(doc.validate().is_empty());
+
+    // no Ed25519 type takes raw hex or PEM
+    for &encoding in &[PublicKeyEncoding::Hex, PublicKeyEncoding::Pem] {
+        assert_eq!(
+            pk.to_encoding(encoding).unwrap_err().kind(),
+            DidErrorKind::UnsupportedConversion {
+                from: "publicKeyBase58".to_owned(),
+                to: encoding.field_name().to_owned(),
+            }
+        );
+    }
+
+    // keys of an unknown type keep it and take any encoding
+    let pk = key(
+        "UnknownKey",
+        "publicKeyJwk",
+        json!({"kty": "OKP", "crv": "Ed25519", "x": "A6EHv_POEL4dcN0Y50vAmWfk1jCbpQ1fHdyGZBJVMbg"}),
+    );
+    let pem = pk.to_encoding(PublicKeyEncoding::Pem).unwrap();
+    assert_eq!(
+        pem.data().as_str(),
+        "-----BEGIN PUBLIC KEY-----\nMCowBQYDK2VwAyEAA6EHv/POEL4dcN0Y50vAmWfk1jCbpQ1fHdyGZBJVMbg=\n-----END PUBLIC KEY-----\n"
+    );
+    assert_eq!(pem.kind(), PublicKeyType::UnknownKey);
+    let b64 = pem.to_encoding(PublicKeyEncoding::Base64).unwrap();
+    assert_eq!(
+        b64.data().as_str(),
+        "A6EHv/POEL4dcN0Y50vAmWfk1jCbpQ1fHdyGZBJVMbg="
+    );
+    let hex = b64.to_encoding(PublicKeyEncoding::Hex).unwrap();
+    assert_eq!(hex.data().as_str(), ED25519_KEY);
+}
+
+#[test]
+fn did_convert_encoding_1() {
+    let pk = key(
+        "EcdsaSecp256k1VerificationKey2019",
+        "publicKeyHex",
+        "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+    );
+
+    let jwk = pk.to_encoding(PublicKeyEncoding::Jwk).unwrap();
+    let value = jwk.jwk().unwrap();
+    assert_eq!(value.kty, "EC");
+    assert_eq!(value.crv.as_deref(), Some("secp256k1"));
+    assert_eq!(
+        value.x.as_deref(),
+        Some("eb5mfvncu6xVoGKVzocLBwKb_NstzijZWfKBWxb4F5g")
+    );
+    assert_eq!(
+        value.y.as_deref(),
+        Some("SDradyajxGVdpPv8DhEIqP0XtEimhVQZnEfQj_sQ1Lg")
+    );
+
+    let address = jwk.to_encoding(PublicKeyEncoding::EthereumAddress).unwrap();
+    assert_eq!(
+        address.data().as_str(),
+        "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf"
+    );
+
+    assert_eq!(
+        address.kind(),
+        PublicKeyType::EcdsaSecp256k1RecoveryMethod2020
+    );
+
+    // multibase keys are compressed again
+    let mb = jwk.to_encoding(PublicKeyEncoding::Multibase).unwrap();
+    let hex = mb.to_encoding(PublicKeyEncoding::Hex).unwrap();
+    assert_eq!(hex.data(), pk.data());
+
+    let mut doc = Document::new(
+        "https://www.w3.org/ns/did/v1",
+        "did:example:123456789abcdefghi",
+    );
+    for (i, key) in [jwk, address, mb, hex].iter().enumerate() {
+        doc.add_public_key(key.clone().with_id(&format!("#keys-{}", i)).unwrap());
+    }
+    assert!(doc.validate().is_empty());
+}
+
+#[test]
+fn did_convert_encoding_2() {
+    let curves = [
+        (
+            "0226efcebd0ee9e34a669187e18b3a9122b2f733945b649cc9f9f921e9f9dad812",
+            "P-256",
+            "Ju_OvQ7p40pmkYfhizqRIrL3M5RbZJzJ-fkh6fna2BI",
+            "kCOL3pzHuzMNFQxncE3SWucFUgV0S28xv0BwdFhy0OY",
+        ),
+        (
+            "030ce818753960c6c09910ab6f007ec1b79176cba552f407cfa943772f9d3c30f86bb8a76aed636f3da58e11dee1ae1e61",
+            "P-384",
+            "DOgYdTlgxsCZEKtvAH7Bt5F2y6VS9AfPqUN3L508MPhruKdq7WNvPaWOEd7hrh5h",
+            "LTqeiVe0yux9BslZvxcfON99YAtaRT0dxd9Al4z_QIHC5OKcGRg7bo7_ESuqc3Zx",
+        ),
+    ];
+
+    for (compressed, crv, x, y) in curves.iter() {
+        let pk: PublicKey = serde_json::from_value(json!({
+            "id": "did:example:123#keys-1",
+            "type": "UnknownKey",
+            "controller": "did:example:123",
+            "publicKeyJwk": {"kty": "EC", "crv": crv, "x": x, "y": y},
+        }))
+        .unwrap();
+
+        let mb = pk.to_encoding(PublicKeyEncoding::Multibase).unwrap();
+        let hex = mb.to_encoding(PublicKeyEncoding::Hex).unwrap();
+        assert_eq!(hex.data(), compressed);
+
+        let jwk = mb.to_encoding(PublicKeyEncoding::Jwk).unwrap();
+        assert_eq!(jwk.jwk(), pk.jwk());
+
+        let pem = jwk.to_encoding(PublicKeyEncoding::Pem).unwrap();
+        assert_eq!(
+            pem.to_encoding(PublicKeyEncoding::Jwk).unwrap().jwk(),
+            pk.jwk()
+        );
+    }
+}
+
+#[test]
+fn did_convert_encoding_3() {
+    let pk = key("RsaVerificationKey2018", "publicKeyPem", RSA_PKCS1);
+
+    let jwk = pk.to_encoding(PublicKeyEncoding::Jwk).unwrap();
+    let value = jwk.jwk().unwrap();
+    assert_eq!(value.kty, "RSA");
+    assert_eq!(value.e.as_deref(), Some("AQAB"));
+    assert_eq!(value.n.as_deref(), Some("vEH6wtGLdFq1TeMgRceOHr5bfEOlUesCimCy13kdmtJpaXXkXlH9jWFUIv1mDLCFO9D8SSRPTVXlcS6wag1qM7smzVM3yvtcLGFoYoXuh8etNVTVndBB3GJ7Wa70S8Y8tPoDUdAzwLf5lfcXwllJSGFUvu14BRfQkrompFsTx_0"));
+
+    let pem = jwk.to_encoding(PublicKeyEncoding::Pem).unwrap();
+    assert_eq!(pem.data().as_str(), RSA_SPKI);
+}
+
+#[test]
+fn did_convert_encoding_4() {
+    let unsupported = |from: &str, to: &str| DidErrorKind::UnsupportedConversion {
+        from: from.to_owned(),
+        to: to.to_owned(),
+    };
+
+    let pk = key(
+        "UnknownKey",
+        "ethereumAddress",
+        "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf",
+    );
+    assert_eq!(
+        pk.to_encoding(PublicKeyEncoding::Pem).unwrap_err().kind(),
+        unsupported("ethereumAddress", "publicKeyPem")
+    );
+
+    let pk = key("Ed25519VerificationKey2018", "publicKeyHex", ED25519_KEY);
+    assert_eq!(
+        pk.to_encoding(PublicKeyEncoding::EthereumAddress)
+            .unwrap_err()
+            .kind(),
+        unsupported("publicKeyHex", "ethereumAddress")
+    );
+    assert_eq!(
+        pk.to_encoding(PublicKeyEncoding::Unknown)
+            .unwrap_err()
+            .kind(),
+        unsupported("publicKeyHex", "publicKeyUnknown")
+    );
+
+    // raw keys of an unknown type only convert to other raw encodings
+    let pk = key("UnknownKey", "publicKeyHex", ED25519_KEY);
+    assert!(pk.to_encoding(PublicKeyEncoding::Base58).is_ok());
+    assert_eq!(
+        pk.to_encoding(PublicKeyEncoding::Multibase)
+            .unwrap_err()
+            .kind(),
+        unsupported("publicKeyHex", "publicKeyMultibase")
+    );
+
+    // the key data has to match the key type
+    let pk = key("Ed25519VerificationKey2018", "publicKeyPem", RSA_SPKI);
+    assert_eq!(
+        pk.to_encoding(PublicKeyEncoding::Jwk).unwrap_err().kind(),
+        DidErrorKind::MalformedKeyData {
+            encoding: "publicKeyPem".to_owned()
+        }
+    );
+    let pk = key("Ed25519VerificationKey2018", "publicKeyHex", "02b97c30");
+    assert!(pk.to_encoding(PublicKeyEncoding::Multibase).is_err());
+}
+
+#[test]
+fn did_key_types_0() {
+    let jstr = r##"
+    {
+        "@context": "https://www.w3.org/ns/did/v1",
+        "id": "did:example:123",
+        "publicKey": [{
+            "id": "did:example:123#key-0",
+            "type": "Ed25519VerificationKey2020",
+            "controller": "did:example:123",
+            "publicKeyMultibase": "z6MkmM42vxfqZQsv4ehtTjFFxQ4sQKS2w6WR7emozFAn5cxu"
+        }, {
+            "id": "did:example:123#zC9ByQ8aJs8vrNXyDhPHHNNMSHPcaSgNpjjsBYpMMjsTdS",
+            "type": "X25519KeyAgreementKey2019",
+            "controller": "did:example:123",
+            "publicKeyBase58": "9hFgmPVfmBZwRvFEyniQDBkz9LmV7gDEqytWyGZLmDXE"
+        }, {
+            "id": "did:example:123#key-1",
+            "type": "X25519KeyAgreementKey2020",
+            "controller": "did:example:123",
+            "publicKeyMultibase": "z6LSbysY2xFMRpGMhb7tFTLMpeuPRaqaWM1yECx2AtzE3KCc"
+        }, {
+            "id": "did:example:123#key-2",
+            "type": "Multikey",
+            "controller": "did:example:123",
+            "publicKeyMultibase": "z6MkmM42vxfqZQsv4ehtTjFFxQ4sQKS2w6WR7emozFAn5cxu"
+        }, {
+            "id": "did:example:123#_Qq0UL2Fq651Q0Fjd6TvnYE-faHiOpRlPVQcY_-tA4A",
+            "type": "JsonWebKey2020",
+            "controller": "did:example:123",
+            "publicKeyJwk": {
+                "crv": "Ed25519",
+                "x": "VCpo2LMLhn6iWku8MKvSLg2ZAoC-nlOyPVQaO3FxVeQ",
+                "kty": "OKP",
+                "kid": "_Qq0UL2Fq651Q0Fjd6TvnYE-faHiOpRlPVQcY_-tA4A"
+            }
+        }, {
+            "id": "did:example:123#vm-3",
+            "type": "EcdsaSecp256k1RecoveryMethod2020",
+            "controller": "did:example:123",
+            "ethereumAddress": "0xF3beAC30C498D9E26865F34fCAa57dBB935b0D74"
+        }, {
+            "id": "did:example:123#key-3",
+            "type": "EcdsaSecp256r1VerificationKey2019",
+            "controller": "did:example:123",
+            "publicKeyJwk": {
+                "kty": "EC",
+                "crv": "P-256",
+                "x": "38M1FDts7Oea7urmseiugGW7tWc3mLpJh6rKe7xINZ8",
+                "y": "nDQW6XZ7b_u2Sy9slofYLlG03sOEoug3I0aAPQ0exs4"
+            }
+        }, {
+            "id": "did:example:123#key-4",
+            "type": "EcdsaSecp384r1VerificationKey2019",
+            "controller": "did:example:123",
+            "publicKeyJwk": {
+                "kty": "EC",
+                "crv": "P-384",
+                "x": "GnLl6mDti7a2VUIZP5w6pcRX8q5nvEIgB3Q_5RI2p9F_QVsaAlDN7IG68Jn0dS_F",
+                "y": "jq4QoAHKiIzezDp88s_cxSPXtuXYFliuCGndgU4Qp8l91xzD1spCmFIzQgVjqvcP"
+            }
+        }, {
+            "id": "did:example:123#bls12381-g2",
+            "type": "Bls12381G2Key2020",
+            "controller": "did:example:123",
+            "publicKeyBase58": "25EEkQtcLKsEzQ6JTo9cg4W7NHpaurn4Wg6LaNPFq6JQXnrP91SDviUz7KrJVMJd76CtAZFsRLYzvgX2JGxo2ccUHtuHk7ELCWwrkBDfrXCFVfqJKDootee9iVaF6NpdJtBE"
+        }]
+    }
+    "##;
+
+    let doc = Document::from_str(jstr).unwrap();
+    let expected = [
+        (
+            PublicKeyType::Ed25519VerificationKey2020,
+            Some(KeyAlgorithm::Ed25519),
+        ),
+        (
+            PublicKeyType::X25519KeyAgreementKey2019,
+            Some(KeyAlgorithm::X25519),
+        ),
+        (
+            PublicKeyType::X25519KeyAgreementKey2020,
+            Some(KeyAlgorithm::X25519),
+        ),
+        (PublicKeyType::Multikey, Some(KeyAlgorithm::Ed25519)),
+        (PublicKeyType::JsonWebKey2020, Some(KeyAlgorithm::Ed25519)),
+        (
+            PublicKeyType::EcdsaSecp256k1RecoveryMethod2020,
+            Some(KeyAlgorithm::Secp256k1),
+        ),
+        (
+            PublicKeyType::EcdsaSecp256r1VerificationKey2019,
+            Some(KeyAlgorithm::P256),
+        ),
+        (
+            PublicKeyType::EcdsaSecp384r1VerificationKey2019,
+            Some(KeyAlgorithm::P384),
+        ),
+        (
+            PublicKeyType::Bls12381G2Key2020,
+            Some(KeyAlgorithm::Bls12381G2),
+        ),
+    ];
+    assert_eq!(doc.public_key().len(), expected.len());
+    for (pk, (kind, algorithm)) in doc.public_key().iter().zip(expected.iter()) {
+        assert_eq!(pk.key_type(), kind);
+        assert_eq!(pk.algorithm(), *algorithm, "{:?}", kind);
+        assert!(kind.permits(pk.encoding()));
+    }
+    assert!(doc.validate().is_empty());
+
+    let original: Value = serde_json::from_str(jstr).unwrap();
+    let serialized: Value = serde_json::from_str(&doc.to_string()).unwrap();
+    assert_eq!(original, serialized);
+}
+
+#[test]
+fn did_key_types_1() {
+    let jstr = r##"
+    {
+        "@context": "https://www.w3.org/ns/did/v1",
+        "id": "did:example:123",
+        "publicKey": [{
+            "id": "did:example:123#key-0",
+            "type": "Ed25519VerificationKey2020",
+            "controller": "did:example:123",
+            "publicKeyBase58": "FAe4sisG95oZ42w7buUn5qEE4TAnfTTFPiguZUHmhiF"
+        }],
+        "authentication": [
+            "did:example:123#key-0",
+            {
+                "id": "did:example:123#key-1",
+                "type": "JsonWebKey2020",
+                "controller": "did:example:123",
+                "publicKeyMultibase": "z6MkmM42vxfqZQsv4ehtTjFFxQ4sQKS2w6WR7emozFAn5cxu"
+            }
+        ]
+    }
+    "##;
+
+    let doc = Document::from_str(jstr).unwrap();
+    let diagnostics = doc.validate();
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].kind, DiagnosticKind::EncodingNotPermitted);
+    assert_eq!(diagnostics[0].path, "publicKey[0]");
+    assert_eq!(diagnostics[1].path, "authentication[1]");
+    assert_eq!(
+        diagnostics[1].to_string(),
+        "authentication[1]: JsonWebKey2020 keys cannot use publicKeyMultibase"
+    );
+
+    assert!(PublicKeyType::UnknownKey.permits(PublicKeyEncoding::Base64));
+    assert!(!PublicKeyType::Multikey.permits(PublicKeyEncoding::Jwk));
+}
+
+#[test]
+fn did_key_types_2() {
+    let jstr = r#"{"@context":"https://www.w3.org/ns/did/v1","id":"did:example:123","publicKey":[{"id":"did:example:123#key-0","type":"ProprietaryVerificationKey2024","controller":"did:example:123","publicKeyMultibase":"z6MkmM42vxfqZQsv4ehtTjFFxQ4sQKS2w6WR7emozFAn5cxu"},{"id":"did:example:123#key-1","type":"UnknownKey","controller":"did:example:123","publicKeyHex":"03a107bff3ce10be1d70dd18e74bc09967e4d6309ba50d5f1ddc8664125531b8"}]}"#;
+
+    let doc = Document::from_str(jstr).unwrap();
+    let k1 = &doc.public_key()[0];
+    assert_eq!(
+        k1.kind(),
+        PublicKeyType::Other("ProprietaryVerificationKey2024".to_owned())
+    );
+    assert_eq!(k1.kind().to_string(), "ProprietaryVerificationKey2024");
+    assert_eq!(k1.algorithm(), Some(KeyAlgorithm::Ed25519));
+    assert_eq!(doc.public_key()[1].kind(), PublicKeyType::UnknownKey);
+    assert!(doc.validate().is_empty());
+    assert_eq!(doc.to_string(), jstr);
+
+    assert_eq!(
+        PublicKeyType::from_str("Multikey").unwrap(),
+        PublicKeyType::Multikey
+    );
+    assert_eq!(
+        PublicKeyType::from_str("multikey").unwrap(),
+        PublicKeyType::Other("multikey".to_owned())
+    );
+}
+
+#[test]
+fn did_publickey_extra_0() {
+    let jstr = r#"{"@context":"https://w3id.org/did/v1","id":"did:example:123","publicKey":[{"id":"did:example:123#keys-1","type":"Ed25519VerificationKey2018","controller":"did:example:123","publicKeyBase58":"H3C2AVvLMv6gmMNam3uVAjZpfkcJCwDwnZn6z3wXmqPV","expires":"2030-01-01T00:00:00Z","usage":"signing","revoked":{"date":"2024-01-01T00:00:00Z","reason":"superseded"}}],"authentication":["did:example:123#keys-1"]}"#;
+
+    let doc = Document::from_str(jstr).unwrap();
+    let k1 = &doc.public_key()[0];
+    assert_eq!(k1.extra.len(), 3);
+    assert_eq!(k1.extra["expires"], "2030-01-01T00:00:00Z");
+    assert_eq!(k1.extra["usage"], "signing");
+    assert_eq!(k1.extra["revoked"]["reason"], "superseded");
+    assert!(doc.authentication()[0].extra.is_empty());
+    assert_eq!(doc.to_string(), jstr);
+
+    let mb = k1.to_encoding(PublicKeyEncoding::Multibase).unwrap();
+    assert_eq!(mb.extra, k1.extra);
+}
+
+#[test]
+fn did_fingerprint_0() {
+    let expected = "z6MkehRgf7yJbgaGfYsdoAsKdBPE3dj2CYhowQdcjqSJgvVd";
+    for (field, data) in &[
+        ("publicKeyHex", ED25519_KEY),
+        (
+            "publicKeyBase58",
+            "FAe4sisG95oZ42w7buUn5qEE4TAnfTTFPiguZUHmhiF",
+        ),
+        ("publicKeyMultibase", expected),
+    ] {
+        let pk = key("Ed25519VerificationKey2018", field, data);
+        assert_eq!(pk.fingerprint().unwrap(), expected);
+    }
+
+    let jwk = json!({
+        "kty": "EC",
+        "crv": "secp256k1",
+        "x": "Z4Y3NNOxv0J6tCgqOBFnHnaZhJF6LdulT7z8A-2D5_8",
+        "y": "i5a2NtJoUKXkLm6q8nOEu9WOkso1Ag6FTUT6k_LMnGk"
+    });
+    let pk = PublicKey::from_jwk(
+        "did:example:123#key-1",
+        PublicKeyType::JsonWebKey2020,
+        "did:example:123",
+        serde_json::from_value(jwk).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        pk.fingerprint().unwrap(),
+        "zQ3shmcDpahC44SsW9jfhqPtE9mceShBKagBCAnXqy6t6DjDG"
+    );
+
+    let pk = key("UnknownKey", "publicKeyHex", ED25519_KEY);
+    let err = pk.fingerprint().unwrap_err();
+    assert_eq!(
+        err.kind(),
+        DidErrorKind::UnsupportedConversion {
+            from: "publicKeyHex".to_owned(),
+            to: "publicKeyMultibase".to_owned(),
+        }
+    );
+}
+
+#[test]
+fn did_thumbprint_0() {
+    // RFC 7638, section 3.1
+    let jwk: Jwk = serde_json::from_value(json!({
+        "kty": "RSA",
+        "n": "0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw",
+        "e": "AQAB",
+        "alg": "RS256",
+        "kid": "2011-04-29"
+    }))
+    .unwrap();
+    assert_eq!(
+        jwk.thumbprint().unwrap(),
+        "NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs"
+    );
+
+    let jwk: Jwk = serde_json::from_value(json!({
+        "kty": "OKP",
+        "crv": "Ed25519",
+        "x": "VCpo2LMLhn6iWku8MKvSLg2ZAoC-nlOyPVQaO3FxVeQ",
+        "kid": "_Qq0UL2Fq651Q0Fjd6TvnYE-faHiOpRlPVQcY_-tA4A"
+    }))
+    .unwrap();
+    assert_eq!(jwk.thumbprint().unwrap(), jwk.kid.unwrap());
+
+    let pk = key("Ed25519VerificationKey2018", "publicKeyHex", ED25519_KEY);
+    assert_eq!(
+        pk.jwk_thumbprint().unwrap(),
+        "1IG2tMH7J2wbJZnOf8LJzQitKf7LMvoAElsuDMVM54Y"
+    );
+}
+
+#[test]
+fn did_fingerprint_id_0() {
+    let did = "did:example:123";
+    let pk = PublicKey::new(
+        "",
+        PublicKeyType::Ed25519VerificationKey2018,
+        did,
+        PublicKeyEncoding::Base58,
+        "FAe4sisG95oZ42w7buUn5qEE4TAnfTTFPiguZUHmhiF",
+    )
+    .unwrap();
+    let id = format!("{}#{}", did, pk.fingerprint().unwrap());
+    let pk = pk.with_id(&id).unwrap();
+
+    let mut doc = Document::new("https://www.w3.org/ns/did/v1", did);
+    doc.add_public_key(pk);
+    assert_eq!(
+        doc.to_string(),
+        r#"{"@context":"https://www.w3.org/ns/did/v1","id":"did:example:123","publicKey":[{"id":"did:example:123#z6MkehRgf7yJbgaGfYsdoAsKdBPE3dj2CYhowQdcjqSJgvVd","type":"Ed25519VerificationKey2018","controller":"did:example:123","publicKeyBase58":"FAe4sisG95oZ42w7buUn5qEE4TAnfTTFPiguZUHmhiF"}]}"#
+    );
+    let uri = did::Uri::from_str("#z6MkehRgf7yJbgaGfYsdoAsKdBPE3dj2CYhowQdcjqSJgvVd").unwrap();
+    assert!(doc.dereference(&uri).is_some());
+    let err = PublicKey::new(
+        "keys-1",
+        PublicKeyType::Ed25519VerificationKey2018,
+        did,
+        PublicKeyEncoding::Base58,
+        "FAe4sisG95oZ42w7buUn5qEE4TAnfTTFPiguZUHmhiF",
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), DidErrorKind::InvalidUri);
+    let err = doc.public_key()[0].clone().with_id("keys-1").unwrap_err();
+    assert_eq!(err.kind(), DidErrorKind::InvalidUri);
+
+    // a JWK has no string form, it takes from_jwk
+    let err = PublicKey::new(
+        "#keys-1",
+        PublicKeyType::JsonWebKey2020,
+        did,
+        PublicKeyEncoding::Jwk,
+        "",
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.kind(),
+        DidErrorKind::UnsupportedEncoding {
+            encoding: "publicKeyJwk".to_owned(),
+        }
+    );
+}
+
+#[test]
+fn did_reference_0() {
+    let uri = did::Uri::from_str("did:example:123#keys-1").unwrap();
+    let pk = PublicKey::reference_to(&uri);
+    assert!(pk.reference());
+    assert_eq!(
+        serde_json::to_value(&pk).unwrap(),
+        json!("did:example:123#keys-1")
+    );
+
+    // a reference that is not a DID URL is an error, not a panic
+    assert!(serde_json::from_value::<PublicKey>(json!("keys-1")).is_err());
+}
+
+#[test]
+fn did_x25519_0() {
+    let pk = key(
+        "Ed25519VerificationKey2018",
+        "publicKeyBase58",
+        "FAe4sisG95oZ42w7buUn5qEE4TAnfTTFPiguZUHmhiF",
+    );
+    let x = pk.to_x25519().unwrap();
+    assert_eq!(x.kind(), PublicKeyType::X25519KeyAgreementKey2019);
+    assert_eq!(x.encoding(), PublicKeyEncoding::Base58);
+    assert_eq!(x.data(), "5nBYycvtZwuP6rdyywMbEiyN321YQ5xJK5FYBGk9WDje");
+    assert_eq!(
+        x.subject(),
+        "did:example:123456789abcdefghi#z6LSgTMiVvjkfQd8CF1kWasYZKBqtAYf6h8TC3yDfjPgDbWQ"
+    );
+    assert_eq!(x.controller(), pk.controller());
+
+    let pk = key(
+        "Ed25519VerificationKey2020",
+        "publicKeyMultibase",
+        "z6MkehRgf7yJbgaGfYsdoAsKdBPE3dj2CYhowQdcjqSJgvVd",
+    );
+    let x = pk.to_x25519().unwrap();
+    assert_eq!(x.kind(), PublicKeyType::X25519KeyAgreementKey2020);
+    assert_eq!(x.data(), "z6LSgTMiVvjkfQd8CF1kWasYZKBqtAYf6h8TC3yDfjPgDbWQ");
+
+    let pk = key("Ed25519VerificationKey2018", "publicKeyHex", ED25519_KEY)
+        .to_encoding(PublicKeyEncoding::Jwk)
+        .unwrap();
+    let pk = PublicKey::from_jwk(
+        "did:example:123#key-1",
+        PublicKeyType::JsonWebKey2020,
+        "did:example:123",
+        pk.jwk().unwrap().clone(),
+    )
+    .unwrap();
+    let x = pk.to_x25519().unwrap();
+    assert_eq!(x.kind(), PublicKeyType::JsonWebKey2020);
+    let jwk = x.jwk().unwrap();
+    assert_eq!(jwk.crv.as_deref(), Some("X25519"));
+    assert_eq!(
+        jwk.x.as_deref(),
+        Some("RwHQhIhFH1RaQJ-1iuPlhYHKQKw_fxFGmM1x3qxzygE")
+    );
+}
+
+#[test]
+fn did_x25519_1() {
+    let pk = key("RsaVerificationKey2018", "publicKeyPem", RSA_SPKI);
+    let err = pk.to_x25519().unwrap_err();
+    assert_eq!(
+        err.kind(),
+        DidErrorKind::UnsupportedConversion {
+            from: "RsaVerificationKey2018".to_owned(),
+            to: "X25519".to_owned(),
+        }
+    );
+
+    // y = 1 is the identity point
+    let mut identity = vec![0u8; 32];
+    identity[0] = 1;
+    let pk = key(
+        "Ed25519VerificationKey2018",
+        "publicKeyHex",
+        hex::encode(identity),
+    );
+    let err = pk.to_x25519().unwrap_err();
+    assert_eq!(
+        err.kind(),
+        DidErrorKind::MalformedKeyData {
+            encoding: "publicKeyHex".to_owned(),
+        }
+    );
+}
@@ -0,0 +1,464 @@
+extern crate did_doc as did;
+
+mod common;
+
+use common::example_resolver;
+use did::{
+    resolver::{
+        CacheStats, CachingResolver, Content, Dereferencer, DereferencingOptions, DocumentMetadata,
+        InMemoryResolver, MethodRegistry, ResolutionError, ResolutionOptions,
+    },
+    Document, ResolutionResult, Resolver, Uri,
+};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn did_resolve_in_memory_0() {
+    let resolver = example_resolver();
+    let options = ResolutionOptions::default();
+
+    let res = resolver.resolve_str("did:example:123456789abcdefghi", &options);
+    assert!(res.error().is_none());
+    let doc = res.document.unwrap();
+    assert_eq!(doc.subject(), "did:example:123456789abcdefghi");
+
+    let res = resolver.resolve_str("did:example:pqrstuvwxyz0987654321", &options);
+    assert_eq!(res.error(), Some(ResolutionError::NotFound));
+    assert!(res.document.is_none());
+
+    for s in &[
+        "https://example.org",
+        "did:example:123456789abcdefghi#keys-1",
+    ] {
+        let res = resolver.resolve_str(s, &options);
+        assert_eq!(res.error(), Some(ResolutionError::InvalidDid));
+    }
+}
+
+#[test]
+fn did_resolve_in_memory_1() {
+    let mut resolver = InMemoryResolver::new();
+    for version in &["1", "2"] {
+        let metadata = DocumentMetadata {
+            version_id: Some(version.to_string()),
+            ..DocumentMetadata::default()
+        };
+        resolver.insert_with_metadata(
+            Document::new("https://w3id.org/did/v1", "did:example:123456789abcdefghi"),
+            metadata,
+        );
+    }
+
+    let did = Uri::from_str("did:example:123456789abcdefghi").unwrap();
+    let res = resolver.resolve(&did, &ResolutionOptions::default());
+    assert_eq!(res.did_document_metadata.version_id, Some("2".to_string()));
+
+    let options = ResolutionOptions {
+        version_id: Some("1".to_string()),
+        ..ResolutionOptions::default()
+    };
+    let res = resolver.resolve(&did, &options);
+    assert_eq!(res.did_document_metadata.version_id, Some("1".to_string()));
+
+    let options = ResolutionOptions {
+        version_id: Some("3".to_string()),
+        ..ResolutionOptions::default()
+    };
+    let res = resolver.resolve(&did, &options);
+    assert_eq!(res.error(), Some(ResolutionError::NotFound));
+
+    assert!(resolver.remove(&did));
+    assert!(resolver.is_empty());
+}
+
+#[test]
+fn did_resolve_in_memory_2() {
+    let mut resolver = InMemoryResolver::new();
+    let versions = [
+        ("1", Some("2020-01-01T00:00:00Z"), None),
+        (
+            "2",
+            Some("2020-01-01T00:00:00Z"),
+            Some("2021-06-01T12:00:00Z"),
+        ),
+        ("3", None, None),
+    ];
+    for &(version, created, updated) in versions.iter() {
+        let metadata = DocumentMetadata {
+            version_id: Some(version.to_string()),
+            created: created.map(str::to_string),
+            updated: updated.map(str::to_string),
+            ..DocumentMetadata::default()
+        };
+        resolver.insert_with_metadata(
+            Document::new("https://w3id.org/did/v1", "did:example:123456789abcdefghi"),
+            metadata,
+        );
+    }
+
+    let did = Uri::from_str("did:example:123456789abcdefghi").unwrap();
+    let at = |time: &str| ResolutionOptions {
+        version_time: Some(time.to_string()),
+        ..ResolutionOptions::default()
+    };
+
+    let res = resolver.resolve(&did, &at("2021-01-01T00:00:00Z"));
+    assert_eq!(res.did_document_metadata.version_id, Some("1".to_string()));
+    let res = resolver.resolve(&did, &at("2021-06-01T14:00:00+02:00"));
+    assert_eq!(res.did_document_metadata.version_id, Some("2".to_string()));
+    // version 3 has no dates, it is never picked by time
+    let res = resolver.resolve(&did, &at("2030-01-01T00:00:00Z"));
+    assert_eq!(res.did_document_metadata.version_id, Some("2".to_string()));
+
+    let res = resolver.resolve(&did, &at("2019-12-31T23:59:59Z"));
+    assert_eq!(res.error(), Some(ResolutionError::NotFound));
+    let res = resolver.resolve(&did, &at("yesterday"));
+    assert_eq!(res.error(), Some(ResolutionError::InvalidOptions));
+
+    let options = ResolutionOptions {
+        version_id: Some("2".to_string()),
+        ..at("2021-01-01T00:00:00Z")
+    };
+    let res = resolver.resolve(&did, &options);
+    assert_eq!(res.error(), Some(ResolutionError::NotFound));
+}
+
+#[test]
+fn did_resolve_registry_0() {
+    let mut registry = MethodRegistry::new();
+    registry.register("example", example_resolver());
+    assert!(registry.supports("example"));
+    assert_eq!(registry.methods().collect::<Vec<_>>(), vec!["example"]);
+
+    let options = ResolutionOptions::default();
+    let res = registry.resolve_str("did:example:123456789abcdefghi", &options);
+    assert!(res.document.is_some());
+
+    let res = registry.resolve_str("did:git:123456789abcdefghi", &options);
+    assert_eq!(res.error(), Some(ResolutionError::MethodNotSupported));
+
+    let res = registry.resolve_str("did:example:", &options);
+    assert_eq!(res.error(), Some(ResolutionError::InvalidDid));
+
+    assert!(registry.unregister("example"));
+    let res = registry.resolve_str("did:example:123456789abcdefghi", &options);
+    assert_eq!(res.error(), Some(ResolutionError::MethodNotSupported));
+}
+
+#[test]
+fn did_resolution_result_json_0() {
+    let resolver = example_resolver();
+    let options = ResolutionOptions::default();
+
+    let res = resolver.resolve_str("did:example:123456789abcdefghi", &options);
+    let s = serde_json::to_string(&res).unwrap();
+    assert_eq!(
+        s.as_str(),
+        r#"{"didDocument":{"@context":"https://w3id.org/did/v1","id":"did:example:123456789abcdefghi"},"didResolutionMetadata":{},"didDocumentMetadata":{}}"#
+    );
+
+    let res = resolver.resolve_str("did:example:pqrstuvwxyz0987654321", &options);
+    let s = serde_json::to_string(&res).unwrap();
+    assert_eq!(
+        s.as_str(),
+        r#"{"didDocument":null,"didResolutionMetadata":{"error":"notFound"},"didDocumentMetadata":{}}"#
+    );
+    assert_eq!(ResolutionError::NotFound.to_string(), "notFound");
+}
+
+fn service_resolver() -> InMemoryResolver {
+    let jstr = r##"
+    {
+        "@context": "https://w3id.org/did/v1",
+        "id": "did:example:123456789abcdefghi",
+        "publicKey": [{
+            "id": "#keys-1",
+            "type": "Ed25519VerificationKey2018",
+            "controller": "did:example:123456789abcdefghi",
+            "publicKeyBase58": "H3C2AVvLMv6gmMNam3uVAjZpfkcJCwDwnZn6z3wXmqPV"
+        }],
+        "service": [{
+            "id": "did:example:123456789abcdefghi#files",
+            "type": "FileService",
+            "serviceEndpoint": "https://example.com/files/"
+        }]
+    }
+    "##;
+
+    let mut resolver = InMemoryResolver::new();
+    resolver.insert_with_metadata(
+        Document::from_str(jstr).unwrap(),
+        DocumentMetadata {
+            version_id: Some("1".to_string()),
+            ..DocumentMetadata::default()
+        },
+    );
+    resolver.insert_with_metadata(
+        Document::new("https://w3id.org/did/v1", "did:example:123456789abcdefghi"),
+        DocumentMetadata {
+            version_id: Some("2".to_string()),
+            ..DocumentMetadata::default()
+        },
+    );
+    resolver
+}
+
+#[test]
+fn did_dereference_0() {
+    let dereferencer = Dereferencer::new(service_resolver());
+    let options = DereferencingOptions::default();
+
+    let res = dereferencer.dereference_str("did:example:123456789abcdefghi", &options);
+    assert!(res.error().is_none());
+    assert_eq!(
+        res.dereferencing_metadata.content_type,
+        Some("application/did+json".to_string())
+    );
+    match res.content {
+        Some(Content::Document(doc)) => assert!(doc.public_key().is_empty()),
+        c => panic!("unexpected content {:?}", c),
+    }
+    assert_eq!(res.content_metadata.version_id, Some("2".to_string()));
+
+    let res = dereferencer.dereference_str(
+        "did:example:123456789abcdefghi?versionId=1#keys-1",
+        &options,
+    );
+    match res.content {
+        Some(Content::PublicKey(pk)) => assert_eq!(pk.subject(), "#keys-1"),
+        c => panic!("unexpected content {:?}", c),
+    }
+
+    let res =
+        dereferencer.dereference_str("did:example:123456789abcdefghi?versionId=1#files", &options);
+    match res.content {
+        Some(Content::Service(s)) => assert_eq!(s.kind(), "FileService"),
+        c => panic!("unexpected content {:?}", c),
+    }
+
+    let res = dereferencer.dereference_str("did:example:123456789abcdefghi#keys-1", &options);
+    assert_eq!(res.error(), Some(ResolutionError::NotFound));
+}
+
+#[test]
+fn did_dereference_1() {
+    let dereferencer = Dereferencer::new(service_resolver());
+    let options = DereferencingOptions::default();
+
+    let res = dereferencer.dereference_str(
+        "did:example:123456789abcdefghi?versionId=1&service=files&relativeRef=%2Fresume.pdf",
+        &options,
+    );
+    assert_eq!(
+        res.dereferencing_metadata.content_type,
+        Some("text/uri-list".to_string())
+    );
+    match res.content {
+        Some(Content::Url(url)) => assert_eq!(url, "https://example.com/resume.pdf"),
+        c => panic!("unexpected content {:?}", c),
+    }
+
+    let res = dereferencer.dereference_str(
+        "did:example:123456789abcdefghi?versionId=1&service=files&relativeRef=cv/resume.pdf#page-2",
+        &options,
+    );
+    match res.content {
+        Some(Content::Url(url)) => {
+            assert_eq!(url, "https://example.com/files/cv/resume.pdf#page-2")
+        }
+        c => panic!("unexpected content {:?}", c),
+    }
+
+    let res = dereferencer.dereference_str(
+        "did:example:123456789abcdefghi?versionId=1&service=files",
+        &options,
+    );
+    match res.content {
+        Some(Content::Url(url)) => assert_eq!(url, "https://example.com/files/"),
+        c => panic!("unexpected content {:?}", c),
+    }
+
+    let res = dereferencer.dereference_str(
+        "did:example:123456789abcdefghi?versionId=1&service=photos",
+        &options,
+    );
+    assert_eq!(res.error(), Some(ResolutionError::NotFound));
+}
+
+#[test]
+fn did_dereference_2() {
+    let dereferencer = Dereferencer::new(service_resolver());
+    let options = DereferencingOptions::default();
+
+    for s in &["https://example.org", "#keys-1", "did:example:"] {
+        let res = dereferencer.dereference_str(s, &options);
+        assert_eq!(res.error(), Some(ResolutionError::InvalidDidUrl));
+        assert!(res.content.is_none());
+    }
+
+    let res = dereferencer.dereference_str("did:example:pqrstuvwxyz0987654321#keys-1", &options);
+    assert_eq!(res.error(), Some(ResolutionError::NotFound));
+
+    let res = dereferencer.dereference_str("did:example:123456789abcdefghi?versionId=9", &options);
+    assert_eq!(res.error(), Some(ResolutionError::NotFound));
+}
+
+struct CountingResolver {
+    inner: InMemoryResolver,
+    calls: AtomicUsize,
+}
+
+impl CountingResolver {
+    fn new() -> Self {
+        CountingResolver {
+            inner: example_resolver(),
+            calls: AtomicUsize::new(0),
+        }
+    }
+
+    fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+}
+
+impl Resolver for CountingResolver {
+    fn resolve(&self, did: &Uri, options: &ResolutionOptions) -> ResolutionResult {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        self.inner.resolve(did, options)
+    }
+}
+
+#[test]
+fn did_resolve_cached_0() {
+    let cache = CachingResolver::new(CountingResolver::new());
+    let options = ResolutionOptions::default();
+
+    for _ in 0..3 {
+        let res = cache.resolve_str("did:example:123456789abcdefghi", &options);
+        assert!(res.document.is_some());
+    }
+    assert_eq!(cache.resolver().calls(), 1);
+    assert_eq!(
+        cache.stats(),
+        CacheStats {
+            hits: 2,
+            misses: 1,
+            entries: 1
+        }
+    );
+
+    let did = Uri::from_str("did:example:123456789abcdefghi").unwrap();
+    assert!(cache.invalidate(&did));
+    assert!(!cache.invalidate(&did));
+    cache.resolve(&did, &options);
+    assert_eq!(cache.resolver().calls(), 2);
+
+    // specific versions always go to the wrapped resolver
+    let versioned = ResolutionOptions {
+        version_id: Some("1".to_string()),
+        ..ResolutionOptions::default()
+    };
+    cache.resolve(&did, &versioned);
+    cache.resolve(&did, &versioned);
+    assert_eq!(cache.resolver().calls(), 4);
+
+    cache.reset_stats();
+    cache.clear();
+    assert_eq!(cache.stats(), CacheStats::default());
+}
+
+#[test]
+fn did_resolve_cached_1() {
+    let mut cache = CachingResolver::new(CountingResolver::new());
+    cache.set_not_found_ttl(Duration::from_millis(50));
+    let options = ResolutionOptions::default();
+
+    for _ in 0..2 {
+        let res = cache.resolve_str("did:example:pqrstuvwxyz0987654321", &options);
+        assert_eq!(res.error(), Some(ResolutionError::NotFound));
+    }
+    assert_eq!(cache.resolver().calls(), 1);
+
+    thread::sleep(Duration::from_millis(100));
+    cache.resolve_str("did:example:pqrstuvwxyz0987654321", &options);
+    assert_eq!(cache.resolver().calls(), 2);
+
+    // invalid DIDs and unsupported methods are never cached
+    cache.resolve_str("did:example:", &options);
+    cache.resolve_str("did:example:", &options);
+    assert_eq!(cache.resolver().calls(), 4);
+}
+
+#[test]
+fn did_resolve_cached_2() {
+    let mut cache = CachingResolver::new(CountingResolver::new());
+    cache.set_method_ttl("example", Duration::from_millis(50));
+    assert_eq!(cache.ttl("example"), Duration::from_millis(50));
+    assert_eq!(cache.ttl("git"), did::resolver::cache::DEFAULT_TTL);
+    let options = ResolutionOptions::default();
+
+    cache.resolve_str("did:example:123456789abcdefghi", &options);
+    cache.resolve_str("did:example:123456789abcdefghi", &options);
+    assert_eq!(cache.resolver().calls(), 1);
+
+    thread::sleep(Duration::from_millis(100));
+    cache.resolve_str("did:example:123456789abcdefghi", &options);
+    assert_eq!(cache.resolver().calls(), 2);
+
+    cache.set_method_ttl("example", Duration::from_secs(0));
+    cache.clear();
+    cache.resolve_str("did:example:123456789abcdefghi", &options);
+    cache.resolve_str("did:example:123456789abcdefghi", &options);
+    assert_eq!(cache.resolver().calls(), 4);
+}
+
+#[test]
+fn did_resolve_cached_3() {
+    let mut cache = CachingResolver::new(CountingResolver::new());
+    let json = ResolutionOptions {
+        accept: Some("application/did+json".to_string()),
+        ..ResolutionOptions::default()
+    };
+    let json_ld = ResolutionOptions {
+        accept: Some("application/did+ld+json".to_string()),
+        ..ResolutionOptions::default()
+    };
+
+    // each representation is cached on its own
+    cache.resolve_str("did:example:123456789abcdefghi", &json);
+    cache.resolve_str("did:example:123456789abcdefghi", &json_ld);
+    cache.resolve_str("did:example:123456789abcdefghi", &json);
+    assert_eq!(cache.resolver().calls(), 2);
+    assert_eq!(cache.stats().entries, 2);
+
+    let did = Uri::from_str("did:example:123456789abcdefghi").unwrap();
+    assert!(cache.invalidate(&did));
+    assert_eq!(cache.stats().entries, 0);
+
+    cache.set_method_ttl("example", Duration::from_millis(50));
+    cache.resolve_str("did:example:123456789abcdefghi", &json);
+    assert_eq!(cache.evict_expired(), 0);
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(cache.evict_expired(), 1);
+    assert_eq!(cache.stats().entries, 0);
+}
+
+#[test]
+fn did_resolve_cached_4() {
+    // a TTL past the end of time never expires instead of overflowing
+    let mut cache = CachingResolver::new(CountingResolver::new());
+    cache.set_default_ttl(Duration::MAX);
+    cache.set_not_found_ttl(Duration::from_secs(u64::MAX));
+    let options = ResolutionOptions::default();
+
+    for _ in 0..2 {
+        cache.resolve_str("did:example:123456789abcdefghi", &options);
+        cache.resolve_str("did:example:pqrstuvwxyz0987654321", &options);
+    }
+    assert_eq!(cache.resolver().calls(), 2);
+    assert_eq!(cache.evict_expired(), 0);
+    assert_eq!(cache.stats().entries, 2);
+}
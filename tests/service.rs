@@ -0,0 +1,329 @@
+extern crate did_doc as did;
+
+use did::{
+    fields::{select_didcomm, DidCommType, Endpoint, PublicKeyType, ServiceEndpoint},
+    DiagnosticKind, DidErrorKind, Document, Uri,
+};
+use std::str::FromStr;
+
+const MEDIATOR_KEY: &str = "did:key:z6LSgTMiVvjkfQd8CF1kWasYZKBqtAYf6h8TC3yDfjPgDbWQ#z6LSgTMiVvjkfQd8CF1kWasYZKBqtAYf6h8TC3yDfjPgDbWQ";
+
+fn doc() -> Document {
+    let jstr = r##"
+    {
+        "@context": "https://www.w3.org/ns/did/v1",
+        "id": "did:example:123",
+        "publicKey": [{
+            "id": "did:example:123#key-1",
+            "type": "Ed25519VerificationKey2018",
+            "controller": "did:example:123",
+            "publicKeyBase58": "FAe4sisG95oZ42w7buUn5qEE4TAnfTTFPiguZUHmhiF"
+        }],
+        "keyAgreement": [{
+            "id": "did:example:123#key-2",
+            "type": "X25519KeyAgreementKey2019",
+            "controller": "did:example:123",
+            "publicKeyBase58": "5nBYycvtZwuP6rdyywMbEiyN321YQ5xJK5FYBGk9WDje"
+        }]
+    }
+    "##;
+    Document::from_str(jstr).unwrap()
+}
+
+#[test]
+fn did_didcomm_0() {
+    let jstr = format!(
+        r##"{{
+            "id": "#didcomm-1",
+            "type": "DIDCommMessaging",
+            "serviceEndpoint": [{{
+                "uri": "https://example.com/path",
+                "accept": ["didcomm/v2"],
+                "routingKeys": ["{}"]
+            }}, "wss://example.com/ws"]
+        }}"##,
+        MEDIATOR_KEY
+    );
+    let service: ServiceEndpoint = serde_json::from_str(&jstr).unwrap();
+    let doc = doc();
+
+    let endpoints = service.didcomm();
+    assert_eq!(endpoints.len(), 2);
+    assert_eq!(endpoints[0].service_type, DidCommType::Messaging);
+    assert_eq!(endpoints[0].uri, "https://example.com/path");
+    assert_eq!(endpoints[0].accept, vec!["didcomm/v2"]);
+    assert_eq!(endpoints[1].uri, "wss://example.com/ws");
+    assert!(endpoints[1].routing_keys.is_empty());
+
+    let recipients = endpoints[0].resolve_recipient_keys(&doc).unwrap();
+    assert_eq!(recipients.len(), 1);
+    assert_eq!(recipients[0].subject(), "did:example:123#key-2");
+
+    let routing = endpoints[0].resolve_routing_keys(&doc).unwrap();
+    assert_eq!(routing[0].kind(), PublicKeyType::X25519KeyAgreementKey2020);
+    assert_eq!(
+        routing[0].data(),
+        "z6LSgTMiVvjkfQd8CF1kWasYZKBqtAYf6h8TC3yDfjPgDbWQ"
+    );
+}
+
+#[test]
+fn did_didcomm_1() {
+    let jstr = r##"{
+        "id": "did:example:123#did-communication",
+        "type": "did-communication",
+        "priority": 2,
+        "recipientKeys": ["#key-1"],
+        "routingKeys": ["did:example:mediator#key-1"],
+        "accept": ["didcomm/aip2;env=rfc19"],
+        "serviceEndpoint": "https://agent.example.com"
+    }"##;
+    let service: ServiceEndpoint = serde_json::from_str(jstr).unwrap();
+    let doc = doc();
+
+    let endpoints = service.didcomm();
+    assert_eq!(endpoints.len(), 1);
+    let endpoint = &endpoints[0];
+    assert_eq!(endpoint.service_type, DidCommType::DidCommunication);
+    assert_eq!(endpoint.priority, 2);
+    assert_eq!(endpoint.accept, vec!["didcomm/aip2;env=rfc19"]);
+
+    let recipients = endpoint.resolve_recipient_keys(&doc).unwrap();
+    assert_eq!(recipients[0].subject(), "did:example:123#key-1");
+    // another DID's key needs a resolver
+    assert!(endpoint.resolve_routing_keys(&doc).is_err());
+}
+
+#[test]
+fn did_didcomm_2() {
+    let mut doc = doc();
+    let mut service = ServiceEndpoint::new(
+        "#indy",
+        "IndyAgent",
+        Endpoint::from("https://indy.example.com"),
+    )
+    .unwrap();
+    service.extra.insert(
+        "recipientKeys".to_owned(),
+        serde_json::json!([
+            "FAe4sisG95oZ42w7buUn5qEE4TAnfTTFPiguZUHmhiF",
+            "H3C2AVvLMv6gmMNam3uVAjZpfkcJCwDwnZn6z3wXmqPV"
+        ]),
+    );
+    doc.add_service(service);
+    doc.add_service(
+        ServiceEndpoint::new(
+            "#hub",
+            "IdentityHub",
+            Endpoint::from("https://hub.example.com"),
+        )
+        .unwrap(),
+    );
+
+    assert!(doc.service()[1].didcomm().is_empty());
+    let endpoints = doc.service()[0].didcomm();
+    assert_eq!(endpoints[0].service_type, DidCommType::IndyAgent);
+    assert_eq!(endpoints[0].priority, 0);
+
+    let recipients = endpoints[0].resolve_recipient_keys(&doc).unwrap();
+    // the first verkey is key-1 of the document
+    assert_eq!(recipients[0].subject(), "did:example:123#key-1");
+    // the second is not in the document, so is taken as an Ed25519 key
+    assert_eq!(
+        recipients[1].subject(),
+        "did:example:123#H3C2AVvLMv6gmMNam3uVAjZpfkcJCwDwnZn6z3wXmqPV"
+    );
+    assert_eq!(
+        recipients[1].kind(),
+        PublicKeyType::Ed25519VerificationKey2018
+    );
+}
+
+fn services() -> Document {
+    let jstr = r##"
+    {
+        "@context": "https://www.w3.org/ns/did/v1",
+        "id": "did:example:123",
+        "service": [{
+            "id": "#agent",
+            "type": "did-communication",
+            "priority": 1,
+            "recipientKeys": ["#key-1"],
+            "serviceEndpoint": "https://agent.example.com"
+        }, {
+            "id": "did:example:123#files",
+            "type": "FileService",
+            "serviceEndpoint": "https://example.com/files/"
+        }, {
+            "id": "#didcomm",
+            "type": "DIDCommMessaging",
+            "serviceEndpoint": [
+                {"uri": "https://a.example.com", "accept": ["didcomm/v2"]},
+                {"uri": "https://b.example.com", "accept": ["didcomm/v2"]},
+                {"uri": "https://c.example.com", "accept": ["didcomm/v2"], "priority": 2}
+            ]
+        }, {
+            "id": "#backup",
+            "type": "FileService",
+            "serviceEndpoint": "https://backup.example.com/"
+        }]
+    }
+    "##;
+    Document::from_str(jstr).unwrap()
+}
+
+#[test]
+fn did_service_query_0() {
+    let doc = services();
+
+    let files = doc.services_by_type("FileService");
+    assert_eq!(files.len(), 2);
+    assert_eq!(files[1].subject(), "#backup");
+    assert!(doc.services_by_type("LinkedDomains").is_empty());
+
+    for id in &["#files", "did:example:123#files"] {
+        let service = doc.service_by_id(id).unwrap();
+        assert_eq!(service.endpoint(), "https://example.com/files/");
+    }
+    assert_eq!(
+        doc.service_by_id("#agent").unwrap().kind(),
+        "did-communication"
+    );
+    assert!(doc.service_by_id("did:example:456#files").is_none());
+    assert!(doc.service_by_id("#photos").is_none());
+}
+
+#[test]
+fn did_service_query_1() {
+    let doc = services();
+
+    let uris: Vec<_> = doc.didcomm_endpoints().into_iter().map(|e| e.uri).collect();
+    assert_eq!(
+        uris,
+        vec![
+            "https://a.example.com",
+            "https://b.example.com",
+            "https://agent.example.com",
+            "https://c.example.com",
+        ]
+    );
+
+    let endpoint = select_didcomm(&doc, &[]).unwrap();
+    assert_eq!(endpoint.uri, "https://a.example.com");
+    // the v1 service lists no accept, so takes anything
+    let endpoint = select_didcomm(&doc, &["didcomm/aip2;env=rfc19"]).unwrap();
+    assert_eq!(endpoint.uri, "https://agent.example.com");
+    assert_eq!(endpoint.id, "#agent");
+}
+
+#[test]
+fn did_service_url_0() {
+    let doc = services();
+
+    let uri = Uri::from_str("did:example:123?service=files&relativeRef=%2Fresume.pdf").unwrap();
+    assert_eq!(
+        doc.service_url(&uri).unwrap(),
+        "https://example.com/resume.pdf"
+    );
+
+    let uri = Uri::from_str("did:example:123?service=backup&relativeRef=cv/a.pdf#page-2").unwrap();
+    assert_eq!(
+        doc.service_url(&uri).unwrap(),
+        "https://backup.example.com/cv/a.pdf#page-2"
+    );
+
+    let error = |s: &str| {
+        let uri = Uri::from_str(s).unwrap();
+        doc.service_url(&uri).unwrap_err().kind()
+    };
+    assert_eq!(
+        error("did:example:123?service=photos"),
+        DidErrorKind::DanglingReference
+    );
+    assert_eq!(
+        error("did:example:456?service=files"),
+        DidErrorKind::DanglingReference
+    );
+    assert_eq!(error("#files"), DidErrorKind::DanglingReference);
+    assert_eq!(error("did:example:123#files"), DidErrorKind::InvalidDidUrl);
+}
+
+#[test]
+fn did_service_validate_0() {
+    assert!(services().validate().is_empty());
+
+    let jstr = r##"
+    {
+        "@context": "https://www.w3.org/ns/did/v1",
+        "id": "did:example:123",
+        "publicKey": [{
+            "id": "did:example:123#key-1",
+            "type": "Ed25519VerificationKey2018",
+            "controller": "did:example:123",
+            "publicKeyBase58": "FAe4sisG95oZ42w7buUn5qEE4TAnfTTFPiguZUHmhiF"
+        }],
+        "service": [{
+            "type": "FileService",
+            "serviceEndpoint": "https://example.com/files/"
+        }, {
+            "id": "#files",
+            "type": "",
+            "serviceEndpoint": "files/"
+        }, {
+            "id": "did:example:123#files",
+            "type": "FileService",
+            "serviceEndpoint": ["https://example.com/", {"uri": "example.com"}]
+        }, {
+            "id": "#key-1",
+            "type": "FileService",
+            "serviceEndpoint": []
+        }, {
+            "id": "did:example:#files",
+            "type": "FileService",
+            "serviceEndpoint": {"origins": ["https://example.com"]}
+        }, {
+            "id": "files",
+            "type": "FileService",
+            "serviceEndpoint": "https://example.com/files/"
+        }]
+    }
+    "##;
+    let doc = Document::from_str(jstr).unwrap();
+    let found: Vec<_> = doc
+        .validate()
+        .into_iter()
+        .map(|d| (d.kind, d.path))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (DiagnosticKind::InvalidServiceId, "service[0].id".to_owned()),
+            (
+                DiagnosticKind::MissingServiceType,
+                "service[1].type".to_owned()
+            ),
+            (
+                DiagnosticKind::InvalidServiceEndpoint,
+                "service[1].serviceEndpoint".to_owned()
+            ),
+            (DiagnosticKind::DuplicateId, "service[2].id".to_owned()),
+            (
+                DiagnosticKind::InvalidServiceEndpoint,
+                "service[2].serviceEndpoint".to_owned()
+            ),
+            (DiagnosticKind::DuplicateId, "service[3].id".to_owned()),
+            (
+                DiagnosticKind::InvalidServiceEndpoint,
+                "service[3].serviceEndpoint".to_owned()
+            ),
+            (DiagnosticKind::InvalidServiceId, "service[4].id".to_owned()),
+            (DiagnosticKind::InvalidServiceId, "service[5].id".to_owned()),
+        ]
+    );
+
+    // an id that is not a URI is kept as written
+    let service = &doc.service()[5];
+    assert_eq!(service.id(), "files");
+    assert!(service.subject().is_empty());
+    assert!(doc.to_string().contains(r#""id":"files""#));
+}
@@ -95,9 +95,29 @@ fn did_uri_2() {
     for s in &["did:", "https://example.org", "did:git", "did:sov"] {
         let res = Uri::from_str(s);
         match res {
-            Ok(_) => assert!(false),
+            Ok(_) => panic!("{} should not parse", s),
             Err(e) => assert_eq!(e.kind(), DidErrorKind::InvalidUri),
         };
     }
 }
 
+#[test]
+fn did_uri_3() {
+    let base = Uri::from_str("did:example:123456789abcdefghi").unwrap();
+
+    let rel = Uri::from_str("#keys-1").unwrap();
+    assert!(rel.is_relative());
+    assert!(!rel.is_empty());
+    assert_eq!(rel.fragment, Some("keys-1".to_string()));
+    assert_eq!(rel.to_string(), "#keys-1");
+
+    let abs = rel.to_absolute(&base);
+    assert!(!abs.is_relative());
+    assert_eq!(abs, "did:example:123456789abcdefghi#keys-1");
+
+    let did = Uri::from_str("did:example:pqrstuvwxyz0987654321#keys-1").unwrap();
+    assert!(!did.is_relative());
+    assert_eq!(did.to_absolute(&base), did);
+
+    assert!(!Uri::new().is_relative());
+}
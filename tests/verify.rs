@@ -0,0 +1,230 @@
+// most of these tests need one of the signature algorithm features
+#![cfg_attr(
+    not(all(
+        feature = "ed25519",
+        feature = "secp256k1",
+        feature = "p256",
+        feature = "rsa"
+    )),
+    allow(dead_code, unused_imports)
+)]
+
+extern crate did_doc as did;
+
+mod common;
+
+use common::key;
+use did::{
+    crypto::SignatureAlgorithm,
+    fields::{PublicKey, PublicKeyEncoding},
+    DidErrorKind,
+};
+use serde_json::json;
+
+const MESSAGE: &[u8] = b"did:example:123 says hello";
+
+const ED25519_SIGNATURE: &str = "81d275a375cacd6a3bb22fee0313015e91728ae05719749f188335eff27649d2c85b06aa783223ba2ba6c2ffb1c9d63f4697270d031d6888b80ac3ce3ffd230b";
+
+const RSA_SPKI: &str = "-----BEGIN PUBLIC KEY-----\nMIGfMA0GCSqGSIb3DQEBAQUAA4GNADCBiQKBgQC8QfrC0Yt0WrVN4yBFx44evlt8\nQ6VR6wKKYLLXeR2a0mlpdeReUf2NYVQi/WYMsIU70PxJJE9NVeVxLrBqDWozuybN\nUzfK+1wsYWhihe6Hx601VNWd0EHcYntZrvRLxjy0+gNR0DPAt/mV9xfCWUlIYVS+\n7XgFF9CSuiakWxPH/QIDAQAB\n-----END PUBLIC KEY-----\n";
+
+fn tampered(signature: &[u8]) -> Vec<u8> {
+    let mut signature = signature.to_vec();
+    signature[10] ^= 1;
+    signature
+}
+
+#[cfg(feature = "ed25519")]
+#[test]
+fn did_verify_ed25519_0() {
+    let keys = [
+        key(
+            "Ed25519VerificationKey2018",
+            "publicKeyBase58",
+            json!("FAe4sisG95oZ42w7buUn5qEE4TAnfTTFPiguZUHmhiF"),
+        ),
+        key(
+            "Ed25519VerificationKey2020",
+            "publicKeyMultibase",
+            json!("z6MkehRgf7yJbgaGfYsdoAsKdBPE3dj2CYhowQdcjqSJgvVd"),
+        ),
+        key(
+            "Multikey",
+            "publicKeyMultibase",
+            json!("z6MkehRgf7yJbgaGfYsdoAsKdBPE3dj2CYhowQdcjqSJgvVd"),
+        ),
+        key(
+            "JsonWebKey2020",
+            "publicKeyJwk",
+            json!({"kty": "OKP", "crv": "Ed25519", "x": "A6EHv_POEL4dcN0Y50vAmWfk1jCbpQ1fHdyGZBJVMbg"}),
+        ),
+    ];
+    let signature = hex::decode(ED25519_SIGNATURE).unwrap();
+
+    for pk in keys.iter() {
+        pk.verify(MESSAGE, &signature).unwrap();
+        assert_eq!(
+            pk.verify(b"something else", &signature).unwrap_err().kind(),
+            DidErrorKind::InvalidSignature
+        );
+        assert_eq!(
+            pk.verify(MESSAGE, &tampered(&signature))
+                .unwrap_err()
+                .kind(),
+            DidErrorKind::InvalidSignature
+        );
+        assert_eq!(
+            pk.verify(MESSAGE, &signature[..63]).unwrap_err().kind(),
+            DidErrorKind::InvalidSignature
+        );
+    }
+}
+
+#[cfg(feature = "secp256k1")]
+#[test]
+fn did_verify_secp256k1_0() {
+    let keys = [
+        key(
+            "EcdsaSecp256k1VerificationKey2019",
+            "publicKeyHex",
+            json!("032a5bbcb0eede528e6abe5f2ec50ad7887eb5677af383a460b05ee23bf892dfe5"),
+        ),
+        key(
+            "JsonWebKey2020",
+            "publicKeyJwk",
+            json!({
+                "kty": "EC",
+                "crv": "secp256k1",
+                "x": "Klu8sO7eUo5qvl8uxQrXiH61Z3rzg6RgsF7iO_iS3-U",
+                "y": "Usk3R1UO2oQEyLRzeGwA39j9HvS8Az81nM9bd71lbSE",
+                "alg": "ES256K"
+            }),
+        ),
+    ];
+    let der = hex::decode("304502210090f2fe8f18903186887b30554bb42b50d3c27bcaf89e8390cd7a41ef26585e7a02206d52740b126836f6f672498e606b536a76d40937dc0aa73bddb08ea3c7bd4c13").unwrap();
+    let raw = hex::decode("90f2fe8f18903186887b30554bb42b50d3c27bcaf89e8390cd7a41ef26585e7a6d52740b126836f6f672498e606b536a76d40937dc0aa73bddb08ea3c7bd4c13").unwrap();
+    // the same signature with s replaced by n - s
+    let high_s = hex::decode("90f2fe8f18903186887b30554bb42b50d3c27bcaf89e8390cd7a41ef26585e7a92ad8bf4ed97c909098db6719f94ac9443dad3aed33df8ffe221cfe90878f52e").unwrap();
+
+    for pk in keys.iter() {
+        pk.verify(MESSAGE, &der).unwrap();
+        pk.verify(MESSAGE, &raw).unwrap();
+        pk.verify_with(SignatureAlgorithm::Es256K, MESSAGE, &raw)
+            .unwrap();
+        assert_eq!(
+            pk.verify(MESSAGE, &tampered(&raw)).unwrap_err().kind(),
+            DidErrorKind::InvalidSignature
+        );
+        assert_eq!(
+            pk.verify(MESSAGE, &high_s).unwrap_err().kind(),
+            DidErrorKind::InvalidSignature
+        );
+        assert_eq!(
+            pk.verify_with(SignatureAlgorithm::Es256, MESSAGE, &raw)
+                .unwrap_err()
+                .kind(),
+            DidErrorKind::UnsupportedAlgorithm {
+                algorithm: "ES256".to_owned()
+            }
+        );
+    }
+}
+
+#[cfg(feature = "p256")]
+#[test]
+fn did_verify_p256_0() {
+    let jwk = key(
+        "EcdsaSecp256r1VerificationKey2019",
+        "publicKeyJwk",
+        json!({
+            "kty": "EC",
+            "crv": "P-256",
+            "x": "02AzL62byDr6_0p0DeilFr8bj7P942D_HQOXnB-UPuI",
+            "y": "6KZgB_0nawJxJlxtsJLEoMXrjEX9xDZQLIoJX11XRfI"
+        }),
+    );
+    // a Multikey only says what it is through its multicodec prefix
+    let multibase = jwk.to_encoding(PublicKeyEncoding::Multibase).unwrap();
+    let multikey = key("Multikey", "publicKeyMultibase", json!(multibase.data()));
+    let der = hex::decode("30460221008e25f038b793e432178c5c9c4863ac5342293fdd7f34cdc146cc13b12f47906b02210091962fc8c20208ac161eef494778f61c8ce6589aa0cc6ba166fc09ed9f09335e").unwrap();
+    let raw = hex::decode("8e25f038b793e432178c5c9c4863ac5342293fdd7f34cdc146cc13b12f47906b91962fc8c20208ac161eef494778f61c8ce6589aa0cc6ba166fc09ed9f09335e").unwrap();
+
+    for pk in [jwk, multikey].iter() {
+        pk.verify(MESSAGE, &der).unwrap();
+        pk.verify(MESSAGE, &raw).unwrap();
+        assert_eq!(
+            pk.verify(MESSAGE, &tampered(&der)).unwrap_err().kind(),
+            DidErrorKind::InvalidSignature
+        );
+    }
+}
+
+#[cfg(feature = "rsa")]
+#[test]
+fn did_verify_rsa_0() {
+    let rs256 = base64_decode("PRtpqwDvIMOJWdVvwoTZNbXCcW3xGNhfzwnc1rqxaZFwP2In0JIoupCOxMBwpxtj5Hu0iHUimJjefOI8xQJbqHGRUz5q+HVyHy154dboUTVl/XN+MGHS6MdPYD6sESsn8i+PT2MAIvEMYAaQV5cYXy4a8NhumnKgaREhVHup4M0=");
+    let ps256 = base64_decode("TX9gq4sbTN9F/hpCxp8o7FohGxws2cq2GxZkaU80Y6hNJDQOnDyTRjqf4uOln3u4NefimPRVjoEcfZdRxX985Jqx/peK2eJXL+2LygEVJupYtIkQrHb5gksezrZV8lTXq+h+TVwO5h6QGhKTobki5P0KyE55R5NVxC+hrGvhnV0=");
+
+    let pk = key("RsaVerificationKey2018", "publicKeyPem", json!(RSA_SPKI));
+    pk.verify(MESSAGE, &rs256).unwrap();
+    pk.verify_with(SignatureAlgorithm::Rs256, MESSAGE, &rs256)
+        .unwrap();
+    pk.verify_with(SignatureAlgorithm::Ps256, MESSAGE, &ps256)
+        .unwrap();
+    assert_eq!(
+        pk.verify(MESSAGE, &ps256).unwrap_err().kind(),
+        DidErrorKind::InvalidSignature
+    );
+    assert_eq!(
+        pk.verify_with(SignatureAlgorithm::Ps256, MESSAGE, &rs256)
+            .unwrap_err()
+            .kind(),
+        DidErrorKind::InvalidSignature
+    );
+
+    // JWK keys name their algorithm
+    let jwk = pk.to_encoding(PublicKeyEncoding::Jwk).unwrap();
+    let mut value = serde_json::to_value(&jwk).unwrap();
+    value["type"] = json!("JsonWebKey2020");
+    value["publicKeyJwk"]["alg"] = json!("PS256");
+    let jwk: PublicKey = serde_json::from_value(value).unwrap();
+    jwk.verify(MESSAGE, &ps256).unwrap();
+    assert!(jwk.verify(MESSAGE, &rs256).is_err());
+}
+
+#[cfg(feature = "rsa")]
+fn base64_decode(s: &str) -> Vec<u8> {
+    use base64::Engine;
+    base64::engine::general_purpose::STANDARD.decode(s).unwrap()
+}
+
+#[test]
+fn did_verify_unsupported_0() {
+    let pk = key(
+        "Bls12381G2Key2020",
+        "publicKeyBase58",
+        json!("FAe4sisG95oZ42w7buUn5qEE4TAnfTTFPiguZUHmhiF"),
+    );
+    assert_eq!(
+        pk.verify(MESSAGE, &[0; 64]).unwrap_err().kind(),
+        DidErrorKind::UnsupportedAlgorithm {
+            algorithm: "Bls12381G2Key2020".to_owned()
+        }
+    );
+
+    let pk = key(
+        "Ed25519VerificationKey2018",
+        "publicKeyBase58",
+        json!("FAe4sisG95oZ42w7buUn5qEE4TAnfTTFPiguZUHmhiF"),
+    );
+    let result = pk.verify(MESSAGE, &hex::decode(ED25519_SIGNATURE).unwrap());
+    if cfg!(feature = "ed25519") {
+        assert!(result.is_ok());
+    } else {
+        assert_eq!(
+            result.unwrap_err().kind(),
+            DidErrorKind::UnsupportedAlgorithm {
+                algorithm: "EdDSA".to_owned()
+            }
+        );
+    }
+}